@@ -0,0 +1,109 @@
+//! The canonical encoding of signed coordinates as field integers.
+//!
+//! Latitudes and longitudes are negative in the southern and western hemispheres, but field
+//! elements are not signed: casting a negative integer with `as u64` wraps it to a value near
+//! 2^64, far outside the range the distance circuit accepts. Coordinates are instead encoded in
+//! offset binary, as millionths of a degree offset by 90 degrees of latitude and 180 degrees of
+//! longitude, so every coordinate on Earth is a non-negative integer below 2^32 and the
+//! encoding preserves order. The prover's `distance::to_fixed_point`, the attendance pallet and
+//! [`haversine`](crate::haversine) all use these functions.
+
+/// Fixed-point coordinate units per degree, a resolution of about 11cm
+pub const UNITS_PER_DEGREE: i64 = 1_000_000;
+
+/// The offset of an encoded latitude: -90 degrees encodes as 0
+pub const LATITUDE_OFFSET: i64 = 90 * UNITS_PER_DEGREE;
+
+/// The offset of an encoded longitude: -180 degrees encodes as 0
+pub const LONGITUDE_OFFSET: i64 = 180 * UNITS_PER_DEGREE;
+
+/// Encodes signed coordinates in offset binary.
+///
+/// # Arguments
+/// * `latitude` - Millionths of a degree, -90 to 90 degrees
+/// * `longitude` - Millionths of a degree, -180 to 180 degrees
+///
+/// # Returns
+/// `(latitude, longitude)` in fixed-point units, or `None` if either is out of range
+pub fn encode(latitude: i64, longitude: i64) -> Option<(u64, u64)> {
+    // `abs` would overflow on `i64::MIN`, reached by saturating casts of huge floats
+    if !(-LATITUDE_OFFSET..=LATITUDE_OFFSET).contains(&latitude)
+        || !(-LONGITUDE_OFFSET..=LONGITUDE_OFFSET).contains(&longitude)
+    {
+        return None;
+    }
+    Some((
+        (latitude + LATITUDE_OFFSET) as u64,
+        (longitude + LONGITUDE_OFFSET) as u64,
+    ))
+}
+
+/// Decodes coordinates encoded by [`encode`], giving signed millionths of a degree.
+///
+/// # Arguments
+/// * `coordinates` - `(latitude, longitude)` in fixed-point units
+///
+/// # Returns
+/// The signed `(latitude, longitude)`, or `None` if a latitude exceeds 180 or a longitude
+/// 360 degrees in fixed-point units, e.g. a negative value cast with `as u64`
+pub fn decode((latitude, longitude): (u64, u64)) -> Option<(i64, i64)> {
+    if latitude > 2 * LATITUDE_OFFSET as u64 || longitude > 2 * LONGITUDE_OFFSET as u64 {
+        return None;
+    }
+    Some((
+        latitude as i64 - LATITUDE_OFFSET,
+        longitude as i64 - LONGITUDE_OFFSET,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_coordinates() {
+        // Sydney, south and east, and Rio de Janeiro, south and west
+        let sydney = (-33_868_800, 151_209_300);
+        let rio = (-22_906_800, -43_172_900);
+        assert_eq!(encode(sydney.0, sydney.1), Some((56_131_200, 331_209_300)));
+        assert_eq!(encode(rio.0, rio.1), Some((67_093_200, 136_827_100)));
+        assert_eq!(decode((56_131_200, 331_209_300)), Some(sydney));
+        assert_eq!(decode((67_093_200, 136_827_100)), Some(rio));
+
+        // The corners of the range, all below 2^32
+        assert_eq!(encode(-LATITUDE_OFFSET, -LONGITUDE_OFFSET), Some((0, 0)));
+        assert_eq!(encode(0, 0), Some((90_000_000, 180_000_000)));
+        assert_eq!(
+            encode(LATITUDE_OFFSET, LONGITUDE_OFFSET),
+            Some((180_000_000, 360_000_000))
+        );
+        assert!(2 * LONGITUDE_OFFSET < 1 << 32);
+    }
+
+    #[test]
+    fn test_encoding_preserves_order() {
+        let latitudes = [-90_000_000, -1, 0, 1, 90_000_000];
+        let encoded: Vec<u64> = latitudes
+            .iter()
+            .map(|latitude| encode(*latitude, 0).expect("in range").0)
+            .collect();
+        assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+        // One unit apart on either side of the equator, as without the offset
+        assert_eq!(encoded[3] - encoded[1], 2);
+    }
+
+    #[test]
+    fn test_out_of_range_coordinates() {
+        assert_eq!(encode(LATITUDE_OFFSET + 1, 0), None);
+        assert_eq!(encode(-LATITUDE_OFFSET - 1, 0), None);
+        assert_eq!(encode(0, LONGITUDE_OFFSET + 1), None);
+        assert_eq!(encode(0, -LONGITUDE_OFFSET - 1), None);
+        assert_eq!(encode(i64::MIN, 0), None);
+        assert_eq!(encode(0, i64::MIN), None);
+        assert_eq!(encode(i64::MAX, i64::MAX), None);
+        assert_eq!(decode((180_000_001, 0)), None);
+        assert_eq!(decode((0, 360_000_001)), None);
+        // A negative coordinate cast naively is rejected rather than decoded
+        assert_eq!(decode(((-33_868_800i64) as u64, 0)), None);
+    }
+}
@@ -0,0 +1,141 @@
+//! The encoding of a geohash as public inputs of the geohash prefix circuit.
+//!
+//! The prover builds its inputs from a string and the attendance pallet from the challenge's
+//! bytes; both go through [`prefix_public_inputs`], so a proof made for a geohash always
+//! verifies against the same geohash on chain. Hidden challenges are verified against a
+//! commitment instead, see [`committed_prefix_public_inputs`]. The crate is `no_std` so the
+//! runtime can use it, which is also why it holds the other geohash functions the runtime shares
+//! with clients, such as the geohash validation in [`is_geohash`], the cells around a challenge
+//! in [`neighbors`], the integer great-circle distance in [`haversine`], the compact storage
+//! encoding in [`packed`] and the encoding of signed coordinates in [`coordinates`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod coordinates;
+pub mod haversine;
+pub mod neighbors;
+pub mod packed;
+
+use alloc::vec::Vec;
+use ark_ff::PrimeField;
+
+/// The geohash alphabet, in the order of the 5-bit values it encodes
+pub const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// The longest geohash, whose cells are a few centimetres across
+pub const MAX_GEOHASH_LENGTH: usize = 12;
+
+/// Whether every byte of `geohash` is a character of the geohash alphabet, [`BASE32`].
+///
+/// Lengths are checked by callers, against [`MAX_GEOHASH_LENGTH`] or a stricter bound.
+///
+/// # Arguments
+/// * `geohash` - The geohash bytes, e.g. `b"gcpv"`
+///
+/// # Returns
+/// `false` if `geohash` has a character outside the alphabet, such as an upper-case one
+pub fn is_geohash(geohash: &[u8]) -> bool {
+    geohash.iter().all(|c| BASE32.contains(c))
+}
+
+/// Encodes geohash bytes as field elements, one per byte and in order, each the byte's value.
+///
+/// `oracle::location_to_field_inputs` computes the same values as integers.
+///
+/// # Arguments
+/// * `geohash` - The geohash bytes, e.g. `b"gcpv"`
+///
+/// # Returns
+/// One field element per byte of `geohash`
+pub fn geohash_to_public_inputs<F: PrimeField>(geohash: &[u8]) -> Vec<F> {
+    geohash.iter().map(|c| F::from(*c as u64)).collect()
+}
+
+/// The public inputs of the prefix circuit, in order: the challenge bytes encoded by
+/// [`geohash_to_public_inputs`], then the precision of the proven location, i.e. its length.
+///
+/// # Arguments
+/// * `challenge` - The challenge geohash bytes
+/// * `precision` - The length of the location geohash the proof is about
+///
+/// # Returns
+/// One field element per byte of `challenge`, followed by `precision`
+pub fn prefix_public_inputs<F: PrimeField>(challenge: &[u8], precision: u32) -> Vec<F> {
+    let mut inputs = geohash_to_public_inputs(challenge);
+    inputs.push(F::from(precision as u64));
+    inputs
+}
+
+/// The public inputs of the committed prefix circuit, in order: the commitment to the
+/// challenge, then the precision of the proven location. The challenge itself stays a witness,
+/// so a hidden challenge is verified without its geohash on chain.
+///
+/// # Arguments
+/// * `commitment` - The commitment to the challenge, as computed by the prover
+/// * `precision` - The length of the location geohash the proof is about
+///
+/// # Returns
+/// The commitment followed by `precision`
+pub fn committed_prefix_public_inputs<F: PrimeField>(commitment: F, precision: u32) -> Vec<F> {
+    alloc::vec![commitment, F::from(precision as u64)]
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+
+    use super::*;
+
+    #[test]
+    fn test_is_geohash() {
+        assert!(is_geohash(b"gcpvj0du6"));
+        assert!(is_geohash(BASE32));
+        assert!(is_geohash(b""));
+        // Letters left out of the alphabet, upper case and other characters
+        for geohash in ["gcpa", "gcpi", "gcpl", "gcpo", "GCPV", "gc v", "gc-v"] {
+            assert!(
+                !is_geohash(geohash.as_bytes()),
+                "{} is not a geohash",
+                geohash
+            );
+        }
+    }
+
+    #[test]
+    fn test_geohash_to_public_inputs() {
+        assert_eq!(
+            geohash_to_public_inputs::<Fr>(b"gcpv"),
+            vec![Fr::from(103), Fr::from(99), Fr::from(112), Fr::from(118)]
+        );
+        assert!(geohash_to_public_inputs::<Fr>(b"").is_empty());
+    }
+
+    #[test]
+    fn test_prefix_public_inputs() {
+        let mut expected = geohash_to_public_inputs::<Fr>(b"gcpv");
+        expected.push(Fr::from(8));
+        assert_eq!(prefix_public_inputs::<Fr>(b"gcpv", 8), expected);
+    }
+
+    #[test]
+    fn test_committed_prefix_public_inputs() {
+        assert_eq!(
+            committed_prefix_public_inputs::<Fr>(Fr::from(42), 8),
+            vec![Fr::from(42), Fr::from(8)]
+        );
+    }
+
+    #[test]
+    fn test_one_input_per_byte() {
+        for geohash in ["0", "u4pruydqqvj", "zzzzzzzzzzzz"] {
+            let inputs = geohash_to_public_inputs::<Fr>(geohash.as_bytes());
+            assert_eq!(inputs.len(), geohash.len());
+            assert!(geohash
+                .bytes()
+                .zip(inputs)
+                .all(|(c, input)| Fr::from(c as u64) == input));
+        }
+    }
+}
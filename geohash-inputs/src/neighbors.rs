@@ -0,0 +1,171 @@
+//! The cells adjacent to a geohash cell.
+//!
+//! Front-ends use them to tell attendees they are close to a challenge area, and the runtime
+//! serves them so clients need no geohash logic of their own. Adjacent cells are found with the
+//! usual lookup tables: the last character is replaced by its neighbour in the requested
+//! direction, and the prefix is moved too when the cell lies on the border of its parent.
+
+use alloc::vec::Vec;
+
+use crate::BASE32;
+
+/// A direction on the geohash grid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Towards the north pole, increasing latitude
+    North,
+    /// Increasing longitude
+    East,
+    /// Towards the south pole, decreasing latitude
+    South,
+    /// Decreasing longitude
+    West,
+}
+
+impl Direction {
+    /// The neighbour lookup table and the border characters of this direction, for a
+    /// geohash of even length
+    fn tables(self) -> (&'static [u8; 32], &'static [u8]) {
+        match self {
+            Direction::North => (b"p0r21436x8zb9dcf5h7kjnmqesgutwvy", b"prxz"),
+            Direction::East => (b"bc01fg45238967deuvhjyznpkmstqrwx", b"bcfguvyz"),
+            Direction::South => (b"14365h7k9dcfesgujnmqp0r2twvyx8zb", b"028b"),
+            Direction::West => (b"238967debc01fg45kmstqrwxuvhjyznp", b"0145hjnp"),
+        }
+    }
+
+    /// The direction whose even-length tables apply to an odd-length geohash, whose cells
+    /// are split the other way round
+    fn transposed(self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::North,
+            Direction::South => Direction::West,
+            Direction::West => Direction::South,
+        }
+    }
+}
+
+/// The cell of the same precision adjacent to `geohash` in `direction`.
+///
+/// Longitude wraps around the antimeridian, but there is no cell beyond a pole.
+///
+/// # Arguments
+/// * `geohash` - The geohash bytes, e.g. `b"gcpv"`
+/// * `direction` - Which side of the cell to step to
+///
+/// # Returns
+/// The adjacent geohash, or `None` past a pole or if `geohash` is empty or not a geohash
+pub fn adjacent(geohash: &[u8], direction: Direction) -> Option<Vec<u8>> {
+    if !crate::is_geohash(geohash) {
+        return None;
+    }
+    let (&last, prefix) = geohash.split_last()?;
+    let (table, border) = if geohash.len() % 2 == 0 {
+        direction.tables()
+    } else {
+        direction.transposed().tables()
+    };
+    let index = table.iter().position(|c| *c == last)?;
+
+    let mut cell = if !border.contains(&last) {
+        prefix.to_vec()
+    } else if !prefix.is_empty() {
+        adjacent(prefix, direction)?
+    } else if matches!(direction, Direction::North | Direction::South) {
+        return None;
+    } else {
+        Vec::new()
+    };
+    cell.push(BASE32[index]);
+    Some(cell)
+}
+
+/// The cells surrounding `geohash`, at the same precision.
+///
+/// The cells are in clockwise order from the north: north, northeast, east, southeast,
+/// south, southwest, west and northwest. Cells beyond a pole do not exist and are left out,
+/// so a cell touching a pole has five neighbours.
+///
+/// # Arguments
+/// * `geohash` - The geohash bytes, e.g. `b"gcpv"`
+///
+/// # Returns
+/// The neighbouring geohashes, none if `geohash` is empty or not a geohash
+pub fn neighbors(geohash: &[u8]) -> Vec<Vec<u8>> {
+    let step = |cell: &Option<Vec<u8>>, direction| {
+        cell.as_deref().and_then(|cell| adjacent(cell, direction))
+    };
+
+    let north = adjacent(geohash, Direction::North);
+    let south = adjacent(geohash, Direction::South);
+    [
+        north.clone(),
+        step(&north, Direction::East),
+        adjacent(geohash, Direction::East),
+        step(&south, Direction::East),
+        south.clone(),
+        step(&south, Direction::West),
+        adjacent(geohash, Direction::West),
+        step(&north, Direction::West),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(geohashes: &[&str]) -> Vec<Vec<u8>> {
+        geohashes.iter().map(|g| g.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_neighbors() {
+        assert_eq!(
+            neighbors(b"dqcjq"),
+            cells(&["dqcjw", "dqcjx", "dqcjr", "dqcjp", "dqcjn", "dqcjj", "dqcjm", "dqcjt"])
+        );
+        // Crossing into the neighbouring parent cells
+        assert_eq!(
+            neighbors(b"gcpv"),
+            cells(&["gcpy", "u10n", "u10j", "u10h", "gcpu", "gcps", "gcpt", "gcpw"])
+        );
+        assert_eq!(
+            neighbors(b"u4pruydqqvj"),
+            cells(&[
+                "u4pruydqqvm",
+                "u4pruydqqvq",
+                "u4pruydqqvn",
+                "u4pruydqquy",
+                "u4pruydqquv",
+                "u4pruydqquu",
+                "u4pruydqqvh",
+                "u4pruydqqvk",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_longitude_wraps_and_poles_end() {
+        // At the north pole and across the antimeridian
+        assert_eq!(
+            neighbors(b"zzzz"),
+            cells(&["bpbp", "bpbn", "zzzy", "zzzw", "zzzx"])
+        );
+        assert_eq!(adjacent(b"z", Direction::East), Some(b"b".to_vec()));
+        assert_eq!(adjacent(b"b", Direction::North), None);
+        // At the south pole
+        assert_eq!(neighbors(b"0"), cells(&["2", "3", "1", "p", "r"]));
+    }
+
+    #[test]
+    fn test_invalid_geohash_has_no_neighbors() {
+        assert!(neighbors(b"").is_empty());
+        assert!(neighbors(b"gcpa").is_empty());
+        assert_eq!(adjacent(b"", Direction::North), None);
+        assert_eq!(adjacent(b"gcpa", Direction::East), None);
+    }
+}
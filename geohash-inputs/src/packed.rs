@@ -0,0 +1,122 @@
+//! A compact encoding of geohashes, 5 bits per character.
+//!
+//! A geohash character is one of 32, so a byte per character wastes 3 bits. Packed, the
+//! characters' 5-bit values are concatenated most significant bit first, followed by a single
+//! set bit marking the end and zero bits up to the next byte. The end marker makes the length
+//! recoverable and the encoding unambiguous, and keeps a packed geohash no longer than the
+//! geohash itself: a 12-character geohash packs into 8 bytes.
+
+use alloc::vec::Vec;
+
+use crate::BASE32;
+
+/// Bits per geohash character
+const BITS_PER_CHARACTER: usize = 5;
+
+/// The length in bytes of a packed geohash of `length` characters
+pub fn packed_len(length: usize) -> usize {
+    (length * BITS_PER_CHARACTER) / 8 + 1
+}
+
+/// Packs a geohash at 5 bits per character.
+///
+/// # Arguments
+/// * `geohash` - The geohash bytes, e.g. `b"gcpv"`
+///
+/// # Returns
+/// The packed geohash, [`packed_len`] bytes long, or `None` if `geohash` has a character
+/// outside the geohash alphabet
+pub fn pack(geohash: &[u8]) -> Option<Vec<u8>> {
+    let mut packed = Vec::with_capacity(packed_len(geohash.len()));
+    let (mut buffer, mut bits) = (0u16, 0);
+    for c in geohash {
+        let value = BASE32.iter().position(|b| b == c)? as u16;
+        buffer = (buffer << BITS_PER_CHARACTER) | value;
+        bits += BITS_PER_CHARACTER;
+        if bits >= 8 {
+            bits -= 8;
+            packed.push((buffer >> bits) as u8);
+        }
+    }
+    // The end marker, then padding
+    buffer = (buffer << 1) | 1;
+    bits += 1;
+    packed.push((buffer << (8 - bits)) as u8);
+    Some(packed)
+}
+
+/// Unpacks a geohash packed by [`pack`].
+///
+/// # Arguments
+/// * `packed` - The packed geohash
+///
+/// # Returns
+/// The geohash bytes, or `None` if `packed` is not a packed geohash
+pub fn unpack(packed: &[u8]) -> Option<Vec<u8>> {
+    let last = *packed.last()?;
+    if last == 0 {
+        return None;
+    }
+    let bits = packed.len() * 8 - last.trailing_zeros() as usize - 1;
+    if bits % BITS_PER_CHARACTER != 0 {
+        return None;
+    }
+
+    let bit = |index: usize| (packed[index / 8] >> (7 - index % 8)) & 1;
+    Some(
+        (0..bits / BITS_PER_CHARACTER)
+            .map(|character| {
+                let start = character * BITS_PER_CHARACTER;
+                let value = (start..start + BITS_PER_CHARACTER)
+                    .fold(0, |value, index| (value << 1) | bit(index));
+                BASE32[value as usize]
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_round_trips() {
+        for geohash in [
+            "",
+            "0",
+            "z",
+            "gcpv",
+            "u4pruydqqvj",
+            "zzzzzzzzzzzz",
+            "000000000000",
+        ] {
+            let packed = pack(geohash.as_bytes()).expect("valid geohash");
+            assert_eq!(packed.len(), packed_len(geohash.len()));
+            assert_eq!(unpack(&packed), Some(geohash.as_bytes().to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_pack_layout() {
+        // g = 15 = 01111, c = 11 = 01011, then the end marker
+        assert_eq!(pack(b"gc"), Some(vec![0b0111_1010, 0b1110_0000]));
+        assert_eq!(pack(b""), Some(vec![0b1000_0000]));
+    }
+
+    #[test]
+    fn test_packed_is_never_longer() {
+        for length in 1..=32 {
+            assert!(packed_len(length) <= length);
+        }
+        assert_eq!(packed_len(12), 8);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert_eq!(pack(b"gcpa"), None);
+        assert_eq!(unpack(&[]), None);
+        assert_eq!(unpack(&[0b0111_1010, 0]), None);
+        // 11 data bits, not a whole number of characters
+        assert_eq!(unpack(&[0b0111_1010, 0b1111_0000]), None);
+    }
+}
@@ -0,0 +1,249 @@
+//! A prefix circuit for hidden challenges, whose public input is a commitment to the
+//! challenge instead of the challenge geohash.
+//!
+//! The challenge creator picks a random salt and publishes only [`commit`] of the challenge and
+//! the salt, a Poseidon hash of the salt followed by the challenge bytes. Attendees given the
+//! challenge and the salt prove that their location starts with the committed challenge by
+//! opening the commitment inside the circuit, so neither the challenge nor the location is
+//! revealed. The salt keeps the small space of geohashes from being searched for the committed
+//! one. The public inputs are, in order, the commitment and the precision of the location, see
+//! `geohash_inputs::committed_prefix_public_inputs`; the challenge length is fixed by the
+//! circuit's shape, like the location's.
+
+use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::{
+    find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge,
+};
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge, FieldBasedCryptographicSponge};
+use ark_ff::PrimeField;
+use ark_groth16::Groth16;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use rand::CryptoRng;
+use rand::RngCore;
+
+/// Full rounds of the Poseidon permutation, for a width of 3
+const FULL_ROUNDS: usize = 8;
+
+/// Partial rounds of the Poseidon permutation, for a width of 3 over a 254-bit field
+const PARTIAL_ROUNDS: usize = 57;
+
+/// Exponent of the Poseidon S-box
+const ALPHA: u64 = 5;
+
+/// Field elements absorbed per Poseidon permutation
+const RATE: usize = 2;
+
+/// The Poseidon parameters of the commitment. They are derived deterministically, so the
+/// challenge creator and every prover use the same ones.
+pub fn poseidon_config<F: PrimeField>() -> PoseidonConfig<F> {
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        RATE,
+        FULL_ROUNDS as u64,
+        PARTIAL_ROUNDS as u64,
+        0,
+    );
+    PoseidonConfig::new(FULL_ROUNDS, PARTIAL_ROUNDS, ALPHA, mds, ark, RATE, 1)
+}
+
+/// The Poseidon hash of `salt` followed by the encoded challenge bytes.
+fn hash_commitment<F: PrimeField + Absorb>(
+    config: &PoseidonConfig<F>,
+    challenge: &[F],
+    salt: F,
+) -> F {
+    let mut sponge = PoseidonSponge::new(config);
+    sponge.absorb(&salt);
+    for byte in challenge {
+        sponge.absorb(byte);
+    }
+    sponge.squeeze_native_field_elements(1)[0]
+}
+
+/// Commits to a challenge geohash, to publish in place of the geohash.
+///
+/// # Arguments
+/// * `challenge` - The challenge geohash to hide
+/// * `salt` - A random field element, kept secret with the challenge
+pub fn commit(challenge: &str, salt: Fr) -> Fr {
+    let challenge = geohash_inputs::geohash_to_public_inputs(challenge.as_bytes());
+    hash_commitment(&poseidon_config(), &challenge, salt)
+}
+
+/// The 32 bytes of a commitment as the attendance pallet takes it, its uncompressed
+/// little-endian serialization.
+pub fn commitment_bytes(commitment: Fr) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    commitment
+        .serialize_uncompressed(&mut bytes[..])
+        .expect("a field element fits in 32 bytes");
+    bytes
+}
+
+#[derive(Clone)]
+pub struct CommittedPrefixCircuit<F: PrimeField> {
+    pub challenge: Option<Vec<F>>,
+    pub salt: Option<F>,
+    pub location: Option<Vec<F>>,
+    pub config: PoseidonConfig<F>,
+}
+
+impl CommittedPrefixCircuit<Fr> {
+    pub fn new_from_str(challenge: &str, salt: Fr, location: &str) -> Self {
+        Self {
+            challenge: Some(geohash_inputs::geohash_to_public_inputs(
+                challenge.as_bytes(),
+            )),
+            salt: Some(salt),
+            location: Some(geohash_inputs::geohash_to_public_inputs(
+                location.as_bytes(),
+            )),
+            config: poseidon_config(),
+        }
+    }
+}
+
+pub fn setup_committed_groth16<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    circuit: CommittedPrefixCircuit<Fr>,
+) -> Result<
+    (
+        ark_groth16::ProvingKey<Bn254>,
+        ark_groth16::VerifyingKey<Bn254>,
+    ),
+    SynthesisError,
+> {
+    Groth16::<Bn254>::circuit_specific_setup(circuit, rng)
+}
+
+pub fn create_committed_proof<R: RngCore + CryptoRng>(
+    pk: &ark_groth16::ProvingKey<Bn254>,
+    circuit: CommittedPrefixCircuit<Fr>,
+    rng: &mut R,
+) -> Result<ark_groth16::Proof<Bn254>, SynthesisError> {
+    Groth16::<Bn254>::prove(pk, circuit, rng)
+}
+
+/// Verifies a proof that a location of `precision` characters starts with the challenge
+/// behind `commitment`.
+pub fn verify_committed_proof(
+    vk: &ark_groth16::VerifyingKey<Bn254>,
+    commitment: Fr,
+    precision: usize,
+    proof: &ark_groth16::Proof<Bn254>,
+) -> Result<bool, SynthesisError> {
+    Groth16::<Bn254>::verify(vk, &public_inputs(commitment, precision), proof)
+}
+
+/// The public inputs of the committed prefix circuit, encoded as the attendance pallet
+/// encodes them: the commitment, then the precision.
+pub fn public_inputs(commitment: Fr, precision: usize) -> Vec<Fr> {
+    geohash_inputs::committed_prefix_public_inputs(commitment, precision as u32)
+}
+
+impl<F: PrimeField + Absorb> ConstraintSynthesizer<F> for CommittedPrefixCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let challenge = self.challenge.ok_or(SynthesisError::AssignmentMissing)?;
+        let salt = self.salt.ok_or(SynthesisError::AssignmentMissing)?;
+        let location = self.location.ok_or(SynthesisError::AssignmentMissing)?;
+
+        if challenge.is_empty() || location.is_empty() || challenge.len() > location.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        // Public
+        let commitment = FpVar::new_input(cs.clone(), || {
+            Ok(hash_commitment(&self.config, &challenge, salt))
+        })?;
+        let precision = F::from(location.len() as u64);
+        FpVar::new_input(cs.clone(), || Ok(precision))?
+            .enforce_equal(&FpVar::constant(precision))?;
+
+        // Witness
+        let salt = FpVar::new_witness(cs.clone(), || Ok(salt))?;
+        let challenge_vars = challenge
+            .iter()
+            .map(|&val| FpVar::new_witness(cs.clone(), || Ok(val)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let location_vars = location
+            .iter()
+            .map(|&val| FpVar::new_witness(cs.clone(), || Ok(val)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The witnessed challenge is the committed one
+        let mut sponge = PoseidonSpongeVar::new(cs.clone(), &self.config);
+        sponge.absorb(&salt)?;
+        for byte in &challenge_vars {
+            sponge.absorb(byte)?;
+        }
+        sponge.squeeze_field_elements(1)?[0].enforce_equal(&commitment)?;
+
+        for (challenge_var, location_var) in challenge_vars.iter().zip(location_vars.iter()) {
+            location_var.enforce_equal(challenge_var)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_serialize::CanonicalDeserialize;
+    use rand::thread_rng;
+
+    use super::*;
+
+    fn is_satisfied(challenge: &str, salt: Fr, location: &str) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        CommittedPrefixCircuit::new_from_str(challenge, salt, location)
+            .generate_constraints(cs.clone())
+            .is_ok()
+            && cs.is_satisfied().unwrap_or(false)
+    }
+
+    #[test]
+    fn test_commitment_hides_the_challenge_behind_the_salt() {
+        let salt = Fr::from(42);
+        assert!(commit("gcp", salt) == commit("gcp", salt));
+        assert!(commit("gcp", salt) != commit("gcq", salt));
+        assert!(commit("gcp", salt) != commit("gcp", Fr::from(43)));
+        assert!(commit("gcp", salt) != commit("gcpv", salt));
+
+        let bytes = commitment_bytes(commit("gcp", salt));
+        assert!(Fr::deserialize_uncompressed(&bytes[..]).unwrap() == commit("gcp", salt));
+    }
+
+    #[test]
+    fn test_committed_prefix_constraints() {
+        let salt = Fr::from(42);
+        assert!(is_satisfied("gcp", salt, "gcpvj0"));
+        assert!(is_satisfied("gcpvj0", salt, "gcpvj0"));
+        assert!(!is_satisfied("gcq", salt, "gcpvj0"));
+        assert!(!is_satisfied("gcpvj0", salt, "gcp"));
+    }
+
+    #[test]
+    fn test_prove_verify_committed_prefix() {
+        let rng = &mut thread_rng();
+        let salt = Fr::rand(rng);
+        let circuit = CommittedPrefixCircuit::new_from_str("gcp", salt, "gcpvj0");
+
+        let (pk, vk) = setup_committed_groth16(rng, circuit.clone()).expect("setup failed");
+        let proof = create_committed_proof(&pk, circuit, rng).expect("proof not generated");
+
+        let verify = |commitment, precision| {
+            verify_committed_proof(&vk, commitment, precision, &proof).expect("verification failed")
+        };
+        assert!(verify(commit("gcp", salt), 6));
+        // Another challenge, the same challenge under another salt, or another precision fail
+        assert!(!verify(commit("gcq", salt), 6));
+        assert!(!verify(commit("gcp", salt + Fr::from(1)), 6));
+        assert!(!verify(commit("gcp", salt), 8));
+    }
+}
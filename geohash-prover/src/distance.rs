@@ -0,0 +1,214 @@
+//! A circuit proving that secret coordinates lie within a radius of a public center,
+//! without revealing the coordinates.
+//!
+//! Coordinates are fixed-point integers, see [`to_fixed_point`]. The public inputs are,
+//! in order, the center latitude, the center longitude and the radius, all in the same
+//! fixed-point units; the attendance pallet encodes them identically.
+
+use core::cmp::Ordering;
+
+use ark_bn254::Bn254;
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use ark_groth16::Groth16;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use geohash_inputs::coordinates;
+use rand::CryptoRng;
+use rand::RngCore;
+
+/// Fixed-point units per degree, a resolution of about 11cm.
+pub const UNITS_PER_DEGREE: f64 = coordinates::UNITS_PER_DEGREE as f64;
+
+/// Exclusive upper bound on fixed-point values. Bounding every coordinate and the
+/// radius keeps the squared distance far below the field modulus, so it cannot wrap.
+pub const MAX_FIXED_POINT: u64 = 1 << 32;
+
+/// Converts degrees to fixed-point units, offset so that all values are non-negative.
+///
+/// Latitude is offset by 90 and longitude by 180 degrees, the encoding of
+/// `geohash_inputs::coordinates` that the attendance pallet checks centers against.
+/// The distance is measured in these units without correcting longitude for latitude,
+/// so east-west distances are overstated away from the equator and the radius is
+/// conservative there.
+///
+/// # Returns
+/// `(latitude, longitude)` in fixed-point units, or `None` for a latitude beyond 90
+/// or a longitude beyond 180 degrees
+pub fn to_fixed_point(latitude: f64, longitude: f64) -> Option<(u64, u64)> {
+    let units = |degrees: f64| (degrees * UNITS_PER_DEGREE).round() as i64;
+    coordinates::encode(units(latitude), units(longitude))
+}
+
+#[derive(Clone, Default)]
+pub struct DistanceCircuit<F: PrimeField> {
+    pub latitude: Option<F>,
+    pub longitude: Option<F>,
+    pub center_latitude: Option<F>,
+    pub center_longitude: Option<F>,
+    pub radius: Option<F>,
+}
+
+impl DistanceCircuit<Fr> {
+    pub fn new(
+        (latitude, longitude): (u64, u64),
+        (center_latitude, center_longitude): (u64, u64),
+        radius: u64,
+    ) -> Self {
+        Self {
+            latitude: Some(latitude.into()),
+            longitude: Some(longitude.into()),
+            center_latitude: Some(center_latitude.into()),
+            center_longitude: Some(center_longitude.into()),
+            radius: Some(radius.into()),
+        }
+    }
+}
+
+pub fn setup_distance_groth16<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    circuit: DistanceCircuit<Fr>,
+) -> Result<
+    (
+        ark_groth16::ProvingKey<Bn254>,
+        ark_groth16::VerifyingKey<Bn254>,
+    ),
+    SynthesisError,
+> {
+    Groth16::<Bn254>::circuit_specific_setup(circuit, rng)
+}
+
+pub fn create_distance_proof<R: RngCore + CryptoRng>(
+    pk: &ark_groth16::ProvingKey<Bn254>,
+    circuit: DistanceCircuit<Fr>,
+    rng: &mut R,
+) -> Result<ark_groth16::Proof<Bn254>, SynthesisError> {
+    Groth16::<Bn254>::prove(pk, circuit, rng)
+}
+
+pub fn verify_distance_proof(
+    vk: &ark_groth16::VerifyingKey<Bn254>,
+    center: (u64, u64),
+    radius: u64,
+    proof: &ark_groth16::Proof<Bn254>,
+) -> Result<bool, SynthesisError> {
+    Groth16::<Bn254>::verify(vk, &public_inputs(center, radius), proof)
+}
+
+/// The public inputs of the distance circuit: center latitude, center longitude, radius.
+pub fn public_inputs((center_latitude, center_longitude): (u64, u64), radius: u64) -> Vec<Fr> {
+    vec![center_latitude.into(), center_longitude.into(), radius.into()]
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for DistanceCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let input = |value: Option<F>| {
+            FpVar::new_input(cs.clone(), || value.ok_or(SynthesisError::AssignmentMissing))
+        };
+        let witness = |value: Option<F>| {
+            FpVar::new_witness(cs.clone(), || value.ok_or(SynthesisError::AssignmentMissing))
+        };
+
+        // Public
+        let center_latitude = input(self.center_latitude)?;
+        let center_longitude = input(self.center_longitude)?;
+        let radius = input(self.radius)?;
+
+        // Witness
+        let latitude = witness(self.latitude)?;
+        let longitude = witness(self.longitude)?;
+
+        let bound = FpVar::constant(F::from(MAX_FIXED_POINT));
+        for value in [&center_latitude, &center_longitude, &radius, &latitude, &longitude] {
+            value.enforce_cmp(&bound, Ordering::Less, false)?;
+        }
+
+        // Squaring makes the sign of the difference irrelevant
+        let d_latitude = &latitude - &center_latitude;
+        let d_longitude = &longitude - &center_longitude;
+        let distance_squared = d_latitude.square()? + d_longitude.square()?;
+        distance_squared.enforce_cmp(&radius.square()?, Ordering::Less, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::thread_rng;
+
+    use super::*;
+
+    fn is_satisfied(circuit: DistanceCircuit<Fr>) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).expect("constraints");
+        cs.is_satisfied().expect("satisfiability")
+    }
+
+    /// Fixed-point units of coordinates known to be in range
+    fn fixed_point(latitude: f64, longitude: f64) -> (u64, u64) {
+        to_fixed_point(latitude, longitude).expect("valid coordinates")
+    }
+
+    #[test]
+    fn test_to_fixed_point() {
+        assert_eq!(to_fixed_point(-90.0, -180.0), Some((0, 0)));
+        assert_eq!(to_fixed_point(51.5, -0.1), Some((141_500_000, 179_900_000)));
+        // Southern and western hemispheres
+        assert_eq!(to_fixed_point(-33.8688, 151.2093), Some((56_131_200, 331_209_300)));
+        assert_eq!(to_fixed_point(-22.9068, -43.1729), Some((67_093_200, 136_827_100)));
+        assert_eq!(to_fixed_point(-90.1, 0.0), None);
+        assert_eq!(to_fixed_point(0.0, 180.1), None);
+        // Saturating to `i64::MIN` or `i64::MAX` when cast
+        assert_eq!(to_fixed_point(f64::NEG_INFINITY, 0.0), None);
+        assert_eq!(to_fixed_point(0.0, -1e300), None);
+        assert_eq!(to_fixed_point(f64::INFINITY, 0.0), None);
+    }
+
+    #[test]
+    fn test_distance_constraints() {
+        let center = fixed_point(51.5, -0.1);
+
+        // 30 units north and 40 east is exactly 50 units away
+        let edge = (center.0 + 30, center.1 + 40);
+        assert!(is_satisfied(DistanceCircuit::new(edge, center, 50)));
+        assert!(!is_satisfied(DistanceCircuit::new(edge, center, 49)));
+
+        // Negative differences square to the same distance
+        let opposite = (center.0 - 30, center.1 - 40);
+        assert!(is_satisfied(DistanceCircuit::new(opposite, center, 50)));
+
+        // Coordinates outside the fixed-point range are rejected
+        let out_of_range = (MAX_FIXED_POINT, center.1);
+        assert!(!is_satisfied(DistanceCircuit::new(out_of_range, center, u32::MAX as u64)));
+    }
+
+    #[test]
+    fn test_distance_across_the_equator_and_meridian() {
+        // 30 units south and 40 west of a center on both zero lines
+        let center = fixed_point(0.0, 0.0);
+        let location = fixed_point(-0.00003, -0.00004);
+        assert!(is_satisfied(DistanceCircuit::new(location, center, 50)));
+        assert!(!is_satisfied(DistanceCircuit::new(location, center, 49)));
+
+        // A naive cast of the signed coordinates is far out of range
+        let naive = ((-30i64) as u64, (-40i64) as u64);
+        assert!(!is_satisfied(DistanceCircuit::new(naive, (0, 0), 50)));
+    }
+
+    #[test]
+    fn test_prove_verify_distance() {
+        let center = fixed_point(51.5, -0.1);
+        let location = fixed_point(51.5001, -0.1001);
+        let circuit = DistanceCircuit::new(location, center, 200);
+        let rng = &mut thread_rng();
+
+        let (pk, vk) = setup_distance_groth16(rng, circuit.clone()).expect("setup failed");
+        let proof = create_distance_proof(&pk, circuit, rng).expect("proof not generated");
+
+        assert!(verify_distance_proof(&vk, center, 200, &proof).expect("verification failed"));
+        assert!(!verify_distance_proof(&vk, center, 100, &proof).expect("verification failed"));
+        let elsewhere = fixed_point(48.85, 2.35);
+        assert!(!verify_distance_proof(&vk, elsewhere, 200, &proof).expect("verification failed"));
+    }
+}
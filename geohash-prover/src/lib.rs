@@ -3,11 +3,22 @@ use ark_bn254::Fr;
 use ark_ff::PrimeField;
 use ark_groth16::Groth16;
 use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
 use ark_snark::SNARK;
 use rand::CryptoRng;
 use rand::RngCore;
 
+pub mod commitment;
+pub mod distance;
+
+/// Size of an uncompressed BN254 Groth16 proof as deserialized by the attendance
+/// pallet: two G1 points of 64 bytes and one G2 point of 128 bytes. The pallet's
+/// `MaxProofLength` must be at least this.
+pub const UNCOMPRESSED_PROOF_SIZE: usize = 256;
+
 #[derive(Clone, Default)]
 pub struct CompareCircuit<F: PrimeField> {
     pub shorter: Option<Vec<F>>,
@@ -50,15 +61,74 @@ pub fn create_proof<R: RngCore + CryptoRng>(
     Groth16::<Bn254>::prove(pk, circuit, rng)
 }
 
+/// Serializes a proof in the format the attendance pallet deserializes it from:
+/// uncompressed, [`UNCOMPRESSED_PROOF_SIZE`] bytes. Submit these bytes as the `proof` of
+/// `submission_with_proof`.
+pub fn proof_to_raw(proof: &ark_groth16::Proof<Bn254>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(UNCOMPRESSED_PROOF_SIZE);
+    proof
+        .serialize_uncompressed(&mut bytes)
+        .expect("serializing into a Vec does not fail");
+    bytes
+}
+
+/// Checks whether the assignment of `circuit` satisfies its constraints, without a setup
+/// or a proof, for a fast answer while developing or testing a circuit. A `false` result
+/// means proving would produce a proof that fails verification.
+///
+/// # Returns
+/// Whether every constraint is satisfied, or the error synthesizing the circuit, such as
+/// `SynthesisError::Unsatisfiable` for a challenge longer than the location
+pub fn debug_check(circuit: CompareCircuit<Fr>) -> Result<bool, SynthesisError> {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone())?;
+    cs.is_satisfied()
+}
+
+/// Verifies a proof that a location of `precision` characters starts with `public_inputs`.
 pub fn verify_proof<'a>(
     vk: &ark_groth16::VerifyingKey<Bn254>,
     public_inputs: &'a str,
+    precision: usize,
     proof: &ark_groth16::Proof<Bn254>,
 ) -> Result<bool, SynthesisError> {
-    let public_inputs = &Vec::<Fr>::from(PrimeString::<Fr>::from(public_inputs));
+    let public_inputs = &self::public_inputs(public_inputs, precision);
     Groth16::<Bn254>::verify(vk, public_inputs, proof)
 }
 
+/// Prepares a verifying key so the pairing precomputation is paid once, off-chain.
+pub fn prepare_vk(
+    vk: &ark_groth16::VerifyingKey<Bn254>,
+) -> Result<ark_groth16::PreparedVerifyingKey<Bn254>, SynthesisError> {
+    Groth16::<Bn254>::process_vk(vk)
+}
+
+/// Serializes a prepared verifying key, uncompressed, for storage.
+pub fn serialize_prepared_vk(
+    pvk: &ark_groth16::PreparedVerifyingKey<Bn254>,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut bytes = Vec::new();
+    pvk.serialize_uncompressed(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Deserializes a prepared verifying key produced by [`serialize_prepared_vk`].
+pub fn deserialize_prepared_vk(
+    bytes: &[u8],
+) -> Result<ark_groth16::PreparedVerifyingKey<Bn254>, SerializationError> {
+    ark_groth16::PreparedVerifyingKey::<Bn254>::deserialize_uncompressed(bytes)
+}
+
+pub fn verify_proof_with_prepared_vk<'a>(
+    pvk: &ark_groth16::PreparedVerifyingKey<Bn254>,
+    public_inputs: &'a str,
+    precision: usize,
+    proof: &ark_groth16::Proof<Bn254>,
+) -> Result<bool, SynthesisError> {
+    let public_inputs = &self::public_inputs(public_inputs, precision);
+    Groth16::<Bn254>::verify_with_processed_vk(pvk, public_inputs, proof)
+}
+
 impl<F: PrimeField> ConstraintSynthesizer<F> for CompareCircuit<F> {
     fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
         let shorter = self.shorter.ok_or(SynthesisError::AssignmentMissing)?;
@@ -73,11 +143,15 @@ impl<F: PrimeField> ConstraintSynthesizer<F> for CompareCircuit<F> {
             .iter()
             .map(|&val| FpVar::new_input(cs.clone(), || Ok(val)))
             .collect::<Result<Vec<_>, _>>()?;
+        // The precision of the location, fixed by the circuit's shape so that a verifier
+        // learns how precise the proven location is without seeing it
+        let precision = F::from(larger.len() as u64);
+        FpVar::new_input(cs.clone(), || Ok(precision))?
+            .enforce_equal(&FpVar::constant(precision))?;
 
         // Witness
         let larger_vars = larger
             .iter()
-            .take(shorter.len())
             .map(|&val| FpVar::new_witness(cs.clone(), || Ok(val)))
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -88,18 +162,20 @@ impl<F: PrimeField> ConstraintSynthesizer<F> for CompareCircuit<F> {
     }
 }
 
-// Generate a vector of prime field values for a string
+/// The public inputs of the prefix circuit for a challenge geohash and the precision of
+/// the proven location, encoded as the attendance pallet encodes them, see
+/// `geohash_inputs::prefix_public_inputs`.
+pub fn public_inputs(geohash: &str, precision: usize) -> Vec<Fr> {
+    geohash_inputs::prefix_public_inputs(geohash.as_bytes(), precision as u32)
+}
+
+// Generate a vector of prime field values for a string, one per byte, through the
+// encoding shared with the attendance pallet.
 #[derive(Clone)]
 struct PrimeString<F: PrimeField>(Vec<F>);
 impl<'a, F: PrimeField> From<&'a str> for PrimeString<F> {
     fn from(value: &'a str) -> Self {
-        Self(
-            value
-                .as_bytes()
-                .iter()
-                .map(|c| (*c as u64).into())
-                .collect(),
-        )
+        Self(geohash_inputs::geohash_to_public_inputs(value.as_bytes()))
     }
 }
 
@@ -121,7 +197,8 @@ mod tests {
 
         let (pk, vk) = setup_groth16(rng, circuit.clone()).expect("setup failed");
         let proof = create_proof(&pk, circuit, rng).expect("proof not generated");
-        let verified = verify_proof(&vk, small, &proof).expect("verification failed");
+        let verified =
+            verify_proof(&vk, small, large.len(), &proof).expect("verification failed");
 
         assert!(verified, "this can't be verified");
     }
@@ -139,6 +216,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_public_inputs() {
+        // The encoding the attendance pallet uses for the challenge's bytes and precision
+        for geohash in ["", "0", "gcpv", "u4pruydqqvj"] {
+            assert_eq!(
+                public_inputs(geohash, 12),
+                geohash_inputs::prefix_public_inputs::<Fr>(geohash.as_bytes(), 12)
+            );
+        }
+        let mut inputs = public_inputs("gcpv", 6);
+        assert_eq!(inputs.pop(), Some(Fr::from(6)));
+        assert_eq!(
+            CompareCircuit::new_from_str("gcpv", "gcpvj0").shorter,
+            Some(inputs)
+        );
+    }
+
+    #[test]
+    fn test_precision_is_bound_to_the_location() {
+        let circuit = CompareCircuit::new_from_str("gcp", "gcpvj0");
+        let rng = &mut thread_rng();
+
+        let (pk, vk) = setup_groth16(rng, circuit.clone()).expect("setup failed");
+        let proof = create_proof(&pk, circuit, rng).expect("proof not generated");
+
+        assert!(verify_proof(&vk, "gcp", 6, &proof).expect("verification failed"));
+        // Claiming a more precise location than the one proven fails
+        assert!(!verify_proof(&vk, "gcp", 8, &proof).expect("verification failed"));
+        assert!(!verify_proof(&vk, "gcp", 3, &proof).expect("verification failed"));
+    }
+
+    #[test]
+    fn test_debug_check() {
+        assert!(matches!(
+            debug_check(CompareCircuit::new_from_str("gcp", "gcpvj0")),
+            Ok(true)
+        ));
+        // A location outside the challenge cannot be proven
+        assert!(matches!(
+            debug_check(CompareCircuit::new_from_str("gcq", "gcpvj0")),
+            Ok(false)
+        ));
+        assert!(matches!(
+            debug_check(CompareCircuit::new_from_str("gcpvj0", "gcp")),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+    }
+
     #[test]
     fn test_empty_shorter() {
         let result = std::panic::catch_unwind(|| {
@@ -176,4 +301,136 @@ mod tests {
             "Expected panic, but the code did not panic"
         );
     }
+
+    #[test]
+    fn test_serialized_proof_size() {
+        let circuit = CompareCircuit::new_from_str("gcp", "gcpvj0");
+        let rng = &mut thread_rng();
+
+        let (pk, _) = setup_groth16(rng, circuit.clone()).expect("setup failed");
+        let proof = create_proof(&pk, circuit, rng).expect("proof not generated");
+
+        let mut bytes = Vec::new();
+        proof.serialize_uncompressed(&mut bytes).expect("serialization failed");
+        assert_eq!(bytes.len(), UNCOMPRESSED_PROOF_SIZE);
+        assert_eq!(proof.uncompressed_size(), UNCOMPRESSED_PROOF_SIZE);
+        assert_eq!(proof.compressed_size(), 128);
+
+        assert_eq!(proof_to_raw(&proof), bytes);
+        let raw = ark_groth16::Proof::<Bn254>::deserialize_uncompressed(&proof_to_raw(&proof)[..])
+            .expect("raw proof deserializes");
+        assert!(raw == proof);
+
+        // The size does not depend on the circuit inputs
+        let circuit = CompareCircuit::new_from_str("gcpvj0", "gcpvj0");
+        let (pk, _) = setup_groth16(rng, circuit.clone()).expect("setup failed");
+        let proof = create_proof(&pk, circuit, rng).expect("proof not generated");
+        assert_eq!(proof.uncompressed_size(), UNCOMPRESSED_PROOF_SIZE);
+    }
+
+    #[test]
+    fn test_verify_with_deserialized_prepared_vk() {
+        let circuit = CompareCircuit::new_from_str("gcp", "gcpvj0");
+        let rng = &mut thread_rng();
+
+        let (pk, vk) = setup_groth16(rng, circuit.clone()).expect("setup failed");
+        let proof = create_proof(&pk, circuit, rng).expect("proof not generated");
+
+        let pvk = prepare_vk(&vk).expect("vk not prepared");
+        let bytes = serialize_prepared_vk(&pvk).expect("serialization failed");
+        let pvk = deserialize_prepared_vk(&bytes).expect("deserialization failed");
+
+        let verify = |geohash| {
+            verify_proof_with_prepared_vk(&pvk, geohash, 6, &proof).expect("verification failed")
+        };
+        assert!(verify("gcp"));
+        assert!(!verify("gcq"));
+    }
+
+    mod properties {
+        use ark_relations::r1cs::ConstraintSystem;
+        use proptest::prelude::*;
+        use proptest::test_runner::{Config, RngAlgorithm, TestRng, TestRunner};
+        use rand::{rngs::StdRng, SeedableRng};
+
+        use super::*;
+
+        const GEOHASH_ALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+        fn geohash(len: std::ops::RangeInclusive<usize>) -> impl Strategy<Value = String> {
+            prop::collection::vec(prop::sample::select(GEOHASH_ALPHABET), len)
+                .prop_map(|bytes| String::from_utf8(bytes).expect("ascii"))
+        }
+
+        fn runner() -> TestRunner {
+            TestRunner::new_with_rng(
+                Config {
+                    cases: 16,
+                    ..Config::default()
+                },
+                TestRng::deterministic_rng(RngAlgorithm::ChaCha),
+            )
+        }
+
+        /// Whether a proof that `prefix` starts `full` can be created and verified
+        fn proves(prefix: &str, full: &str) -> bool {
+            let circuit = CompareCircuit::new_from_str(prefix, full);
+            let rng = &mut StdRng::seed_from_u64(42);
+
+            let Ok((pk, vk)) = setup_groth16(rng, circuit.clone()) else {
+                return false;
+            };
+            let Ok(proof) = create_proof(&pk, circuit, rng) else {
+                return false;
+            };
+            verify_proof(&vk, prefix, full.len(), &proof).unwrap_or(false)
+        }
+
+        fn is_satisfied(prefix: &str, full: &str) -> bool {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            CompareCircuit::new_from_str(prefix, full)
+                .generate_constraints(cs.clone())
+                .is_ok()
+                && cs.is_satisfied().unwrap_or(false)
+        }
+
+        #[test]
+        fn true_prefix_always_verifies() {
+            let pairs = (geohash(1..=12), 0usize..=12).prop_map(|(full, cut)| {
+                let len = 1 + cut % full.len();
+                (full[..len].to_string(), full)
+            });
+            runner()
+                .run(&pairs, |(prefix, full)| {
+                    prop_assert!(proves(&prefix, &full), "{} should prefix {}", prefix, full);
+                    Ok(())
+                })
+                .unwrap();
+        }
+
+        #[test]
+        fn non_prefix_never_verifies() {
+            let pairs = (geohash(1..=12), geohash(1..=12))
+                .prop_filter("must not be a prefix", |(prefix, full)| {
+                    !full.starts_with(prefix.as_str())
+                });
+            runner()
+                .run(&pairs, |(prefix, full)| {
+                    prop_assert!(!is_satisfied(&prefix, &full));
+                    prop_assert!(!proves(&prefix, &full), "{} is not a prefix of {}", prefix, full);
+                    Ok(())
+                })
+                .unwrap();
+        }
+
+        #[test]
+        fn edge_cases() {
+            // Equal length and single character prefixes
+            assert!(proves("gcpvj0", "gcpvj0"));
+            assert!(proves("g", "gcpvj0"));
+            assert!(proves("g", "g"));
+            assert!(!proves("h", "g"));
+            assert!(!proves("gcpvj1", "gcpvj0"));
+        }
+    }
 }
@@ -4,7 +4,7 @@
 //! using the Blake2-256 cryptographic hash function.
 
 use sp_io::hashing::blake2_256;
-use oracle::{Hash, Hasher};
+use crate::{Hash, Hasher};
 
 /// Implementation of the `Hasher` trait using Blake2-256.
 ///
@@ -27,6 +27,7 @@ impl Hasher for Blake2_256 {
     /// # Example
     ///
     /// ```
+    /// # use oracle::{blake2_256::Blake2_256, Hasher};
     /// let message = "Hello, world!";
     /// let hash = Blake2_256::hash(message);
     /// ```
@@ -0,0 +1,168 @@
+//! Reuse of a recent location for repeated attestations.
+//!
+//! An oracle attesting every few seconds from the same place would otherwise
+//! query its location source, and spend its API quota, on every attestation.
+//! [`Cached`] reuses the last location obtained from a source within a time to
+//! live; each attestation is still signed anew.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::{Location, LocationError};
+
+/// A location source whose last location is reused within a time to live.
+pub struct Cached<L: Location> {
+    source: L,
+    ttl: Duration,
+    /// When the last location was obtained, at which accuracy, and the location
+    last: Mutex<Option<(Instant, u8, L::Output)>>,
+}
+
+impl<L: Location> Cached<L> {
+    /// Wraps a location source.
+    ///
+    /// # Arguments
+    /// * `source` - The location source
+    /// * `ttl` - How long a location is reused, `Duration::ZERO` to query the
+    ///   source every time
+    pub fn new(source: L, ttl: Duration) -> Self {
+        Self {
+            source,
+            ttl,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// The last location at `accuracy`, if obtained within the time to live.
+    fn fresh(&self, accuracy: u8) -> Option<L::Output>
+    where
+        L::Output: Clone,
+    {
+        let last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        match &*last {
+            Some((at, cached, location)) if *cached == accuracy && at.elapsed() < self.ttl => {
+                Some(location.clone())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<L> Location for Cached<L>
+where
+    L: Location + Send + Sync,
+    L::Output: Clone + Send,
+{
+    type Output = L::Output;
+
+    /// Reuses the last location if it was obtained at the same accuracy within
+    /// the time to live, and queries the source otherwise.
+    ///
+    /// # Arguments
+    /// * `accuracy` - The desired accuracy level for the location data
+    ///
+    /// # Returns
+    /// * `Result<L::Output, LocationError>` - The location, or the source's
+    ///   error, which is not cached
+    async fn current_location(&self, accuracy: u8) -> Result<L::Output, LocationError> {
+        if let Some(location) = self.fresh(accuracy) {
+            return Ok(location);
+        }
+        let location = self.source.current_location(accuracy).await?;
+        if !self.ttl.is_zero() {
+            *self.last.lock().unwrap_or_else(|e| e.into_inner()) =
+                Some((Instant::now(), accuracy, location.clone()));
+        }
+        Ok(location)
+    }
+}
+
+/// A provider counting its lookups, failing every other one when `flaky`
+#[cfg(test)]
+struct CountingLocation {
+    calls: std::sync::atomic::AtomicUsize,
+    flaky: bool,
+}
+
+#[cfg(test)]
+impl CountingLocation {
+    fn calls(&self) -> usize {
+        self.calls.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl Location for CountingLocation {
+    type Output = String;
+
+    async fn current_location(&self, accuracy: u8) -> Result<String, LocationError> {
+        let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if self.flaky && call % 2 == 0 {
+            return Err(LocationError::Location);
+        }
+        Ok("gcpvj0du"[..accuracy as usize].to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_location_is_reused_within_the_ttl() {
+    let cached = Cached::new(
+        CountingLocation {
+            calls: Default::default(),
+            flaky: false,
+        },
+        Duration::from_secs(3600),
+    );
+    for _ in 0..3 {
+        assert_eq!(
+            cached.current_location(6).await.expect("location"),
+            "gcpvj0"
+        );
+    }
+    assert_eq!(cached.source.calls(), 1);
+
+    // Another accuracy is looked up, and replaces the cached location
+    assert_eq!(
+        cached.current_location(8).await.expect("location"),
+        "gcpvj0du"
+    );
+    assert_eq!(
+        cached.current_location(6).await.expect("location"),
+        "gcpvj0"
+    );
+    assert_eq!(cached.source.calls(), 3);
+}
+
+#[tokio::test]
+async fn test_uncached_lookups() {
+    // Without a time to live every lookup reaches the source
+    let uncached = Cached::new(
+        CountingLocation {
+            calls: Default::default(),
+            flaky: false,
+        },
+        Duration::ZERO,
+    );
+    for _ in 0..3 {
+        uncached.current_location(6).await.expect("location");
+    }
+    assert_eq!(uncached.source.calls(), 3);
+
+    // Failures are not cached
+    let flaky = Cached::new(
+        CountingLocation {
+            calls: Default::default(),
+            flaky: true,
+        },
+        Duration::from_secs(3600),
+    );
+    assert!(matches!(
+        flaky.current_location(6).await,
+        Err(LocationError::Location)
+    ));
+    assert_eq!(flaky.current_location(6).await.expect("location"), "gcpvj0");
+    assert_eq!(flaky.current_location(6).await.expect("location"), "gcpvj0");
+    assert_eq!(flaky.source.calls(), 2);
+}
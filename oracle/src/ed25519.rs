@@ -3,8 +3,8 @@
 //! This module provides an implementation of the `Signer` trait
 //! using the Ed25519 elliptic curve digital signature algorithm.
 
-use ed25519_dalek::{Signer, SigningKey};
-use oracle::{Hash, Key, SignerError};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use crate::{Hash, PublicKey, SecretKey, SignerError};
 use rand::rngs::OsRng;
 
 /// Implementation of the `Signer` trait using the Ed25519 signature algorithm.
@@ -14,7 +14,7 @@ use rand::rngs::OsRng;
 /// cryptographic applications requiring digital signatures.
 pub struct Ed25519;
 
-impl oracle::Signer for Ed25519 {
+impl crate::Signer for Ed25519 {
     /// The type of signature produced by this implementation.
     ///
     /// Ed25519 signatures are binary data represented as a byte vector.
@@ -37,7 +37,7 @@ impl oracle::Signer for Ed25519 {
     /// This function will return an error if:
     /// - The provided key is invalid for Ed25519 signing
     /// - The internal signing operation fails
-    fn sign(message: Hash, key: Key) -> Result<Self::Signature, SignerError> {
+    fn sign(message: Hash, key: SecretKey) -> Result<Self::Signature, SignerError> {
         let signing_key = SigningKey::from_bytes(key.as_bytes());
 
         let signature = signing_key
@@ -61,16 +61,157 @@ impl oracle::Signer for Ed25519 {
     /// # Examples
     ///
     /// ```
+    /// # use oracle::{ed25519::Ed25519, Signer};
     /// let (private_key, public_key) = Ed25519::generate_key();
     /// // Use private_key for signing
     /// // Share public_key for verification
     /// ```
-    fn generate_key() -> (Key, Key) {
+    fn generate_key() -> (SecretKey, PublicKey) {
         let mut csprng = OsRng;
         let signing_key = SigningKey::generate(&mut csprng);
         (
-            Key::new(signing_key.to_bytes()),
-            Key::new(signing_key.verifying_key().to_bytes()),
+            SecretKey::new(signing_key.to_bytes()),
+            PublicKey::new(signing_key.verifying_key().to_bytes()),
         )
     }
+
+    /// Derives the Ed25519 public key for a private key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The private key (signing key)
+    ///
+    /// # Returns
+    ///
+    /// The public key (verification key)
+    fn public_key(key: SecretKey) -> PublicKey {
+        PublicKey::new(SigningKey::from_bytes(key.as_bytes()).verifying_key().to_bytes())
+    }
+}
+
+impl crate::Verifier for Ed25519 {
+    /// Ed25519 signatures as produced by the `Signer` implementation.
+    type Signature = Vec<u8>;
+
+    /// Verifies an Ed25519 signature over a message hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The hash of the signed message
+    /// * `signature` - The 64-byte signature
+    /// * `public_key` - The public key of the signer
+    ///
+    /// # Returns
+    ///
+    /// `true` if the signature is valid. Malformed public keys or signatures
+    /// are treated as invalid.
+    fn verify(message: Hash, signature: &Self::Signature, public_key: PublicKey) -> bool {
+        let Ok(verifying_key) = VerifyingKey::from_bytes(public_key.as_bytes()) else {
+            return false;
+        };
+        let Ok(signature) = Signature::from_slice(signature) else {
+            return false;
+        };
+        verifying_key.verify(message.as_bytes(), &signature).is_ok()
+    }
+}
+
+#[test]
+fn test_public_key_matches_generated_pair() {
+    use crate::Signer as _;
+
+    let (secret_key, public_key) = Ed25519::generate_key();
+    assert_eq!(Ed25519::public_key(secret_key).as_bytes(), public_key.as_bytes());
+}
+
+#[test]
+fn test_verify_location() {
+    use crate::blake2_256::Blake2_256;
+    use crate::{signing_payload, verify_location, Hasher, Signer as _, DOMAIN_TAG};
+
+    let (secret_key, public_key) = Ed25519::generate_key();
+    let signature = Ed25519::sign(
+        Blake2_256::hash(signing_payload(DOMAIN_TAG, None, b"gcpvj0")),
+        secret_key,
+    )
+    .expect("signed");
+
+    assert!(verify_location::<Ed25519, Blake2_256>(
+        b"gcpvj0",
+        None,
+        &signature,
+        public_key,
+        DOMAIN_TAG
+    ));
+    assert!(!verify_location::<Ed25519, Blake2_256>(
+        b"gcpvj1",
+        None,
+        &signature,
+        public_key,
+        DOMAIN_TAG
+    ));
+    let (_, other_public_key) = Ed25519::generate_key();
+    assert!(!verify_location::<Ed25519, Blake2_256>(
+        b"gcpvj0",
+        None,
+        &signature,
+        other_public_key,
+        DOMAIN_TAG
+    ));
+}
+
+#[test]
+fn test_signature_with_different_domain_tag_fails() {
+    use crate::blake2_256::Blake2_256;
+    use crate::{signing_payload, verify_location, Hasher, Signer as _, DOMAIN_TAG};
+
+    let (secret_key, public_key) = Ed25519::generate_key();
+    let signature = Ed25519::sign(
+        Blake2_256::hash(signing_payload(b"another-protocol", None, b"gcpvj0")),
+        secret_key,
+    )
+    .expect("signed");
+
+    assert!(!verify_location::<Ed25519, Blake2_256>(
+        b"gcpvj0",
+        None,
+        &signature,
+        public_key,
+        DOMAIN_TAG
+    ));
+}
+
+#[test]
+fn test_signature_for_another_challenge_fails() {
+    use crate::blake2_256::Blake2_256;
+    use crate::{signing_payload, verify_location, Hasher, Signer as _, DOMAIN_TAG};
+
+    let (secret_key, public_key) = Ed25519::generate_key();
+    let signature = Ed25519::sign(
+        Blake2_256::hash(signing_payload(DOMAIN_TAG, Some(b"gcpv".as_slice()), b"gcpvj0")),
+        secret_key,
+    )
+    .expect("signed");
+
+    assert!(verify_location::<Ed25519, Blake2_256>(
+        b"gcpvj0",
+        Some(b"gcpv".as_slice()),
+        &signature,
+        public_key,
+        DOMAIN_TAG
+    ));
+    assert!(!verify_location::<Ed25519, Blake2_256>(
+        b"gcpvj0",
+        Some(b"gcp".as_slice()),
+        &signature,
+        public_key,
+        DOMAIN_TAG
+    ));
+    assert!(!verify_location::<Ed25519, Blake2_256>(
+        b"gcpvj0",
+        None,
+        &signature,
+        public_key,
+        DOMAIN_TAG
+    ));
 }
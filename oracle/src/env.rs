@@ -1,4 +1,7 @@
 use std::env;
+use std::str::FromStr;
+
+use oracle::{ParseHexError, SecretKey};
 use thiserror::Error;
 
 /// Errors that can occur during environment and key-related operations.
@@ -32,47 +35,45 @@ pub enum EnvError {
     HexParseError(String),
 }
 
-/// Environment variable name used to store the oracle's private key.
-///
-/// When set, this environment variable should contain a hex-encoded
-/// 32-byte private key used for signing operations.
-const ENV_ORACLE_KEY: &str = "ORACLE_KEY";
+impl From<ParseHexError> for EnvError {
+    fn from(e: ParseHexError) -> Self {
+        match e {
+            ParseHexError::InvalidLength => EnvError::InvalidHexLength,
+            ParseHexError::InvalidHex(e) => EnvError::HexParseError(e),
+        }
+    }
+}
 
-/// Converts a hexadecimal string to a fixed-size byte array.
-///
-/// This function handles both raw hex strings and those with a "0x" prefix.
-/// It ensures the string length matches the expected output array size
-/// before attempting conversion.
+/// Parses a hex string, with or without "0x" prefix, into a key.
 ///
 /// # Type Parameters
-/// * `N` - The size of the output array in bytes
+/// * `K` - The kind of key, `SecretKey` or `PublicKey`
 ///
 /// # Arguments
-/// * `hex_string` - The hexadecimal string to convert (with or without "0x" prefix)
+/// * `hex_string` - The hexadecimal key
 ///
 /// # Returns
-/// * `Result<[u8; N], EnvError>` - The byte array if successful, or an error if:
-///   - The string length doesn't match the expected array size (2 hex chars per byte)
-///   - The string contains invalid hexadecimal characters
+/// * `Result<K, EnvError>` - The key if successful, or an error if the
+///   string is not 32 bytes of valid hex
+pub(crate) fn try_hex_to_key<K>(hex_string: &str) -> Result<K, EnvError>
+where
+    K: FromStr<Err = ParseHexError>,
+{
+    Ok(hex_string.parse::<K>()?)
+}
+
+/// Environment variable name used to store the oracle's private key.
 ///
-/// # Examples
-/// ```
-/// let bytes = try_hex_to_array::<2>("0102".to_string()).unwrap();
-/// assert_eq!(bytes, [1, 2]);
+/// When set, this environment variable should contain a hex-encoded
+/// 32-byte private key used for signing operations.
+const ENV_ORACLE_KEY: &str = "ORACLE_KEY";
+
+/// Environment variable name used to store the user PIN of a PKCS#11 token.
 ///
-/// let bytes = try_hex_to_array::<2>("0x0102".to_string()).unwrap();
-/// assert_eq!(bytes, [1, 2]);
-/// ```
-pub(crate) fn try_hex_to_array<const N: usize>(hex_string: String) -> Result<[u8; N], EnvError> {
-    let hex_string = hex_string.strip_prefix("0x").unwrap_or(&hex_string);
-    if hex_string.len() != N * 2 {
-        return Err(EnvError::InvalidHexLength);
-    }
-    let mut out = [0u8; N];
-    hex::decode_to_slice(hex_string, &mut out)
-        .map_err(|e| EnvError::HexParseError(e.to_string()))?;
-    Ok(out)
-}
+/// The PIN is read from the environment rather than the command line, where
+/// other users could see it in the process list.
+#[cfg(feature = "pkcs11")]
+const ENV_ORACLE_PKCS11_PIN: &str = "ORACLE_PKCS11_PIN";
 
 /// Converts a byte array or slice to a hexadecimal string.
 ///
@@ -103,7 +104,7 @@ pub(crate) fn array_to_hex<T: AsRef<[u8]>>(array: T) -> String {
 /// it to contain a valid hexadecimal string representing a 32-byte key.
 ///
 /// # Returns
-/// * `Result<[u8; 32], EnvError>` - The 32-byte key if successful, or an error if:
+/// * `Result<SecretKey, EnvError>` - The 32-byte key if successful, or an error if:
 ///   - The environment variable is not set or not accessible
 ///   - The variable's value is not a valid hexadecimal string
 ///   - The hex string doesn't decode to exactly 32 bytes
@@ -114,8 +115,17 @@ pub(crate) fn array_to_hex<T: AsRef<[u8]>>(array: T) -> String {
 /// let key = try_key_from_environment().unwrap();
 /// // Use the key for cryptographic operations
 /// ```
-pub(crate) fn try_key_from_environment() -> Result<[u8; 32], EnvError> {
-    try_hex_to_array(env::var(ENV_ORACLE_KEY).map_err(|_| EnvError::VarNotFound)?)
+pub(crate) fn try_key_from_environment() -> Result<SecretKey, EnvError> {
+    try_hex_to_key(&env::var(ENV_ORACLE_KEY).map_err(|_| EnvError::VarNotFound)?)
+}
+
+/// Reads the user PIN of a PKCS#11 token from the environment.
+///
+/// # Returns
+/// * `Option<String>` - The PIN, or `None` if `ORACLE_PKCS11_PIN` is not set
+#[cfg(feature = "pkcs11")]
+pub(crate) fn pin_from_environment() -> Option<String> {
+    env::var(ENV_ORACLE_PKCS11_PIN).ok()
 }
 
 #[test]
@@ -124,30 +134,42 @@ fn test_try_key_from_environment() {
     env::set_var(ENV_ORACLE_KEY, secret_key);
     let mut array = [0; 32];
     array[31] = 0x2a;
-    assert_eq!(try_key_from_environment(), Ok(array));
+    assert_eq!(
+        try_key_from_environment().map(|key| *key.as_bytes()),
+        Ok(array)
+    );
     env::remove_var(ENV_ORACLE_KEY);
 }
 
 #[test]
-fn test_try_hex_to_array() {
+fn test_try_hex_to_key() {
+    let hex = format!("{}2a", "0".repeat(62));
+    let mut array = [0; 32];
+    array[31] = 0x2a;
+
     // Valid hex string
-    let result = try_hex_to_array::<2>("0102".to_string());
-    assert_eq!(result, Ok([1, 2]));
+    assert_eq!(
+        try_hex_to_key::<SecretKey>(&hex).map(|key| *key.as_bytes()),
+        Ok(array)
+    );
 
     // Valid with 0x prefix
-    let result = try_hex_to_array::<2>("0x0102".to_string());
-    assert_eq!(result, Ok([1, 2]));
+    assert_eq!(
+        try_hex_to_key::<SecretKey>(&format!("0x{}", hex)).map(|key| *key.as_bytes()),
+        Ok(array)
+    );
 
     // Invalid length
-    let result = try_hex_to_array::<2>("010".to_string());
-    assert_eq!(result, Err(EnvError::InvalidHexLength));
+    assert_eq!(
+        try_hex_to_key::<SecretKey>("010").map(|key| *key.as_bytes()),
+        Err(EnvError::InvalidHexLength)
+    );
 
     // Invalid hex characters
-    let result = try_hex_to_array::<2>("01zz".to_string());
     assert_eq!(
-        result,
+        try_hex_to_key::<SecretKey>(&format!("{}zz", "0".repeat(62))).map(|key| *key.as_bytes()),
         Err(EnvError::HexParseError(
-            "Invalid character 'z' at position 2".to_string()
+            "Invalid character 'z' at position 62".to_string()
         ))
     );
 }
@@ -0,0 +1,166 @@
+//! Top-level error type for the Oracle CLI.
+//!
+//! Every failure in the CLI converts into a `CliError`, so `main` reports
+//! errors from a single place.
+
+#[cfg(feature = "pkcs11")]
+use oracle::pkcs11::Pkcs11Error;
+use oracle::qr::QrError;
+use oracle::{LocationError, SignerError};
+use thiserror::Error;
+
+use crate::env::EnvError;
+use crate::keystore::KeystoreError;
+use crate::stdin::StdinError;
+use crate::submit::SubmitError;
+
+/// Errors that can occur while executing a CLI command.
+#[derive(Error, Debug)]
+pub enum CliError {
+    /// Neither a command-line key nor the `ORACLE_KEY` environment variable was given.
+    #[error("no key provided: pass a key or set the ORACLE_KEY environment variable")]
+    MissingKey,
+
+    /// A key was provided but could not be parsed.
+    ///
+    /// # Fields
+    /// * EnvError - Why the key could not be parsed
+    #[error("invalid key: {0}")]
+    InvalidKey(EnvError),
+
+    /// The expected public key could not be parsed.
+    ///
+    /// # Fields
+    /// * EnvError - Why the public key could not be parsed
+    #[error("invalid expected public key: {0}")]
+    InvalidExpectedPublicKey(EnvError),
+
+    /// The loaded private key does not correspond to the expected public key.
+    #[error("public key mismatch: expected 0x{expected}, key derives 0x{actual}")]
+    PublicKeyMismatch {
+        /// The hex public key that was expected
+        expected: String,
+        /// The hex public key derived from the loaded private key
+        actual: String,
+    },
+
+    /// The key could not be loaded from, or stored in, the OS keyring.
+    #[error(transparent)]
+    Keystore(#[from] KeystoreError),
+
+    /// A PKCS#11 token was selected but `ORACLE_PKCS11_PIN` is not set.
+    #[cfg(feature = "pkcs11")]
+    #[error("no PKCS#11 PIN provided: set the ORACLE_PKCS11_PIN environment variable")]
+    MissingPin,
+
+    /// The key on the PKCS#11 token could not be opened.
+    #[cfg(feature = "pkcs11")]
+    #[error("failed to open the PKCS#11 key: {0}")]
+    Pkcs11(#[from] Pkcs11Error),
+
+    /// A PKCS#11 token was selected, but this binary has no PKCS#11 support.
+    #[cfg(not(feature = "pkcs11"))]
+    #[error("PKCS#11 tokens are not supported: rebuild the oracle with `--features pkcs11`")]
+    Pkcs11Unsupported,
+
+    /// The geohash to sign is coarser than the `--min-accuracy` floor.
+    #[error(
+        "refusing to sign a geohash of precision {precision}, below the minimum accuracy {minimum}"
+    )]
+    InsufficientAccuracy {
+        /// The length of the geohash that would have been signed
+        precision: u8,
+        /// The shortest geohash allowed
+        minimum: u8,
+    },
+
+    /// The geohash given to `sign-geohash` is not a geohash.
+    ///
+    /// # Fields
+    /// * String - The rejected geohash
+    #[error("invalid geohash {0:?}: expected 1 to 12 characters of the geohash alphabet")]
+    InvalidGeohash(String),
+
+    /// The current location could not be obtained.
+    #[error("failed to get location: {0}")]
+    Location(#[from] LocationError),
+
+    /// The coordinates given on stdin could not be read.
+    #[error(transparent)]
+    Stdin(#[from] StdinError),
+
+    /// The location could not be signed.
+    #[error("failed to sign location: {0}")]
+    Signer(#[from] SignerError),
+
+    /// The attestation could not be submitted to the node.
+    #[error("failed to submit attestation: {0}")]
+    Submit(#[from] SubmitError),
+
+    /// The signed location could not be serialized.
+    #[error("failed to serialize output: {0}")]
+    Serialize(#[from] serde_json::Error),
+
+    /// The signed location could not be serialized, though it was signed.
+    #[error("failed to serialize attestation with signature {signature}: {source}")]
+    UnserializableAttestation {
+        /// The `0x`-prefixed hex signature of the attestation
+        signature: String,
+        /// Why the signed location could not be serialized
+        source: serde_json::Error,
+    },
+
+    /// The signed location could not be encoded for a QR code.
+    #[error(transparent)]
+    Qr(#[from] QrError),
+
+    /// The signed location could not be written out.
+    #[error("failed to write output: {0}")]
+    Output(#[from] std::io::Error),
+}
+
+impl From<EnvError> for CliError {
+    /// Distinguishes a missing key from a malformed one.
+    fn from(e: EnvError) -> Self {
+        match e {
+            EnvError::VarNotFound => CliError::MissingKey,
+            e => CliError::InvalidKey(e),
+        }
+    }
+}
+
+#[test]
+fn test_env_error_mapping() {
+    assert!(matches!(
+        CliError::from(EnvError::VarNotFound),
+        CliError::MissingKey
+    ));
+    assert!(matches!(
+        CliError::from(EnvError::InvalidHexLength),
+        CliError::InvalidKey(EnvError::InvalidHexLength)
+    ));
+    assert_eq!(
+        CliError::from(EnvError::HexParseError("bad".to_string())).to_string(),
+        "invalid key: failed to parse hex string: bad"
+    );
+}
+
+#[test]
+fn test_error_messages() {
+    assert_eq!(
+        CliError::MissingKey.to_string(),
+        "no key provided: pass a key or set the ORACLE_KEY environment variable"
+    );
+    assert_eq!(
+        CliError::from(LocationError::Location).to_string(),
+        "failed to get location: failed to locate"
+    );
+    assert_eq!(
+        CliError::from(SignerError::SignatureFailed("bad key".to_string())).to_string(),
+        "failed to sign location: signature failed: bad key"
+    );
+    assert_eq!(
+        CliError::from(StdinError::Latitude("91".to_string())).to_string(),
+        "latitude must be between -90 and 90 degrees, got `91`"
+    );
+}
@@ -0,0 +1,150 @@
+//! Priority-ordered fallback between location sources.
+//!
+//! Some operators prefer a chain of sources to agreement among them: try the
+//! most precise source first and fall back to the next one when it fails.
+//! [`Fallback`] tries its sources in order and returns the first location
+//! obtained; when every source fails, the error lists each source's failure.
+
+use crate::{Location, LocationError};
+
+/// A boxed location source producing `O`.
+type Source<O> = Box<dyn Location<Output = O> + Send + Sync>;
+
+/// Location sources tried in order until one succeeds.
+///
+/// # Examples
+///
+/// ```ignore
+/// let chain = Fallback::new().or("gps", gps).or("ipinfo", ipinfo);
+/// let location = oracle::location(&chain, 8).await?;
+/// ```
+pub struct Fallback<O> {
+    sources: Vec<(String, Source<O>)>,
+}
+
+impl<O> Default for Fallback<O> {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl<O> Fallback<O> {
+    /// Creates an empty chain, which fails to locate until a source is added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a source to try after those already in the chain.
+    ///
+    /// # Arguments
+    /// * `name` - The name identifying the source in errors
+    /// * `source` - The location source
+    pub fn or<L>(mut self, name: impl Into<String>, source: L) -> Self
+    where
+        L: Location<Output = O> + Send + Sync + 'static,
+    {
+        self.sources.push((name.into(), Box::new(source)));
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl<O> Location for Fallback<O>
+where
+    O: AsRef<[u8]> + Send + 'static,
+{
+    type Output = O;
+
+    /// Obtains the location from the first source that succeeds.
+    ///
+    /// # Arguments
+    /// * `accuracy` - The desired accuracy level, passed to every source
+    ///
+    /// # Returns
+    /// * `Result<O, LocationError>` - The first location obtained, the error of
+    ///   the only source of a chain of one, or `LocationError::AllSourcesFailed`
+    ///   listing the failure of every source
+    async fn current_location(&self, accuracy: u8) -> Result<O, LocationError> {
+        let mut failures = Vec::new();
+        for (name, source) in &self.sources {
+            match source.current_location(accuracy).await {
+                Ok(location) => return Ok(location),
+                Err(e) => failures.push((name.clone(), e)),
+            }
+        }
+        if failures.len() == 1 {
+            return Err(failures.remove(0).1);
+        }
+        Err(LocationError::AllSourcesFailed(failures))
+    }
+}
+
+/// Lists the failure of each source, as `name: error` separated by `; `.
+pub(crate) fn describe(failures: &[(String, LocationError)]) -> String {
+    failures
+        .iter()
+        .map(|(name, e)| format!("{}: {}", name, e))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// A source that always fails with `RateLimited`, or always returns its geohash
+#[cfg(test)]
+struct MockSource(Option<&'static str>);
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl Location for MockSource {
+    type Output = String;
+
+    async fn current_location(&self, accuracy: u8) -> Result<String, LocationError> {
+        match self.0 {
+            Some(geohash) => Ok(geohash[..accuracy as usize].to_string()),
+            None => Err(LocationError::RateLimited("retry later".to_string())),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_first_successful_source_is_used() {
+    let chain = Fallback::new()
+        .or("gps", MockSource(None))
+        .or("ipinfo", MockSource(Some("gcpvj0du")))
+        .or("ip-api", MockSource(Some("u4pruydq")));
+    assert_eq!(chain.current_location(6).await.expect("located"), "gcpvj0");
+
+    let chain = Fallback::new().or("ipinfo", MockSource(Some("gcpvj0du")));
+    assert_eq!(chain.current_location(4).await.expect("located"), "gcpv");
+}
+
+#[tokio::test]
+async fn test_every_failure_is_reported() {
+    let chain = Fallback::new()
+        .or("gps", MockSource(None))
+        .or("ipinfo", MockSource(None));
+    let error = chain.current_location(6).await.expect_err("no source");
+    assert!(matches!(
+        &error,
+        LocationError::AllSourcesFailed(failures) if failures.len() == 2
+    ));
+    assert_eq!(
+        error.to_string(),
+        "every location source failed: \
+         gps: rate limited by the location service: retry later; \
+         ipinfo: rate limited by the location service: retry later"
+    );
+
+    // A single source fails with its own error
+    let chain = Fallback::new().or("ipinfo", MockSource(None));
+    assert!(matches!(
+        chain.current_location(6).await,
+        Err(LocationError::RateLimited(_))
+    ));
+    let empty: Fallback<String> = Fallback::new();
+    assert!(matches!(
+        empty.current_location(6).await,
+        Err(LocationError::AllSourcesFailed(failures)) if failures.is_empty()
+    ));
+}
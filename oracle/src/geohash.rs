@@ -1,64 +1,376 @@
 //! Geohash implementation for location services.
 //!
 //! This module provides functionality to get the current geographical location
-//! based on IP address and convert it to a geohash string.
+//! based on IP address, or from coordinates supplied by the caller, and convert
+//! it to a geohash string or to a canonical encoding of the raw coordinates.
+
+use std::f64::consts::PI;
+use std::str::FromStr;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use geohash::Coord;
 use oracle::{Location, LocationError};
+use rand::Rng;
+
+/// Approximate length of one degree of latitude in meters.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// The default HTTP user agent sent to the IP geolocation service.
+pub const USER_AGENT: &str = concat!("aoi-oracle/", env!("CARGO_PKG_VERSION"));
+
+/// Longest `Retry-After` the oracle waits out before retrying a rate-limited lookup.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Interval of the TCP keep-alive probes on pooled connections.
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// The default number of idle connections kept open to the geolocation service.
+pub const HTTP_POOL_SIZE: usize = 1;
+
+/// Builds the HTTP client used for IP geolocation.
+///
+/// The client keeps up to `pool_size` idle connections to the service open with
+/// TCP keep-alive, so repeated lookups, such as those of a run with `--interval`,
+/// reuse an established connection instead of each paying for a new TCP and TLS
+/// handshake. A `pool_size` of 0 opens a new connection for every lookup.
+///
+/// # Arguments
+///
+/// * `user_agent` - The HTTP user agent identifying the oracle
+/// * `pool_size` - The most idle connections kept open
+///
+/// # Returns
+///
+/// * `Result<reqwest::Client, LocationError>` - The client, or `LocationError::Location`
+///   if the TLS backend could not be initialized
+pub fn http_client(user_agent: &str, pool_size: usize) -> Result<reqwest::Client, LocationError> {
+    reqwest::Client::builder()
+        .user_agent(user_agent)
+        .pool_max_idle_per_host(pool_size)
+        .tcp_keepalive(TCP_KEEPALIVE)
+        .build()
+        .map_err(|_| LocationError::Location)
+}
 
 /// Module for retrieving geographical location data using IP geolocation.
 ///
 /// This module interacts with the ipinfo.io API to determine the current
 /// geographical location based on the device's IP address.
 mod ip_info {
+    use std::time::Duration;
+
+    use oracle::LocationError;
+    use reqwest::{header::RETRY_AFTER, Response, StatusCode};
     use serde::Deserialize;
+
+    use super::MAX_RETRY_AFTER;
+
     /// The base URL for the ipinfo.io API service.
-    const IPINFO: &str = "https://ipinfo.io";
+    pub const IPINFO: &str = "https://ipinfo.io";
     
     /// Structure for deserializing the ipinfo.io API response.
     #[derive(Deserialize)]
-    struct IpInfo {
-        /// The location string in format "latitude,longitude"
-        loc: String,
+    pub struct IpInfo {
+        /// The location string in format "latitude,longitude", absent for bogons
+        pub loc: Option<String>,
+        /// Whether the IP address is a bogon, i.e. private or reserved
+        #[serde(default)]
+        pub bogon: bool,
+    }
+
+    impl IpInfo {
+        /// Extracts the coordinates, rejecting obviously bogus locations.
+        ///
+        /// A bogon IP address, or coordinates of exactly `0,0` as reported when
+        /// geolocation fails, are rejected unless `allow_bogon` is set.
+        ///
+        /// # Returns
+        ///
+        /// * `Result<(f64, f64), LocationError>` - A tuple of (latitude, longitude) if
+        ///   successful, `LocationError::Implausible` for a bogus location, or
+        ///   `LocationError::Location` if the location is missing or malformed.
+        pub fn coordinates(&self, allow_bogon: bool) -> Result<(f64, f64), LocationError> {
+            if self.bogon && !allow_bogon {
+                return Err(LocationError::Implausible(
+                    "the IP address is private or reserved (bogon)".to_string(),
+                ));
+            }
+            let loc = self.loc.as_deref().ok_or(LocationError::Location)?;
+            let (lat, lon) = loc.split_once(',').ok_or(LocationError::Location)?;
+            let lat = lat.trim().parse::<f64>().map_err(|_| LocationError::Location)?;
+            let lon = lon.trim().parse::<f64>().map_err(|_| LocationError::Location)?;
+
+            if lat == 0.0 && lon == 0.0 && !allow_bogon {
+                return Err(LocationError::Implausible(
+                    "coordinates are exactly 0,0 (null island)".to_string(),
+                ));
+            }
+            Ok((lat, lon))
+        }
     }
 
     /// Fetches the current geographical coordinates using IP geolocation.
     ///
-    /// Makes an HTTP request to ipinfo.io to determine the current location
-    /// based on the device's IP address. Parses the response and extracts
-    /// latitude and longitude coordinates.
+    /// Makes an HTTP request to the ipinfo.io API at `url` with `client` to determine
+    /// the current location based on the device's IP address. Parses the response and
+    /// extracts latitude and longitude coordinates.
+    ///
+    /// When the service answers `429 Too Many Requests` with a `Retry-After` of at most
+    /// `MAX_RETRY_AFTER`, the request is retried once after that delay.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The HTTP client, whose pooled connections are reused
+    /// * `url` - The ipinfo.io endpoint, [`IPINFO`] outside tests
+    /// * `allow_bogon` - Accept locations that look bogus, see [`IpInfo::coordinates`]
     ///
     /// # Returns
     ///
-    /// * `Result<(f64, f64), String>` - A tuple of (latitude, longitude) if successful,
-    ///   or an error message string if the request or parsing failed.
+    /// * `Result<(f64, f64), LocationError>` - A tuple of (latitude, longitude) if successful,
+    ///   or an error if the request or parsing failed or the location is implausible.
     ///
     /// # Errors
     ///
     /// This function will return an error if:
-    /// - The HTTP request to ipinfo.io fails
+    /// - The HTTP request to ipinfo.io fails or returns an error status
+    /// - The service is still rate limiting, or asks to wait longer than `MAX_RETRY_AFTER`
     /// - The response cannot be parsed as valid JSON
     /// - The location format is invalid (not "latitude,longitude")
     /// - The latitude or longitude values cannot be parsed as valid floating-point numbers
-    pub async fn get_ip() -> Result<(f64, f64), String> {
-        let response = reqwest::get(IPINFO).await.map_err(|e| e.to_string())?;
-        let ip_info: IpInfo = response.json().await.map_err(|e| e.to_string())?;
-        let parts: Vec<&str> = ip_info.loc.split(',').collect();
-        if parts.len() != 2 {
-            return Err(format!("Invalid location format: {}", ip_info.loc));
+    /// - The IP address is a bogon or the coordinates are `0,0`, unless allowed
+    pub async fn get_ip(
+        client: &reqwest::Client,
+        url: &str,
+        allow_bogon: bool,
+    ) -> Result<(f64, f64), LocationError> {
+        let get = || async { client.get(url).send().await.map_err(|_| LocationError::Location) };
+
+        let mut response = get().await?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            match retry_after(&response) {
+                Some(delay) if delay <= MAX_RETRY_AFTER => {
+                    tokio::time::sleep(delay).await;
+                    response = get().await?;
+                }
+                _ => return Err(rate_limited(&response)),
+            }
+        }
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited(&response));
+        }
+
+        let ip_info: IpInfo = response
+            .error_for_status()
+            .map_err(|_| LocationError::Location)?
+            .json()
+            .await
+            .map_err(|_| LocationError::Location)?;
+        ip_info.coordinates(allow_bogon)
+    }
+
+    /// The delay requested by a `Retry-After` header given in seconds.
+    fn retry_after(response: &Response) -> Option<Duration> {
+        let seconds = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+        seconds.trim().parse().ok().map(Duration::from_secs)
+    }
+
+    /// The error for a rate-limited response, with the delay the service asked for.
+    fn rate_limited(response: &Response) -> LocationError {
+        LocationError::RateLimited(match retry_after(response) {
+            Some(delay) => format!("retry after {} seconds", delay.as_secs()),
+            None => "retry later".to_string(),
+        })
+    }
+}
+
+/// Module for retrieving geographical location data from ip-api.com.
+///
+/// The fallback IP geolocation service of `--source-order`. Its free tier is
+/// only served over plain HTTP.
+mod ip_api {
+    use oracle::LocationError;
+    use reqwest::StatusCode;
+    use serde::Deserialize;
+
+    /// The ip-api.com endpoint, asking only for the fields the oracle reads.
+    pub const IP_API: &str = "http://ip-api.com/json/?fields=status,message,lat,lon";
+
+    /// Structure for deserializing the ip-api.com response.
+    #[derive(Deserialize)]
+    pub struct IpApi {
+        /// `success` or `fail`
+        pub status: String,
+        /// Why the lookup failed, e.g. `private range`
+        pub message: Option<String>,
+        /// The latitude, absent when the lookup failed
+        pub lat: Option<f64>,
+        /// The longitude, absent when the lookup failed
+        pub lon: Option<f64>,
+    }
+
+    impl IpApi {
+        /// Extracts the coordinates, rejecting obviously bogus locations as
+        /// ipinfo.io's are.
+        ///
+        /// A private or reserved IP address has no location at all, so it is
+        /// rejected even with `allow_bogon`; the null island `0,0` is rejected
+        /// unless `allow_bogon` is set.
+        ///
+        /// # Returns
+        ///
+        /// * `Result<(f64, f64), LocationError>` - A tuple of (latitude, longitude) if
+        ///   successful, `LocationError::Implausible` for a bogus location, or
+        ///   `LocationError::Location` if the lookup failed.
+        pub fn coordinates(&self, allow_bogon: bool) -> Result<(f64, f64), LocationError> {
+            if self.status != "success" {
+                return Err(match self.message.as_deref() {
+                    Some(range @ ("private range" | "reserved range")) => {
+                        LocationError::Implausible(format!("the IP address is in a {}", range))
+                    }
+                    _ => LocationError::Location,
+                });
+            }
+            let (lat, lon) = self.lat.zip(self.lon).ok_or(LocationError::Location)?;
+            if lat == 0.0 && lon == 0.0 && !allow_bogon {
+                return Err(LocationError::Implausible(
+                    "coordinates are exactly 0,0 (null island)".to_string(),
+                ));
+            }
+            Ok((lat, lon))
         }
+    }
 
-        let lat = parts[0]
-            .trim()
-            .parse::<f64>()
-            .map_err(|e| format!("Invalid latitude: {}", e))?;
-        let lon = parts[1]
-            .trim()
-            .parse::<f64>()
-            .map_err(|e| format!("Invalid longitude: {}", e))?;
+    /// Fetches the current geographical coordinates from ip-api.com at `url`
+    /// with `client`.
+    ///
+    /// Unlike ipinfo.io lookups, rate-limited lookups are not retried: the
+    /// service asks to wait until its one-minute window resets, which the next
+    /// source of a fallback chain need not.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The HTTP client, whose pooled connections are reused
+    /// * `url` - The ip-api.com endpoint, [`IP_API`] outside tests
+    /// * `allow_bogon` - Accept locations that look bogus, see [`IpApi::coordinates`]
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(f64, f64), LocationError>` - A tuple of (latitude, longitude) if
+    ///   successful, `LocationError::RateLimited` if the service refused the lookup,
+    ///   or the error of a failed request or [`IpApi::coordinates`].
+    pub async fn get_ip(
+        client: &reqwest::Client,
+        url: &str,
+        allow_bogon: bool,
+    ) -> Result<(f64, f64), LocationError> {
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .map_err(|_| LocationError::Location)?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            // ip-api.com gives the seconds until its window resets in `X-Ttl`
+            let ttl = response
+                .headers()
+                .get("x-ttl")
+                .and_then(|ttl| ttl.to_str().ok())
+                .and_then(|ttl| ttl.trim().parse::<u64>().ok());
+            return Err(LocationError::RateLimited(match ttl {
+                Some(seconds) => format!("retry after {} seconds", seconds),
+                None => "retry later".to_string(),
+            }));
+        }
 
-        Ok((lat, lon))
+        let ip_api: IpApi = response
+            .error_for_status()
+            .map_err(|_| LocationError::Location)?
+            .json()
+            .await
+            .map_err(|_| LocationError::Location)?;
+        ip_api.coordinates(allow_bogon)
+    }
+}
+
+/// An IP geolocation service, as selected with `--source-order`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IpSource {
+    /// ipinfo.io, the default.
+    #[default]
+    IpInfo,
+    /// ip-api.com.
+    IpApi,
+}
+
+impl IpSource {
+    /// Every IP geolocation service, default first.
+    pub const ALL: [IpSource; 2] = [Self::IpInfo, Self::IpApi];
+
+    /// The name selecting this service with `--source-order`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::IpInfo => "ipinfo",
+            Self::IpApi => "ip-api",
+        }
+    }
+}
+
+impl FromStr for IpSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|source| source.name() == s)
+            .ok_or_else(|| format!("expected `ipinfo` or `ip-api`, got `{}`", s))
+    }
+}
+
+/// Where a location source obtains its coordinates.
+#[derive(Default, Clone, Copy)]
+enum Source {
+    /// IP geolocation through ipinfo.io.
+    #[default]
+    IpInfo,
+    /// IP geolocation through ip-api.com.
+    IpApi,
+    /// Coordinates supplied by the caller, e.g. read from stdin.
+    Fixed(f64, f64),
+}
+
+impl From<IpSource> for Source {
+    fn from(source: IpSource) -> Self {
+        match source {
+            IpSource::IpInfo => Source::IpInfo,
+            IpSource::IpApi => Source::IpApi,
+        }
+    }
+}
+
+impl Source {
+    /// Obtains the `(latitude, longitude)` from this source.
+    ///
+    /// `allow_bogon` accepts IP locations that look bogus, and `client` is used for the
+    /// lookup, a new client built by [`http_client`] if not given; neither applies to
+    /// coordinates supplied by the caller.
+    async fn coordinates(
+        self,
+        allow_bogon: bool,
+        client: Option<&reqwest::Client>,
+    ) -> Result<(f64, f64), LocationError> {
+        let built;
+        let client = match (self, client) {
+            (Source::Fixed(lat, lon), _) => return Ok((lat, lon)),
+            (_, Some(client)) => client,
+            (_, None) => {
+                built = http_client(USER_AGENT, HTTP_POOL_SIZE)?;
+                &built
+            }
+        };
+        match self {
+            Source::IpApi => ip_api::get_ip(client, ip_api::IP_API, allow_bogon).await,
+            _ => ip_info::get_ip(client, ip_info::IPINFO, allow_bogon).await,
+        }
     }
 }
 
@@ -66,7 +378,273 @@ mod ip_info {
 ///
 /// This struct provides functionality to get the current geographical location 
 /// and encode it as a geohash string with variable precision.
-pub struct Geohash;
+///
+/// Two privacy options reduce what an attestation discloses:
+/// - `privacy_precision` caps the geohash length regardless of the requested accuracy
+/// - `jitter_meters` moves the coordinates to a random point within that radius
+///   before encoding
+///
+/// Both trade attendance accuracy for privacy: a capped or jittered location may
+/// fall outside a challenge that the true location is inside.
+#[derive(Default)]
+pub struct Geohash {
+    privacy_precision: Option<u8>,
+    jitter_meters: f64,
+    source: Source,
+    allow_bogon: bool,
+    client: Option<reqwest::Client>,
+}
+
+impl Geohash {
+    /// Creates a geohash location source with the given privacy options.
+    ///
+    /// # Arguments
+    ///
+    /// * `privacy_precision` - Maximum geohash length to disclose, if any
+    /// * `jitter_meters` - Radius within which coordinates are randomly perturbed,
+    ///   `0.0` to disable
+    pub fn new(privacy_precision: Option<u8>, jitter_meters: f64) -> Self {
+        Self {
+            privacy_precision,
+            jitter_meters,
+            source: Source::IpInfo,
+            allow_bogon: false,
+            client: None,
+        }
+    }
+
+    /// Uses the given coordinates instead of IP geolocation.
+    ///
+    /// The privacy options still apply to them.
+    pub fn with_coordinates(self, lat: f64, lon: f64) -> Self {
+        Self {
+            source: Source::Fixed(lat, lon),
+            ..self
+        }
+    }
+
+    /// Locates the IP address with `source` instead of ipinfo.io.
+    pub fn ip_source(self, source: IpSource) -> Self {
+        Self {
+            source: source.into(),
+            ..self
+        }
+    }
+
+    /// Accepts IP locations that look bogus instead of failing, see `--allow-bogon`.
+    pub fn allow_bogon(self, allow_bogon: bool) -> Self {
+        Self {
+            allow_bogon,
+            ..self
+        }
+    }
+
+    /// Looks up IP locations with `client`, see [`http_client`], so that every lookup
+    /// shares its connection pool.
+    pub fn http_client(self, client: reqwest::Client) -> Self {
+        Self {
+            client: Some(client),
+            ..self
+        }
+    }
+
+    /// The geohash length used for a requested accuracy, after the privacy cap.
+    fn precision(&self, accuracy: u8) -> Result<u8, LocationError> {
+        disclosed_precision(accuracy, self.privacy_precision)
+    }
+}
+
+/// The lowest accuracy level, a geohash of one character.
+pub const MIN_ACCURACY: u8 = 1;
+
+/// The highest accuracy level, a geohash of `geohash_inputs::MAX_GEOHASH_LENGTH`
+/// characters.
+pub const MAX_ACCURACY: u8 = geohash_inputs::MAX_GEOHASH_LENGTH as u8;
+
+/// The geohash length of an accuracy level.
+///
+/// Accuracy levels are numbered after the geohash length they produce, so each
+/// level maps to that many characters, and each level up divides the cell into
+/// 32. The approximate cell sizes are:
+///
+/// | Accuracy | Cell size        | Scale         |
+/// |----------|------------------|---------------|
+/// | 1        | 5000km x 5000km  | continent     |
+/// | 3        | 156km x 156km    | region        |
+/// | 5        | 4.9km x 4.9km    | city          |
+/// | 6        | 1.2km x 0.61km   | neighbourhood |
+/// | 7        | 153m x 153m      | block         |
+/// | 8        | 38m x 19m        | street        |
+/// | 10       | 1.2m x 0.6m      | room          |
+/// | 12       | 3.7cm x 1.9cm    | survey        |
+///
+/// # Arguments
+///
+/// * `accuracy` - The accuracy level, from [`MIN_ACCURACY`] to [`MAX_ACCURACY`]
+///
+/// # Returns
+///
+/// * `Result<u8, LocationError>` - The geohash length, or `LocationError::Output`
+///   for a level outside that range
+pub fn precision_from_accuracy(accuracy: u8) -> Result<u8, LocationError> {
+    if !(MIN_ACCURACY..=MAX_ACCURACY).contains(&accuracy) {
+        return Err(LocationError::Output(format!(
+            "accuracy {} is outside {} to {}",
+            accuracy, MIN_ACCURACY, MAX_ACCURACY
+        )));
+    }
+    Ok(accuracy)
+}
+
+/// The length of the geohash disclosed for a requested accuracy, after the
+/// privacy cap if any.
+///
+/// # Returns
+///
+/// * `Result<u8, LocationError>` - The geohash length, or the error of
+///   [`precision_from_accuracy`]
+pub fn disclosed_precision(
+    accuracy: u8,
+    privacy_precision: Option<u8>,
+) -> Result<u8, LocationError> {
+    let precision = precision_from_accuracy(accuracy)?;
+    Ok(privacy_precision.map_or(precision, |cap| precision.min(cap)))
+}
+
+/// Implementation of the `Location` trait producing raw coordinates.
+///
+/// Instead of a geohash, the output is the canonical coordinate encoding produced by
+/// [`canonical_coordinates`], for verifiers that compute the geohash themselves.
+/// The accuracy is ignored since the encoding has a fixed precision.
+#[derive(Default)]
+pub struct Coordinates {
+    jitter_meters: f64,
+    source: Source,
+    allow_bogon: bool,
+    client: Option<reqwest::Client>,
+}
+
+impl Coordinates {
+    /// Creates a coordinate location source.
+    ///
+    /// # Arguments
+    ///
+    /// * `jitter_meters` - Radius within which coordinates are randomly perturbed,
+    ///   `0.0` to disable
+    pub fn new(jitter_meters: f64) -> Self {
+        Self {
+            jitter_meters,
+            source: Source::IpInfo,
+            allow_bogon: false,
+            client: None,
+        }
+    }
+
+    /// Uses the given coordinates instead of IP geolocation.
+    ///
+    /// The jitter still applies to them.
+    pub fn with_coordinates(self, lat: f64, lon: f64) -> Self {
+        Self {
+            source: Source::Fixed(lat, lon),
+            ..self
+        }
+    }
+
+    /// Locates the IP address with `source` instead of ipinfo.io.
+    pub fn ip_source(self, source: IpSource) -> Self {
+        Self {
+            source: source.into(),
+            ..self
+        }
+    }
+
+    /// Accepts IP locations that look bogus instead of failing, see `--allow-bogon`.
+    pub fn allow_bogon(self, allow_bogon: bool) -> Self {
+        Self {
+            allow_bogon,
+            ..self
+        }
+    }
+
+    /// Looks up IP locations with `client`, see [`http_client`], so that every lookup
+    /// shares its connection pool.
+    pub fn http_client(self, client: reqwest::Client) -> Self {
+        Self {
+            client: Some(client),
+            ..self
+        }
+    }
+}
+
+/// Implementation of the `Location` trait for a geohash provided by the caller.
+///
+/// For integrations that locate the attendee themselves and only need the
+/// geohash signed. The geohash is returned as given, so callers validate it
+/// first; the accuracy is ignored since the geohash has its own precision.
+pub struct ProvidedGeohash(pub String);
+
+/// Number of fractional digits in the canonical coordinate encoding (~0.11m).
+const COORDINATE_DECIMALS: usize = 6;
+
+/// Encodes coordinates canonically so signatures over them are reproducible.
+///
+/// The encoding is `<latitude>,<longitude>` in decimal degrees, each rounded half
+/// away from zero to exactly six fractional digits, with a leading `-` for negative
+/// values, no `+` sign, no spaces, and zero always written as `0.000000`.
+/// For example `51.507400,-0.127800`.
+///
+/// # Arguments
+///
+/// * `lat` - Latitude in degrees
+/// * `lon` - Longitude in degrees
+///
+/// # Returns
+///
+/// The canonical encoding as a string
+pub(crate) fn canonical_coordinates(lat: f64, lon: f64) -> String {
+    let scale = 10f64.powi(COORDINATE_DECIMALS as i32);
+    // Adding 0.0 turns a rounded negative zero into positive zero
+    let round = |value: f64| (value * scale).round() / scale + 0.0;
+    format!(
+        "{:.*},{:.*}",
+        COORDINATE_DECIMALS,
+        round(lat),
+        COORDINATE_DECIMALS,
+        round(lon)
+    )
+}
+
+/// Moves a coordinate to a uniformly random point within `radius_meters`.
+///
+/// Uses an equirectangular approximation, which is accurate for the small radii
+/// used for privacy jitter.
+///
+/// # Arguments
+///
+/// * `lat` - Latitude in degrees
+/// * `lon` - Longitude in degrees
+/// * `radius_meters` - Maximum displacement, no jitter if not positive
+/// * `rng` - Source of randomness
+///
+/// # Returns
+///
+/// The perturbed `(latitude, longitude)`
+fn jitter<R: Rng>(lat: f64, lon: f64, radius_meters: f64, rng: &mut R) -> (f64, f64) {
+    if radius_meters <= 0.0 {
+        return (lat, lon);
+    }
+    // The square root keeps points uniformly distributed over the disc
+    let distance = radius_meters * rng.gen::<f64>().sqrt();
+    let bearing = rng.gen::<f64>() * 2.0 * PI;
+
+    let dlat = distance * bearing.cos() / METERS_PER_DEGREE;
+    let dlon =
+        distance * bearing.sin() / (METERS_PER_DEGREE * lat.to_radians().cos().max(f64::EPSILON));
+
+    let lat = (lat + dlat).clamp(-90.0, 90.0);
+    let lon = (lon + dlon + 180.0).rem_euclid(360.0) - 180.0;
+    (lat, lon)
+}
 
 #[async_trait]
 impl Location for Geohash {
@@ -76,13 +654,16 @@ impl Location for Geohash {
     /// Gets the current location and encodes it as a geohash string.
     ///
     /// This function:
-    /// 1. Retrieves the current geographical coordinates using IP geolocation
-    /// 2. Encodes these coordinates as a geohash string with the specified accuracy
+    /// 1. Retrieves the current geographical coordinates using IP geolocation,
+    ///    or takes the coordinates it was given
+    /// 2. Encodes these coordinates as a geohash string of the length of the
+    ///    specified accuracy, see [`precision_from_accuracy`]
     ///
     /// # Arguments
     ///
     /// * `accuarcy` - The desired accuracy of the geohash (1-12), which determines
     ///   the length of the generated geohash string. Higher values provide more precise location.
+    ///   Capped by the configured privacy precision.
     ///
     /// # Returns
     ///
@@ -92,14 +673,384 @@ impl Location for Geohash {
     /// # Errors
     ///
     /// This function will return an error if:
+    /// - The accuracy is outside 1 to 12 (LocationError::Output)
     /// - Failed to obtain the current location (LocationError::Location)
     /// - Failed to encode the coordinates as a geohash (LocationError::Output)
-    async fn current_location(accuarcy: u8) -> Result<Self::Output, LocationError> {
-        let (x, y) = ip_info::get_ip()
-            .await
-            .map_err(|_| LocationError::Location)?;
+    async fn current_location(&self, accuarcy: u8) -> Result<Self::Output, LocationError> {
+        let (lat, lon) = self
+            .source
+            .coordinates(self.allow_bogon, self.client.as_ref())
+            .await?;
+        let (lat, lon) = jitter(lat, lon, self.jitter_meters, &mut rand::thread_rng());
 
-        geohash::encode(Coord { x, y }, accuarcy as usize)
+        // The geohash crate takes longitude as `x` and latitude as `y`
+        let coord = Coord { x: lon, y: lat };
+        geohash::encode(coord, self.precision(accuarcy)? as usize)
             .map_err(|e| LocationError::Output(e.to_string()))
     }
 }
+
+#[async_trait]
+impl Location for Coordinates {
+    /// The output type is the canonical coordinate encoding.
+    type Output = String;
+
+    /// Gets the current location as canonically encoded coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `_accuracy` - Ignored, the encoding has a fixed precision
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, LocationError>` - The canonical coordinates if successful,
+    ///   or a LocationError if obtaining the location failed.
+    async fn current_location(&self, _accuracy: u8) -> Result<Self::Output, LocationError> {
+        let (lat, lon) = self
+            .source
+            .coordinates(self.allow_bogon, self.client.as_ref())
+            .await?;
+        let (lat, lon) = jitter(lat, lon, self.jitter_meters, &mut rand::thread_rng());
+
+        Ok(canonical_coordinates(lat, lon))
+    }
+}
+
+#[async_trait]
+impl Location for ProvidedGeohash {
+    /// The output type is the provided geohash.
+    type Output = String;
+
+    /// Returns the provided geohash.
+    ///
+    /// # Arguments
+    ///
+    /// * `_accuracy` - Ignored, the geohash has its own precision
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, LocationError>` - The provided geohash, unchanged
+    async fn current_location(&self, _accuracy: u8) -> Result<Self::Output, LocationError> {
+        Ok(self.0.clone())
+    }
+}
+
+#[test]
+fn test_canonical_coordinates() {
+    assert_eq!(canonical_coordinates(51.5074, -0.1278), "51.507400,-0.127800");
+    assert_eq!(canonical_coordinates(-33.8688, 151.2093), "-33.868800,151.209300");
+    assert_eq!(canonical_coordinates(1.23456789, 0.0000004), "1.234568,0.000000");
+    assert_eq!(canonical_coordinates(-0.0000004, -0.0), "0.000000,0.000000");
+}
+
+#[test]
+fn test_privacy_precision_caps_accuracy() {
+    assert_eq!(Geohash::default().precision(8).ok(), Some(8));
+    assert_eq!(Geohash::new(Some(5), 0.0).precision(8).ok(), Some(5));
+    assert_eq!(Geohash::new(Some(9), 0.0).precision(8).ok(), Some(8));
+}
+
+#[test]
+fn test_precision_from_accuracy() {
+    for accuracy in MIN_ACCURACY..=MAX_ACCURACY {
+        assert_eq!(precision_from_accuracy(accuracy).ok(), Some(accuracy));
+    }
+    assert_eq!(MAX_ACCURACY, 12);
+    for accuracy in [0, 13, u8::MAX] {
+        assert!(matches!(
+            precision_from_accuracy(accuracy),
+            Err(LocationError::Output(_))
+        ));
+    }
+    assert_eq!(
+        precision_from_accuracy(13).unwrap_err().to_string(),
+        "failed to generate output: accuracy 13 is outside 1 to 12"
+    );
+    // The privacy cap applies to a valid accuracy only
+    assert_eq!(disclosed_precision(8, Some(5)).ok(), Some(5));
+    assert!(disclosed_precision(0, Some(5)).is_err());
+}
+
+#[tokio::test]
+async fn test_accuracy_sets_the_geohash_length() {
+    let geohash = Geohash::default().with_coordinates(51.5074, -0.1278);
+    let location = |accuracy| geohash.current_location(accuracy);
+    assert_eq!(location(5).await.expect("location"), "gcpvj");
+    assert_eq!(location(8).await.expect("location"), "gcpvj0du");
+    assert!(matches!(location(13).await, Err(LocationError::Output(_))));
+}
+
+#[tokio::test]
+async fn test_geohash_of_known_cities() {
+    // Sources return `(latitude, longitude)`, in the southern and eastern
+    // hemispheres too, where swapping them lands in another ocean
+    for (lat, lon, expected) in [
+        (51.5074, -0.1278, "gcpvj0"),
+        (-33.8688, 151.2093, "r3gx2f"),
+        (40.6892, -74.0445, "dr5r7p"),
+    ] {
+        let geohash = Geohash::default().with_coordinates(lat, lon);
+        assert_eq!(geohash.current_location(6).await.expect("location"), expected);
+    }
+}
+
+#[test]
+fn test_jitter_stays_within_radius() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let rng = &mut StdRng::seed_from_u64(42);
+    let (lat, lon) = (51.5074, -0.1278);
+    let radius = 500.0;
+    for _ in 0..1000 {
+        let (jittered_lat, jittered_lon) = jitter(lat, lon, radius, rng);
+        let dy = (jittered_lat - lat) * METERS_PER_DEGREE;
+        let dx = (jittered_lon - lon) * METERS_PER_DEGREE * lat.to_radians().cos();
+        assert!((dx * dx + dy * dy).sqrt() <= radius + 1e-6);
+    }
+}
+
+#[test]
+fn test_jitter_disabled() {
+    let rng = &mut rand::thread_rng();
+    assert_eq!(jitter(51.5074, -0.1278, 0.0, rng), (51.5074, -0.1278));
+}
+
+#[tokio::test]
+async fn test_fixed_coordinates() {
+    let coordinates = Coordinates::default().with_coordinates(51.5074, -0.1278);
+    assert_eq!(
+        coordinates.current_location(6).await.expect("location"),
+        "51.507400,-0.127800"
+    );
+
+    let geohash = Geohash::new(Some(4), 0.0).with_coordinates(51.5074, -0.1278);
+    assert_eq!(geohash.current_location(6).await.expect("location").len(), 4);
+}
+
+#[test]
+fn test_bogus_ip_locations_are_rejected() {
+    use ip_info::IpInfo;
+
+    let ip_info = |json: &str| serde_json::from_str::<IpInfo>(json).expect("ip info");
+
+    let located = ip_info(r#"{"ip": "8.8.8.8", "loc": "37.4056,-122.0775"}"#);
+    assert_eq!(located.coordinates(false).ok(), Some((37.4056, -122.0775)));
+
+    let bogon = ip_info(r#"{"ip": "10.0.0.1", "bogon": true}"#);
+    assert!(matches!(bogon.coordinates(false), Err(LocationError::Implausible(_))));
+    // Allowed, but a bogon has no location to use
+    assert!(matches!(bogon.coordinates(true), Err(LocationError::Location)));
+
+    let null_island = ip_info(r#"{"ip": "203.0.113.7", "loc": "0.0000,0.0000"}"#);
+    assert!(matches!(
+        null_island.coordinates(false),
+        Err(LocationError::Implausible(_))
+    ));
+    assert_eq!(null_island.coordinates(true).ok(), Some((0.0, 0.0)));
+
+    // Only exactly 0,0 is suspicious
+    let equator = ip_info(r#"{"loc": "0.0000,32.5825"}"#);
+    assert_eq!(equator.coordinates(false).ok(), Some((0.0, 32.5825)));
+
+    let malformed = ip_info(r#"{"loc": "37.4056"}"#);
+    assert!(matches!(malformed.coordinates(false), Err(LocationError::Location)));
+}
+
+/// Serves one canned HTTP response per connection, in order, on a local port.
+///
+/// Returns the base URL to request and a handle yielding the raw requests received.
+#[cfg(test)]
+async fn serve(responses: Vec<String>) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let url = format!("http://{}", listener.local_addr().expect("address"));
+    let server = tokio::spawn(async move {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.expect("read");
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(response.as_bytes()).await.expect("write");
+            stream.shutdown().await.expect("shutdown");
+            requests.push(String::from_utf8_lossy(&request).to_lowercase());
+        }
+        requests
+    });
+    (url, server)
+}
+
+#[cfg(test)]
+fn http_response(status: &str, headers: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        headers,
+        body.len(),
+        body
+    )
+}
+
+#[tokio::test]
+async fn test_rate_limited_lookup_backs_off() {
+    let too_many = |retry_after: &str| {
+        http_response(
+            "429 Too Many Requests",
+            &format!("Retry-After: {}\r\n", retry_after),
+            "",
+        )
+    };
+    let located = http_response(
+        "200 OK",
+        "Content-Type: application/json\r\n",
+        r#"{"ip": "8.8.8.8", "loc": "37.4056,-122.0775"}"#,
+    );
+
+    // A short Retry-After is waited out, and the oracle identifies itself every time
+    let client = http_client(USER_AGENT, HTTP_POOL_SIZE).expect("client");
+    let (url, server) = serve(vec![too_many("0"), located]).await;
+    let located = ip_info::get_ip(&client, &url, false).await;
+    assert_eq!(located.ok(), Some((37.4056, -122.0775)));
+    let requests = server.await.expect("server");
+    assert_eq!(requests.len(), 2);
+    assert!(requests
+        .iter()
+        .all(|r| r.contains(&format!("user-agent: {}", USER_AGENT))));
+
+    // A long Retry-After fails at once
+    let (url, server) = serve(vec![too_many("3600")]).await;
+    assert!(matches!(
+        ip_info::get_ip(
+            &http_client("custom-agent/1", HTTP_POOL_SIZE).expect("client"),
+            &url,
+            false
+        )
+        .await,
+        Err(LocationError::RateLimited(when)) if when == "retry after 3600 seconds"
+    ));
+    assert!(server.await.expect("server")[0].contains("user-agent: custom-agent/1"));
+
+    // So does a second rate-limited response
+    let (url, server) = serve(vec![too_many("0"), too_many("0")]).await;
+    assert!(matches!(
+        ip_info::get_ip(&client, &url, false).await,
+        Err(LocationError::RateLimited(when)) if when == "retry after 0 seconds"
+    ));
+    assert_eq!(server.await.expect("server").len(), 2);
+}
+
+#[tokio::test]
+async fn test_ip_api_lookup() {
+    let json = |body: &str| http_response("200 OK", "Content-Type: application/json\r\n", body);
+    let client = http_client(USER_AGENT, HTTP_POOL_SIZE).expect("client");
+    let (url, server) = serve(vec![
+        json(r#"{"status": "success", "lat": 51.5074, "lon": -0.1278}"#),
+        json(r#"{"status": "fail", "message": "private range"}"#),
+        http_response("429 Too Many Requests", "X-Ttl: 42\r\n", ""),
+    ])
+    .await;
+
+    assert_eq!(
+        ip_api::get_ip(&client, &url, false).await.ok(),
+        Some((51.5074, -0.1278))
+    );
+    assert!(matches!(
+        ip_api::get_ip(&client, &url, true).await,
+        Err(LocationError::Implausible(why)) if why == "the IP address is in a private range"
+    ));
+    assert!(matches!(
+        ip_api::get_ip(&client, &url, false).await,
+        Err(LocationError::RateLimited(when)) if when == "retry after 42 seconds"
+    ));
+    server.await.expect("server");
+}
+
+#[test]
+fn test_ip_source_names() {
+    for source in IpSource::ALL {
+        assert_eq!(source.name().parse::<IpSource>(), Ok(source));
+    }
+    assert_eq!(IpSource::default(), IpSource::IpInfo);
+    assert_eq!(
+        "gps".parse::<IpSource>(),
+        Err("expected `ipinfo` or `ip-api`, got `gps`".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_ip_location_encodes_to_known_geohash() {
+    // ipinfo.io reports `loc` as `latitude,longitude`; London must come out in
+    // `gcpv`, not at the swapped point in the Indian Ocean
+    let located = http_response(
+        "200 OK",
+        "Content-Type: application/json\r\n",
+        r#"{"ip": "81.2.69.142", "loc": "51.5074,-0.1278"}"#,
+    );
+    let (url, server) = serve(vec![located]).await;
+    let client = http_client(USER_AGENT, HTTP_POOL_SIZE).expect("client");
+    let (lat, lon) = ip_info::get_ip(&client, &url, false)
+        .await
+        .expect("located");
+    server.await.expect("server");
+
+    let geohash = Geohash::default().with_coordinates(lat, lon);
+    assert_eq!(geohash.current_location(8).await.expect("location"), "gcpvj0du");
+}
+
+#[tokio::test]
+async fn test_lookups_reuse_pooled_connections() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Serves every lookup on the first connection, and counts any further ones
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let url = format!("http://{}", listener.local_addr().expect("address"));
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.expect("accept");
+        let body = r#"{"ip": "8.8.8.8", "loc": "37.4056,-122.0775"}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let (mut request, mut buf) = (Vec::new(), [0u8; 1024]);
+        loop {
+            tokio::select! {
+                read = stream.read(&mut buf) => {
+                    let n = read.expect("read");
+                    if n == 0 {
+                        return 1;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    while let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        request.drain(..end + 4);
+                        stream.write_all(response.as_bytes()).await.expect("write");
+                    }
+                }
+                _ = listener.accept() => return 2,
+            }
+        }
+    });
+
+    let client = http_client(USER_AGENT, HTTP_POOL_SIZE).expect("client");
+    for _ in 0..3 {
+        let located = ip_info::get_ip(&client, &url, false).await;
+        assert_eq!(located.ok(), Some((37.4056, -122.0775)));
+    }
+    drop(client);
+    assert_eq!(
+        server.await.expect("server"),
+        1,
+        "a lookup opened a new connection"
+    );
+}
@@ -0,0 +1,88 @@
+//! Runtime hasher selection.
+//!
+//! This module maps the `--hasher` command-line option onto one of the
+//! `Hasher` implementations, so the oracle can match the `PayloadHasher`
+//! configured in the target runtime.
+
+use std::str::FromStr;
+
+use oracle::blake2_256::Blake2_256;
+use oracle::keccak_256::Keccak256;
+use oracle::sha2_256::Sha256;
+use oracle::{Hash, Hasher};
+
+/// The hash function used to digest the signed payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashAlgorithm {
+    /// Blake2-256, the default `PayloadHasher` of Substrate runtimes.
+    #[default]
+    Blake2_256,
+    /// Keccak-256.
+    Keccak256,
+    /// SHA-256.
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// Every hash function compiled into the oracle, default first.
+    pub const ALL: [HashAlgorithm; 3] = [Self::Blake2_256, Self::Keccak256, Self::Sha256];
+
+    /// The name selecting this hash function with `--hasher`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Blake2_256 => "blake2_256",
+            Self::Keccak256 => "keccak256",
+            Self::Sha256 => "sha256",
+        }
+    }
+
+    /// Computes the digest of a message with the selected hash function.
+    ///
+    /// # Arguments
+    /// * `message` - The data to hash
+    ///
+    /// # Returns
+    /// A 32-byte Hash of the input data.
+    pub fn hash<T>(self, message: T) -> Hash
+    where
+        T: AsRef<[u8]>,
+    {
+        match self {
+            Self::Blake2_256 => Blake2_256::hash(message),
+            Self::Keccak256 => Keccak256::hash(message),
+            Self::Sha256 => Sha256::hash(message),
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|hasher| hasher.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "expected `blake2_256`, `keccak256` or `sha256`, got `{}`",
+                    s
+                )
+            })
+    }
+}
+
+#[test]
+fn test_hash_algorithm_dispatch() {
+    let message = b"gcpvj0";
+    let parse = |s: &str| s.parse::<HashAlgorithm>().unwrap();
+
+    assert_eq!(HashAlgorithm::default(), HashAlgorithm::Blake2_256);
+    assert!(parse("blake2_256").hash(message) == Blake2_256::hash(message));
+    assert!(parse("keccak256").hash(message) == Keccak256::hash(message));
+    assert!(parse("sha256").hash(message) == Sha256::hash(message));
+    assert!(HashAlgorithm::Keccak256.hash(message) != HashAlgorithm::Sha256.hash(message));
+    assert!("md5".parse::<HashAlgorithm>().is_err());
+    for hasher in HashAlgorithm::ALL {
+        assert_eq!(parse(hasher.name()), hasher);
+    }
+}
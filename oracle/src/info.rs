@@ -0,0 +1,112 @@
+//! Capabilities compiled into the oracle binary.
+//!
+//! `oracle info` lists them, so users can discover which values the
+//! `--hasher` option and the attestation commands accept without reading
+//! the source.
+
+use std::fmt;
+
+use oracle::PAYLOAD_VERSION;
+use serde::Serialize;
+
+use crate::hasher::HashAlgorithm;
+
+/// The signature schemes the oracle signs attestations with.
+pub const SIGNATURE_SCHEMES: [&str; 1] = ["ed25519"];
+
+/// A source the oracle obtains the signed location from.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct LocationSource {
+    /// Short name of the source
+    pub name: &'static str,
+    /// How the source is selected on the command line
+    pub usage: &'static str,
+}
+
+/// The location sources of the attestation commands.
+pub const LOCATION_SOURCES: [LocationSource; 4] = [
+    LocationSource {
+        name: "ipinfo",
+        usage: "oracle run, IP geolocation through ipinfo.io",
+    },
+    LocationSource {
+        name: "ip-api",
+        usage: "oracle run --source-order=ip-api, IP geolocation through ip-api.com",
+    },
+    LocationSource {
+        name: "stdin",
+        usage: "oracle run --coords-from-stdin",
+    },
+    LocationSource {
+        name: "geohash",
+        usage: "oracle sign-geohash --geohash=<geohash>",
+    },
+];
+
+/// What the oracle supports, as printed by `oracle info`.
+#[derive(Serialize, Debug)]
+pub struct Info {
+    /// The oracle's version
+    pub version: &'static str,
+    /// The layout version of the signed payload
+    pub payload_version: u8,
+    /// The signature schemes attestations are signed with
+    pub signature_schemes: Vec<&'static str>,
+    /// The `--hasher` values, default first
+    pub hashers: Vec<&'static str>,
+    /// The sources of the signed location
+    pub location_sources: &'static [LocationSource],
+}
+
+impl Info {
+    /// The capabilities of this binary.
+    pub fn compiled() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            payload_version: PAYLOAD_VERSION,
+            signature_schemes: SIGNATURE_SCHEMES.to_vec(),
+            hashers: HashAlgorithm::ALL.map(HashAlgorithm::name).to_vec(),
+            location_sources: &LOCATION_SOURCES,
+        }
+    }
+}
+
+impl fmt::Display for Info {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "oracle {}", self.version)?;
+        writeln!(f, "payload version: {}", self.payload_version)?;
+        writeln!(
+            f,
+            "signature schemes: {}",
+            self.signature_schemes.join(", ")
+        )?;
+        writeln!(
+            f,
+            "hashers: {} (default), {}",
+            self.hashers[0],
+            self.hashers[1..].join(", ")
+        )?;
+        writeln!(f, "location sources:")?;
+        for source in self.location_sources {
+            writeln!(f, "  {:<8} {}", source.name, source.usage)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_info_lists_compiled_capabilities() {
+    let info = Info::compiled();
+    assert_eq!(info.signature_schemes, ["ed25519"]);
+    assert_eq!(info.hashers, ["blake2_256", "keccak256", "sha256"]);
+    assert_eq!(info.hashers[0], HashAlgorithm::default().name());
+
+    let text = info.to_string();
+    assert!(text.contains("hashers: blake2_256 (default), keccak256, sha256\n"));
+    assert!(text.contains("  stdin    oracle run --coords-from-stdin\n"));
+
+    let json: serde_json::Value = serde_json::to_value(&info).expect("info serializes");
+    assert_eq!(json["payload_version"], PAYLOAD_VERSION);
+    assert_eq!(json["location_sources"][1]["name"], "ip-api");
+    assert_eq!(json["location_sources"][3]["name"], "geohash");
+}
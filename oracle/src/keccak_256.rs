@@ -0,0 +1,40 @@
+//! Keccak-256 hash implementation.
+//!
+//! This module provides an implementation of the `Hasher` trait
+//! using the Keccak-256 cryptographic hash function.
+
+use sp_io::hashing::keccak_256;
+use crate::{Hash, Hasher};
+
+/// Implementation of the `Hasher` trait using Keccak-256.
+///
+/// Keccak-256 is the 32-byte hash function used by Ethereum, for runtimes
+/// whose payload hasher is `Keccak256`.
+pub struct Keccak256;
+
+impl Hasher for Keccak256 {
+    /// Computes a Keccak-256 hash of the provided message.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The data to hash, which can be any type that can be
+    ///   referenced as a byte slice.
+    ///
+    /// # Returns
+    ///
+    /// A 32-byte `Hash` containing the Keccak-256 hash of the input data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use oracle::{keccak_256::Keccak256, Hasher};
+    /// let message = "Hello, world!";
+    /// let hash = Keccak256::hash(message);
+    /// ```
+    fn hash<T>(message: T) -> Hash
+    where
+        T: AsRef<[u8]>,
+    {
+        Hash::new(keccak_256(message.as_ref()))
+    }
+}
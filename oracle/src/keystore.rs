@@ -0,0 +1,157 @@
+//! Storage of the oracle key in the operating system's secret store.
+//!
+//! On desktops and operator machines the key can be kept in the system keyring
+//! (the macOS Keychain, the Windows Credential Manager or the Secret Service on
+//! Linux) instead of a file or an environment variable. A key is stored as a
+//! `0x`-prefixed hex string under a service and an account name.
+//!
+//! Not every system has a keyring, e.g. a headless server without a Secret
+//! Service. Loading and saving then report that none is available, so callers
+//! can fall back to the other ways of providing a key.
+
+use keyring::Entry;
+use oracle::SecretKey;
+use thiserror::Error;
+
+use crate::env::{self, EnvError};
+
+/// The default keyring service the oracle key is stored under.
+pub const SERVICE: &str = "aoi-oracle";
+
+/// The default keyring account the oracle key is stored under.
+pub const ACCOUNT: &str = "oracle";
+
+/// Errors that can occur while using the keyring.
+#[derive(Error, Debug, PartialEq)]
+pub enum KeystoreError {
+    /// The keyring holds no key for the service and account.
+    #[error("no key in the keyring for service `{service}` and account `{account}`")]
+    NotFound {
+        /// The keyring service that was searched
+        service: String,
+        /// The keyring account that was searched
+        account: String,
+    },
+
+    /// The keyring holds something that is not a hex key.
+    ///
+    /// # Fields
+    /// * EnvError - Why the stored key could not be parsed
+    #[error("invalid key in the keyring: {0}")]
+    InvalidKey(EnvError),
+
+    /// The keyring is available but could not be read or written.
+    ///
+    /// # Fields
+    /// * String - The underlying keyring error
+    #[error("keyring error: {0}")]
+    Keyring(String),
+}
+
+/// Loads the key stored under `service` and `account`.
+///
+/// # Arguments
+/// * `service` - The keyring service name
+/// * `account` - The keyring account name
+///
+/// # Returns
+/// * `Result<Option<SecretKey>, KeystoreError>` - The key, `None` if this system has
+///   no usable keyring, or an error if the keyring holds no valid key
+pub fn load_key(service: &str, account: &str) -> Result<Option<SecretKey>, KeystoreError> {
+    let secret = Entry::new(service, account).and_then(|entry| entry.get_password());
+    stored_key(secret, service, account)
+}
+
+/// Stores `key` under `service` and `account`, replacing any stored key.
+///
+/// # Arguments
+/// * `service` - The keyring service name
+/// * `account` - The keyring account name
+/// * `key` - The private key to store
+///
+/// # Returns
+/// * `Result<bool, KeystoreError>` - Whether the key was stored, `false` if
+///   this system has no usable keyring
+pub fn save_key(service: &str, account: &str, key: &SecretKey) -> Result<bool, KeystoreError> {
+    let secret = format!("0x{}", env::array_to_hex(key.as_bytes()));
+    let outcome = Entry::new(service, account).and_then(|entry| entry.set_password(&secret));
+    saved(outcome)
+}
+
+/// Whether `error` means this system has no usable keyring, rather than that
+/// the keyring failed.
+fn is_unavailable(error: &keyring::Error) -> bool {
+    matches!(
+        error,
+        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+    )
+}
+
+/// The key in the outcome of reading a keyring entry.
+fn stored_key(
+    secret: keyring::Result<String>,
+    service: &str,
+    account: &str,
+) -> Result<Option<SecretKey>, KeystoreError> {
+    match secret {
+        Ok(secret) => env::try_hex_to_key(secret.trim())
+            .map(Some)
+            .map_err(KeystoreError::InvalidKey),
+        Err(keyring::Error::NoEntry) => Err(KeystoreError::NotFound {
+            service: service.to_string(),
+            account: account.to_string(),
+        }),
+        Err(e) if is_unavailable(&e) => Ok(None),
+        Err(e) => Err(KeystoreError::Keyring(e.to_string())),
+    }
+}
+
+/// Whether the outcome of writing a keyring entry stored the key.
+fn saved(outcome: keyring::Result<()>) -> Result<bool, KeystoreError> {
+    match outcome {
+        Ok(()) => Ok(true),
+        Err(e) if is_unavailable(&e) => Ok(false),
+        Err(e) => Err(KeystoreError::Keyring(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+fn unavailable() -> keyring::Error {
+    keyring::Error::PlatformFailure("no secret service".into())
+}
+
+#[test]
+fn test_stored_key() {
+    let stored =
+        |secret| stored_key(secret, SERVICE, ACCOUNT).map(|key| key.map(|key| *key.as_bytes()));
+
+    assert_eq!(
+        stored(Ok(format!("0x{}\n", "11".repeat(32)))),
+        Ok(Some([0x11; 32]))
+    );
+    assert_eq!(
+        stored(Ok("0x2a".to_string())),
+        Err(KeystoreError::InvalidKey(EnvError::InvalidHexLength))
+    );
+    assert_eq!(
+        stored(Err(keyring::Error::NoEntry)),
+        Err(KeystoreError::NotFound {
+            service: SERVICE.to_string(),
+            account: ACCOUNT.to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_missing_keyring_falls_back() {
+    assert!(matches!(
+        stored_key(Err(unavailable()), SERVICE, ACCOUNT),
+        Ok(None)
+    ));
+    assert_eq!(saved(Err(unavailable())), Ok(false));
+    assert_eq!(saved(Ok(())), Ok(true));
+    assert!(matches!(
+        saved(Err(keyring::Error::TooLong("account".to_string(), 8))),
+        Err(KeystoreError::Keyring(_))
+    ));
+}
@@ -1,27 +1,80 @@
-use serde::ser::Serialize;
+use std::str::FromStr;
 
-/// A 32-byte cryptographic key used for operations like signing.
+use serde::{Deserialize, Serialize};
+
+pub mod blake2_256;
+pub mod cache;
+pub mod ed25519;
+pub mod fallback;
+pub mod keccak_256;
+pub mod nmea;
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
+pub mod qr;
+pub mod sha2_256;
+
+/// A 32-byte private key, used to sign.
+///
+/// Kept apart from [`PublicKey`] so that one cannot be passed where the other
+/// is expected. Internally stored as a fixed-size byte array.
+/// Parses from a hex string via `FromStr`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SecretKey([u8; 32]);
+
+/// A 32-byte public key, used to verify signatures.
+///
+/// Derived from a [`SecretKey`] by [`Signer::public_key`], or parsed from the
+/// `public_key` of a [`SignedLocation`]. Internally stored as a fixed-size byte array.
+/// Parses from a hex string via `FromStr`.
 ///
-/// This represents either a public or private key depending on context.
-/// Internally stored as a fixed-size byte array.
-#[derive(Clone, Copy)]
-pub struct Key([u8; 32]);
+/// A public key cannot be used to sign:
+///
+/// ```compile_fail
+/// use oracle::{ed25519::Ed25519, Hash, Signer};
+///
+/// let (_, public_key) = Ed25519::generate_key();
+/// Ed25519::sign(Hash::new([0; 32]), public_key);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey([u8; 32]);
 
 /// A 32-byte cryptographic hash value.
 ///
 /// Represents the output of a hash function applied to arbitrary data.
 /// Used for operations like signature generation.
-#[derive(Clone, Copy)]
+/// Parses from a hex string via `FromStr`.
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Hash([u8; 32]);
 
-impl Key {
-    /// Creates a new Key from a 32-byte array.
+impl SecretKey {
+    /// Creates a new SecretKey from a 32-byte array.
     ///
     /// # Arguments
     /// * `bytes` - A 32-byte array containing the key data
     ///
     /// # Returns
-    /// A new Key instance containing the provided bytes
+    /// A new SecretKey instance containing the provided bytes
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns a reference to the underlying byte array.
+    ///
+    /// # Returns
+    /// A reference to the 32-byte array storing the key
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl PublicKey {
+    /// Creates a new PublicKey from a 32-byte array.
+    ///
+    /// # Arguments
+    /// * `bytes` - A 32-byte array containing the key data
+    ///
+    /// # Returns
+    /// A new PublicKey instance containing the provided bytes
     pub fn new(bytes: [u8; 32]) -> Self {
         Self(bytes)
     }
@@ -56,6 +109,114 @@ impl Hash {
     }
 }
 
+/// Errors that can occur when parsing a key or `Hash` from a hex string.
+#[derive(Error, Debug, PartialEq)]
+pub enum ParseHexError {
+    /// The hex string does not encode exactly 32 bytes.
+    #[error("invalid hex string length")]
+    InvalidLength,
+
+    /// The string contains invalid hexadecimal characters.
+    ///
+    /// # Fields
+    /// * String - A description of what went wrong during parsing
+    #[error("failed to parse hex string: {0}")]
+    InvalidHex(String),
+}
+
+/// Converts a hexadecimal string, with or without a "0x" prefix, to 32 bytes.
+fn hex_to_array(hex_string: &str) -> Result<[u8; 32], ParseHexError> {
+    let hex_string = hex_string.strip_prefix("0x").unwrap_or(hex_string);
+    if hex_string.len() != 64 {
+        return Err(ParseHexError::InvalidLength);
+    }
+    let mut out = [0u8; 32];
+    hex::decode_to_slice(hex_string, &mut out)
+        .map_err(|e| ParseHexError::InvalidHex(e.to_string()))?;
+    Ok(out)
+}
+
+impl FromStr for SecretKey {
+    type Err = ParseHexError;
+
+    /// Parses a private key from a 64-character hex string, optionally prefixed with "0x".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex_to_array(s).map(Self)
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = ParseHexError;
+
+    /// Parses a public key from a 64-character hex string, optionally prefixed with "0x".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex_to_array(s).map(Self)
+    }
+}
+
+impl FromStr for Hash {
+    type Err = ParseHexError;
+
+    /// Parses a hash from a 64-character hex string, optionally prefixed with "0x".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex_to_array(s).map(Self)
+    }
+}
+
+/// A signed location as emitted by the oracle.
+///
+/// Fields are serialized in declaration order, which is part of the output contract:
+/// 1. `location` - the location data that was signed (e.g. a geohash)
+/// 2. `signature` - the signature over the hashed, domain-tagged location
+/// 3. `public_key` - the hex-encoded public key of the signing oracle
+/// 4. `signed` - what `location` holds, `"geohash"` or `"coords"`
+/// 5. `version` - the [`PAYLOAD_VERSION`] of the signed payload
+/// 6. `challenge` - the challenge the signature is bound to, only present when bound
+/// 7. `digest` - the hex digest that was signed, only present when requested
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignedLocation<O, S> {
+    /// The location data that was signed.
+    pub location: O,
+    /// The signature over the hashed payload.
+    pub signature: S,
+    /// The `0x`-prefixed hex public key that verifies the signature.
+    pub public_key: String,
+    /// The kind of location data that was signed.
+    #[serde(default)]
+    pub signed: SignedData,
+    /// The layout version of the signed payload.
+    pub version: u8,
+    /// The challenge geohash included in the signed payload, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<String>,
+    /// The `0x`-prefixed hex hash of the domain-tagged payload, for debugging.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+}
+
+/// The kind of location data covered by a signature.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SignedData {
+    /// A geohash string.
+    #[default]
+    Geohash,
+    /// Canonically encoded `latitude,longitude` coordinates.
+    Coords,
+}
+
+impl FromStr for SignedData {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "geohash" => Ok(Self::Geohash),
+            "coords" => Ok(Self::Coords),
+            _ => Err(format!("expected `geohash` or `coords`, got `{}`", s)),
+        }
+    }
+}
+
 /// Errors that can occur during location operations.
 ///
 /// This enum represents the various ways that acquiring or
@@ -78,6 +239,31 @@ pub enum LocationError {
     /// * String - A description of what went wrong during formatting
     #[error("failed to generate output: {0}")]
     Output(String),
+
+    /// The location source returned a location that is obviously bogus.
+    ///
+    /// This happens for example when IP geolocation reports a private or
+    /// reserved address, or the null island coordinates `0,0`.
+    ///
+    /// # Fields
+    /// * String - Why the location was rejected
+    #[error("implausible location: {0}")]
+    Implausible(String),
+
+    /// The location service refused the request because of rate limiting.
+    ///
+    /// # Fields
+    /// * String - When the service allows the next request
+    #[error("rate limited by the location service: {0}")]
+    RateLimited(String),
+
+    /// Every source of a fallback chain failed to locate.
+    ///
+    /// # Fields
+    /// * Vec<(String, LocationError)> - The name and failure of each source, in
+    ///   the order they were tried
+    #[error("every location source failed: {}", fallback::describe(.0))]
+    AllSourcesFailed(Vec<(String, LocationError)>),
 }
 
 /// Trait for obtaining geographical location data.
@@ -101,7 +287,7 @@ pub trait Location {
     /// # Returns
     /// * `Result<Self::Output, LocationError>` - The location data if successful,
     ///   or an error if obtaining the location failed.
-    async fn current_location(accuracy: u8) -> Result<Self::Output, LocationError>;
+    async fn current_location(&self, accuracy: u8) -> Result<Self::Output, LocationError>;
 }
 
 /// Trait for cryptographic hashing functionality.
@@ -160,40 +346,128 @@ pub trait Signer {
     /// # Returns
     /// * `Result<Self::Signature, SignerError>` - The signature if successful,
     ///   or an error if signing failed.
-    fn sign(message: Hash, key: Key) -> Result<Self::Signature, SignerError>;
+    fn sign(message: Hash, key: SecretKey) -> Result<Self::Signature, SignerError>;
     
     /// Generates a new cryptographic key pair.
     ///
     /// # Returns
     /// A tuple containing (private_key, public_key)
-    fn generate_key() -> (Key, Key);
+    fn generate_key() -> (SecretKey, PublicKey);
+
+    /// Derives the public key corresponding to a private key.
+    ///
+    /// # Arguments
+    /// * `key` - The private key
+    ///
+    /// # Returns
+    /// The public key that verifies signatures made with `key`
+    fn public_key(key: SecretKey) -> PublicKey;
+}
+
+/// Trait for cryptographic signature verification.
+///
+/// Implementors of this trait check signatures produced by a matching `Signer`.
+pub trait Verifier {
+    /// The type representing a cryptographic signature.
+    type Signature;
+
+    /// Verifies a signature over a message hash.
+    ///
+    /// # Arguments
+    /// * `message` - The hash of the signed message
+    /// * `signature` - The signature to check
+    /// * `public_key` - The public key of the signer
+    ///
+    /// # Returns
+    /// `true` if the signature is valid for the message and public key
+    fn verify(message: Hash, signature: &Self::Signature, public_key: PublicKey) -> bool;
 }
 
 /// Helper function to obtain location data using the specified Location implementation.
 ///
 /// This is a convenience wrapper around the Location trait's current_location method.
+/// Taking the provider by reference allows stateful sources, such as one holding
+/// an authenticated API client, to be used.
 ///
 /// # Type Parameters
 /// * `L` - A type that implements the Location trait
 ///
 /// # Arguments
+/// * `provider` - The location source to query
 /// * `accuracy` - The desired accuracy level for the location data
 ///
 /// # Returns
 /// * `Result<L::Output, LocationError>` - The location data if successful,
 ///   or an error if obtaining the location failed
-pub async fn location<L>(accuracy: u8) -> Result<L::Output, LocationError>
+pub async fn location<L>(provider: &L, accuracy: u8) -> Result<L::Output, LocationError>
 where
     L: Location,
 {
-    L::current_location(accuracy).await
+    provider.current_location(accuracy).await
+}
+
+/// Domain-separation tag prepended to every signed payload.
+///
+/// Binding signatures to this attendance protocol prevents a signature produced
+/// by the oracle from being replayed in another protocol that signs the same bytes.
+/// The attendance pallet must be configured with the same tag (its `DomainTag`).
+pub const DOMAIN_TAG: &[u8] = b"aoi-attendance";
+
+/// Version of the signed payload layout.
+///
+/// Prefixed to every signed payload so that verifiers can reject layouts they do
+/// not understand. The attendance pallet rejects any other version.
+pub const PAYLOAD_VERSION: u8 = 1;
+
+/// Builds the payload that is hashed and signed for a location.
+///
+/// The payload is the [`PAYLOAD_VERSION`] byte and the domain-separation tag,
+/// followed by the raw location bytes.
+/// When bound to a challenge, the challenge bytes are inserted before the location,
+/// preceded by their length as a little-endian `u32` so that the split between
+/// challenge and location is unambiguous.
+///
+/// # Arguments
+/// * `tag` - The domain-separation tag
+/// * `challenge` - The challenge the signature is bound to, if any
+/// * `location` - The location data to sign
+///
+/// # Returns
+/// The concatenation `version || tag || location`, or
+/// `version || tag || len(challenge) as u32 LE || challenge || location` when bound
+pub fn signing_payload(tag: &[u8], challenge: Option<&[u8]>, location: &[u8]) -> Vec<u8> {
+    let challenge_len = challenge.map_or(0, |challenge| 4 + challenge.len());
+    let mut payload = Vec::with_capacity(1 + tag.len() + challenge_len + location.len());
+    payload.push(PAYLOAD_VERSION);
+    payload.extend_from_slice(tag);
+    if let Some(challenge) = challenge {
+        payload.extend_from_slice(&(challenge.len() as u32).to_le_bytes());
+        payload.extend_from_slice(challenge);
+    }
+    payload.extend_from_slice(location);
+    payload
+}
+
+/// Maps location bytes to the public inputs of the geohash proof.
+///
+/// Each byte becomes one field element with the byte's value. The prover and
+/// the attendance pallet share this encoding through
+/// `geohash_inputs::geohash_to_public_inputs`, which this must match.
+///
+/// # Arguments
+/// * `location` - The location data, e.g. a geohash
+///
+/// # Returns
+/// One `u64` per byte of `location`, in order
+pub fn location_to_field_inputs(location: &[u8]) -> Vec<u64> {
+    location.iter().map(|c| *c as u64).collect()
 }
 
 /// Signs location data using specified cryptographic components.
 ///
 /// This function composes the hashing and signing operations:
-/// 1. Converts the location data to bytes
-/// 2. Hashes the bytes using the specified Hasher
+/// 1. Prefixes the location data with the [`DOMAIN_TAG`]
+/// 2. Hashes the payload using the specified Hasher
 /// 3. Signs the hash using the specified Signer and key
 ///
 /// # Type Parameters
@@ -209,13 +483,192 @@ where
 /// * `Result<S::Signature, SignerError>` - The signature if successful,
 ///   or an error if signing failed
 pub async fn sign_location<L, S, H>(
-    key: Key,
+    key: SecretKey,
+    location: L::Output,
+) -> Result<S::Signature, SignerError>
+where
+    L: Location,
+    S: Signer,
+    H: Hasher,
+{
+    sign_location_with_tag::<L, S, H>(key, location, DOMAIN_TAG).await
+}
+
+/// Signs location data using a custom domain-separation tag.
+///
+/// Identical to [`sign_location`] but allows overriding the tag, for runtimes
+/// configured with a `DomainTag` other than [`DOMAIN_TAG`]. The signature is not
+/// bound to a challenge.
+///
+/// # Arguments
+/// * `key` - The private key to use for signing
+/// * `location` - The location data to sign
+/// * `tag` - The domain-separation tag to prepend
+///
+/// # Returns
+/// * `Result<S::Signature, SignerError>` - The signature if successful,
+///   or an error if signing failed
+pub async fn sign_location_with_tag<L, S, H>(
+    key: SecretKey,
     location: L::Output,
+    tag: &[u8],
 ) -> Result<S::Signature, SignerError>
 where
     L: Location,
     S: Signer,
     H: Hasher,
 {
-    S::sign(H::hash(location.as_ref()), key)
+    S::sign(H::hash(signing_payload(tag, None, location.as_ref())), key)
+}
+
+/// Verifies a signed location using specified cryptographic components.
+///
+/// Reconstructs the payload exactly as [`signing_payload`] does, hashes it
+/// and checks the signature against the public key.
+///
+/// # Type Parameters
+/// * `V` - A type that implements the Verifier trait
+/// * `H` - A type that implements the Hasher trait
+///
+/// # Arguments
+/// * `location` - The location data that was signed
+/// * `challenge` - The challenge the signature is bound to, if any
+/// * `signature` - The signature to check
+/// * `public_key` - The public key of the signing oracle
+/// * `tag` - The domain-separation tag used when signing
+///
+/// # Returns
+/// `true` if the signature is valid
+pub fn verify_location<V, H>(
+    location: &[u8],
+    challenge: Option<&[u8]>,
+    signature: &V::Signature,
+    public_key: PublicKey,
+    tag: &[u8],
+) -> bool
+where
+    V: Verifier,
+    H: Hasher,
+{
+    V::verify(
+        H::hash(signing_payload(tag, challenge, location)),
+        signature,
+        public_key,
+    )
+}
+
+#[test]
+fn test_key_from_str() {
+    let hex = format!("{}2a", "0".repeat(62));
+    let mut array = [0; 32];
+    array[31] = 0x2a;
+
+    // Valid hex string
+    assert_eq!(
+        hex.parse::<SecretKey>().map(|key| *key.as_bytes()),
+        Ok(array)
+    );
+
+    // Valid with 0x prefix
+    assert_eq!(
+        format!("0x{}", hex)
+            .parse::<SecretKey>()
+            .map(|key| *key.as_bytes()),
+        Ok(array)
+    );
+
+    // Public keys parse the same way
+    assert_eq!(
+        hex.parse::<PublicKey>().map(|key| *key.as_bytes()),
+        Ok(array)
+    );
+
+    // Invalid length
+    assert_eq!(
+        "0102".parse::<SecretKey>().map(|key| *key.as_bytes()),
+        Err(ParseHexError::InvalidLength)
+    );
+
+    // Invalid hex characters
+    assert_eq!(
+        format!("{}zz", "0".repeat(62))
+            .parse::<SecretKey>()
+            .map(|key| *key.as_bytes()),
+        Err(ParseHexError::InvalidHex(
+            "Invalid character 'z' at position 62".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_hash_from_str() {
+    let hex = format!("0x{}", "ab".repeat(32));
+    assert_eq!(
+        hex.parse::<Hash>().map(|hash| *hash.as_bytes()),
+        Ok([0xab; 32])
+    );
+    assert_eq!(
+        "0xab".parse::<Hash>().map(|hash| *hash.as_bytes()),
+        Err(ParseHexError::InvalidLength)
+    );
+}
+
+#[test]
+fn test_location_to_field_inputs() {
+    // One input per byte, as the prover and pallet compute `(*c as u64).into()`
+    assert_eq!(
+        location_to_field_inputs(b"gcpvj0"),
+        vec![103, 99, 112, 118, 106, 48]
+    );
+    for geohash in ["0", "u4pruydqqvj", "zzzzzzzzzzzz"] {
+        let inputs = location_to_field_inputs(geohash.as_bytes());
+        assert_eq!(inputs.len(), geohash.len());
+        assert!(geohash.bytes().zip(inputs).all(|(c, input)| u64::from(c) == input));
+    }
+    assert!(location_to_field_inputs(b"").is_empty());
+}
+
+#[test]
+fn test_signing_payload_layout() {
+    assert_eq!(signing_payload(b"tag", None, b"bcdefg"), b"\x01tagbcdefg".to_vec());
+    assert_eq!(
+        signing_payload(b"tag", Some(b"bcd".as_slice()), b"bcdefg"),
+        b"\x01tag\x03\x00\x00\x00bcdbcdefg".to_vec()
+    );
+
+    // The length prefix keeps different challenge/location splits apart
+    assert_ne!(
+        signing_payload(b"tag", Some(b"b".as_slice()), b"bbb"),
+        signing_payload(b"tag", Some(b"bb".as_slice()), b"bb")
+    );
+}
+
+/// A provider with state: a preset geohash and the number of times it was asked
+#[cfg(test)]
+struct PresetLocation {
+    geohash: String,
+    calls: std::sync::atomic::AtomicUsize,
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl Location for PresetLocation {
+    type Output = String;
+
+    async fn current_location(&self, accuracy: u8) -> Result<String, LocationError> {
+        self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(self.geohash[..accuracy as usize].to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_location_queries_a_stateful_provider() {
+    let provider = PresetLocation {
+        geohash: "gcpvj0du".to_string(),
+        calls: Default::default(),
+    };
+    assert_eq!(location(&provider, 6).await.expect("location"), "gcpvj0");
+    assert_eq!(location(&provider, 8).await.expect("location"), "gcpvj0du");
+    // Borrowed each time, so the same instance keeps its state
+    assert_eq!(provider.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
 }
@@ -11,26 +11,117 @@
 //! oracle generate
 //! ```
 //!
+//! ## Generate a key pair and keep the private key in the OS keyring
+//! ```
+//! oracle generate --save-keyring
+//! ```
+//!
 //! ## Run the oracle with a specific key and accuracy
 //! ```
 //! oracle run --key=<hex_key> --accuracy=6
 //! ```
 //!
+//! ## Pretty-print the output for inspection
+//! ```
+//! oracle run --key=<hex_key> --accuracy=6 --json-pretty
+//! ```
+//!
+//! ## Include the signed digest to debug on-chain verification
+//! ```
+//! oracle run --key=<hex_key> --accuracy=6 --hasher=keccak256 --debug-digest
+//! ```
+//!
+//! ## Bind the attestation to the challenge being checked into
+//! ```
+//! oracle run --key=<hex_key> --accuracy=8 --challenge=gcpv
+//! ```
+//!
+//! ## Sign coordinates provided by another tool
+//! ```
+//! echo '51.5074,-0.1278' | oracle run --key=<hex_key> --accuracy=8 --coords-from-stdin
+//! ```
+//!
+//! ## Fall back to ip-api.com when ipinfo.io fails
+//! ```
+//! oracle run --key=<hex_key> --accuracy=8 --source-order=ipinfo,ip-api
+//! ```
+//!
+//! ## Attest every minute, ten times
+//! ```
+//! oracle run --key=<hex_key> --accuracy=8 --interval=60 --count=10
+//! ```
+//!
+//! ## Attest every minute, looking the location up once an hour
+//! ```
+//! oracle run --key=<hex_key> --accuracy=8 --interval=60 --location-cache-ttl=3600
+//! ```
+//!
+//! ## Sign a bound attestation and submit it to a node
+//! ```
+//! oracle run --key=<hex_key> --accuracy=8 --challenge=gcpv \
+//!     | oracle submit --rpc-url=ws://127.0.0.1:9944 --signer-seed=//Alice
+//! ```
+//!
+//! ## Sign a geohash the caller obtained itself
+//! ```
+//! oracle sign-geohash --key=<hex_key> --geohash=gcpvj0du --challenge=gcpv
+//! ```
+//!
+//! ## Print a QR code to scan at a mobile check-in
+//! ```
+//! oracle run --key=<hex_key> --accuracy=8 --challenge=gcpv --qr --qr-render
+//! ```
+//!
+//! ## List the signature schemes, hashers and location sources
+//! ```
+//! oracle info --json
+//! ```
+//!
 //! ## Run using an environment variable for the key
 //! ```
 //! ORACLE_KEY=<hex_key> oracle run --accuracy=8
 //! ```
+//!
+//! ## Run using the key stored in the OS keyring
+//! ```
+//! oracle run --keyring-service=aoi-oracle --accuracy=8
+//! ```
+//!
+//! ## Run using a key held by a PKCS#11 token (with the `pkcs11` feature)
+//! ```
+//! ORACLE_PKCS11_PIN=<pin> oracle run --pkcs11-module=/usr/lib/softhsm/libsofthsm2.so \
+//!     --pkcs11-slot=0 --accuracy=8
+//! ```
 
-mod blake2_256;
-mod ed25519;
 mod env;
+mod error;
 mod geohash;
+mod hasher;
+mod info;
+mod keystore;
+mod stdin;
+mod submit;
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
 
-use blake2_256::Blake2_256;
 use clap::{Parser, Subcommand};
-use ed25519::Ed25519;
-use geohash::Geohash;
-use oracle::{location, sign_location, Key, Signer};
+use error::CliError;
+use geohash::{Coordinates, Geohash, IpSource, ProvidedGeohash};
+use hasher::HashAlgorithm;
+use info::Info;
+use oracle::cache::Cached;
+use oracle::ed25519::Ed25519;
+use oracle::fallback::Fallback;
+#[cfg(feature = "pkcs11")]
+use oracle::pkcs11::Pkcs11Ed25519;
+use oracle::qr;
+use oracle::{
+    location, signing_payload, Hash, Location, PublicKey, SecretKey, SignedData, SignedLocation,
+    Signer, SignerError, DOMAIN_TAG, PAYLOAD_VERSION,
+};
+use serde::Serialize;
 
 /// Command-line arguments for the Oracle application.
 ///
@@ -54,7 +145,22 @@ enum Commands {
     ///
     /// This command generates a new Ed25519 key pair and
     /// outputs both the private and public keys in hexadecimal format.
-    Generate,
+    Generate {
+        /// Store the private key in the OS keyring instead of printing it.
+        ///
+        /// Only the public key is printed. If this system has no keyring, the
+        /// private key is printed as without this flag, with a warning.
+        #[arg(long)]
+        save_keyring: bool,
+
+        /// Keyring service to store the key under.
+        #[arg(long, default_value = keystore::SERVICE)]
+        keyring_service: String,
+
+        /// Keyring account to store the key under.
+        #[arg(long, default_value = keystore::ACCOUNT)]
+        keyring_account: String,
+    },
     
     /// Run the oracle to generate a signed location.
     ///
@@ -62,87 +168,1357 @@ enum Commands {
     /// 1. Gets the current location
     /// 2. Converts it to a geohash with the specified accuracy
     /// 3. Signs it with the provided key or environment variable
-    /// 4. Outputs the signed data as JSON
+    /// 4. Outputs the location and its signature as JSON
+    ///
+    /// The key is given with at most one of `--key`, `--keyring-service` and
+    /// `--pkcs11-module`, and read from the `ORACLE_KEY` environment variable
+    /// without any.
     Run {
-        /// Hexadecimal private key for signing (optional if ORACLE_KEY env var is set).
-        ///
-        /// The key should be a 32-byte Ed25519 private key in hexadecimal format,
-        /// optionally prefixed with "0x".
-        #[arg(default_value = "")]
-        key: String,
-        
+        #[command(flatten)]
+        keys: KeySourceArgs,
+
         /// Geohash accuracy (1-12), determines precision of location data.
         ///
-        /// Higher values provide more precise location data.
-        /// Common values:
-        /// - 5: City level (~2.4km precision)
-        /// - 6: Neighborhood level (~0.61km precision)
-        /// - 8: Street level (~38m precision)
-        #[arg(default_value = "6")]
+        /// Each level is the length of the signed geohash, and higher values
+        /// provide more precise location data. Common values:
+        /// - 5: City level (~4.9km cells)
+        /// - 6: Neighborhood level (~1.2km by 0.61km cells)
+        /// - 8: Street level (~38m by 19m cells)
+        #[arg(
+            long,
+            default_value = "6",
+            value_parser = clap::value_parser!(u8).range(
+                geohash::MIN_ACCURACY as i64..=geohash::MAX_ACCURACY as i64
+            )
+        )]
         accuracy: u8,
+
+        /// What to sign: the geohash, or the raw coordinates.
+        ///
+        /// `coords` signs the canonical `latitude,longitude` encoding (six fixed
+        /// decimals) for verifiers that compute the geohash themselves.
+        #[arg(long, default_value = "geohash")]
+        sign: SignedData,
+
+        /// Cap the disclosed geohash length below the requested accuracy.
+        ///
+        /// Trades attendance accuracy for privacy: challenges finer than this
+        /// precision can no longer be attested.
+        #[arg(long)]
+        privacy_precision: Option<u8>,
+
+        /// Refuse to sign a geohash shorter than this.
+        ///
+        /// The operator's own floor on the disclosed precision, checked against
+        /// the requested accuracy after the privacy cap before any location is
+        /// looked up. Coordinate attestations always have full precision.
+        #[arg(long)]
+        min_accuracy: Option<u8>,
+
+        /// Randomly move the coordinates within this radius before encoding.
+        ///
+        /// Trades attendance accuracy for privacy: near a cell boundary the jittered
+        /// location may fall in a neighbouring geohash cell.
+        #[arg(long, default_value_t = 0.0)]
+        jitter_meters: f64,
+
+        /// Read the coordinates from stdin instead of using IP geolocation.
+        ///
+        /// The input is `latitude,longitude` in decimal degrees, or a JSON object
+        /// with `latitude` and `longitude` (or `lat` and `lon`) fields.
+        #[arg(long)]
+        coords_from_stdin: bool,
+
+        /// IP geolocation services to try in order, comma-separated.
+        ///
+        /// Each service is tried only if those before it failed, e.g. when
+        /// rate limited, and the first location obtained is signed. When all
+        /// fail, the error lists each service's failure. `ipinfo` (ipinfo.io)
+        /// and `ip-api` (ip-api.com) are supported.
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_value = "ipinfo",
+            conflicts_with = "coords_from_stdin"
+        )]
+        source_order: Vec<IpSource>,
+
+        /// Sign IP locations that look bogus instead of refusing.
+        ///
+        /// By default the oracle refuses to sign when IP geolocation reports a
+        /// private or reserved (bogon) address, or the null island `0,0`.
+        #[arg(long)]
+        allow_bogon: bool,
+
+        /// HTTP user agent sent to the IP geolocation service.
+        ///
+        /// Identifies the oracle to the service; defaults to `aoi-oracle/<version>`.
+        #[arg(long, default_value = geohash::USER_AGENT)]
+        user_agent: String,
+
+        /// Idle connections kept open to the IP geolocation service.
+        ///
+        /// Lookups reuse a pooled connection, sparing each attestation of a run
+        /// with `--interval` a new TLS handshake; 0 opens a new connection for
+        /// every lookup.
+        #[arg(long, default_value_t = geohash::HTTP_POOL_SIZE)]
+        http_pool_size: usize,
+
+        #[command(flatten)]
+        output: OutputArgs,
+
+        /// Keep attesting, waiting this many seconds between attestations.
+        ///
+        /// Each attestation queries the location again and is written as its
+        /// own JSON document. Runs until stopped unless `--count` is given.
+        #[arg(long)]
+        interval: Option<u64>,
+
+        /// Exit after emitting this many attestations.
+        ///
+        /// Defaults to one attestation, or to unlimited with `--interval`.
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        count: Option<u64>,
+
+        /// Reuse a looked-up location for this many seconds.
+        ///
+        /// Spares the geolocation service, and its quota, a lookup on every
+        /// attestation of a stationary oracle running with `--interval`; each
+        /// attestation is still signed anew. 0, the default, looks the location
+        /// up for every attestation.
+        #[arg(long, default_value_t = 0)]
+        location_cache_ttl: u64,
     },
+
+    /// Sign a geohash provided by the caller.
+    ///
+    /// For integrations that locate the attendee themselves, e.g. on a trusted
+    /// device, and only need the oracle to sign. The geohash is validated,
+    /// signed like the geohash of `oracle run` and output as the same JSON, or
+    /// as a compact attestation with `--qr`; no location is looked up. The key
+    /// sources are those of `oracle run`.
+    SignGeohash {
+        /// The geohash to sign, 1 to 12 characters of the geohash alphabet.
+        #[arg(long)]
+        geohash: String,
+
+        #[command(flatten)]
+        keys: KeySourceArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Submit an attestation to an attendance node.
+    ///
+    /// This command reads an attestation written by `oracle run` from stdin,
+    /// submits it as a `submission_with_signature` extrinsic signed by the
+    /// attendee's account, waits until it is finalized and outputs the
+    /// extrinsic and block hashes as JSON.
+    Submit {
+        /// WebSocket RPC endpoint of the node.
+        #[arg(long, default_value = "ws://127.0.0.1:9944")]
+        rpc_url: String,
+
+        /// Secret URI of the attendee's sr25519 account.
+        ///
+        /// A mnemonic phrase, a `0x` hex seed or a dev account such as `//Alice`.
+        /// The account pays the fees and is credited with the attendance.
+        #[arg(long)]
+        signer_seed: String,
+
+        /// Name of the attendance pallet in the target runtime.
+        #[arg(long, default_value = submit::PALLET)]
+        pallet: String,
+
+        /// Namespace of the challenge on the node.
+        ///
+        /// Challenges with the same geohash can be registered by several
+        /// organisations, each in its own namespace. Defaults to the empty,
+        /// default namespace.
+        #[arg(long, default_value = "")]
+        namespace: String,
+    },
+
+    /// List the capabilities compiled into this binary.
+    ///
+    /// This command outputs the supported signature schemes, the `--hasher`
+    /// values and the location sources, as text or, with `--json`, as JSON.
+    Info {
+        /// Output the capabilities as JSON.
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print the JSON output.
+        #[arg(long, requires = "json")]
+        json_pretty: bool,
+    },
+}
+
+/// Where the signing key is loaded from, shared by the signing subcommands.
+///
+/// The key is given with at most one of `--key`, `--keyring-service` and
+/// `--pkcs11-module`, and read from the `ORACLE_KEY` environment variable
+/// without any.
+#[derive(clap::Args)]
+struct KeySourceArgs {
+    /// Hexadecimal private key for signing (optional if ORACLE_KEY env var is set).
+    ///
+    /// The key should be a 32-byte Ed25519 private key in hexadecimal format,
+    /// optionally prefixed with "0x". Takes precedence over `ORACLE_KEY`.
+    #[arg(long, group = "key_source")]
+    key: Option<String>,
+
+    /// Load the private key from the OS keyring, under this service.
+    ///
+    /// Cannot be combined with `--key`. `ORACLE_KEY` is used instead, with
+    /// a warning, if this system has no keyring.
+    #[arg(long, group = "key_source")]
+    keyring_service: Option<String>,
+
+    /// Keyring account the private key is stored under.
+    #[arg(long, default_value = keystore::ACCOUNT)]
+    keyring_account: String,
+
+    /// Sign with an Ed25519 key held by a PKCS#11 token, through this module.
+    ///
+    /// The path of the token vendor's PKCS#11 library. The private key never
+    /// leaves the token; its user PIN is read from `ORACLE_PKCS11_PIN`. Needs
+    /// a binary built with the `pkcs11` feature.
+    #[arg(long, group = "key_source")]
+    pkcs11_module: Option<PathBuf>,
+
+    /// The PKCS#11 slot of the token.
+    #[arg(long, default_value_t = 0, requires = "pkcs11_module")]
+    pkcs11_slot: u64,
+
+    /// Label of the key pair on the token, if it holds several Ed25519 keys.
+    #[arg(long, requires = "pkcs11_module")]
+    pkcs11_key_label: Option<String>,
+
+    /// Hex public key the loaded private key is expected to correspond to.
+    ///
+    /// When given, the oracle refuses to run if the public key derived from
+    /// the private key differs, catching wrong-key mistakes before signing.
+    #[arg(long)]
+    expect_public_key: Option<String>,
+}
+
+/// How attestations are signed and written, shared by the signing subcommands.
+#[derive(clap::Args)]
+struct OutputArgs {
+    /// Domain-separation tag prepended to the signed payload.
+    ///
+    /// Must match the `DomainTag` configured in the target runtime.
+    /// Defaults to the attendance protocol tag.
+    #[arg(long, default_value_t = String::from_utf8_lossy(DOMAIN_TAG).into_owned())]
+    domain_tag: String,
+
+    /// Bind the signature to the challenge geohash being checked into.
+    ///
+    /// The challenge bytes are included in the signed payload, so the attestation
+    /// cannot be submitted against any other challenge. The attendance pallet
+    /// only accepts bound attestations.
+    #[arg(long)]
+    challenge: Option<String>,
+
+    /// Hash function applied to the domain-tagged payload before signing.
+    ///
+    /// Must match the `PayloadHasher` configured in the target runtime:
+    /// `blake2_256`, `keccak256` or `sha256`.
+    #[arg(long, default_value = "blake2_256")]
+    hasher: HashAlgorithm,
+
+    /// Include the hex digest of the signed payload in the output.
+    ///
+    /// Lets operators compare the oracle's digest against the one computed
+    /// by the runtime when diagnosing verification failures.
+    #[arg(long)]
+    debug_digest: bool,
+
+    /// Pretty-print the JSON output for human inspection.
+    ///
+    /// Compact JSON is the default, intended for machine consumption.
+    #[arg(long)]
+    json_pretty: bool,
+
+    /// Output a compact attestation for QR codes instead of JSON.
+    ///
+    /// A single `aoi:`-prefixed line of base64 for mobile check-in flows,
+    /// leaving out the debugging digest.
+    #[arg(long, conflicts_with = "json_pretty")]
+    qr: bool,
+
+    /// Also render the compact attestation as a QR code in the terminal.
+    ///
+    /// The code is written before the line it encodes.
+    #[arg(long, requires = "qr")]
+    qr_render: bool,
 }
 
 /// Main entry point for the Oracle CLI application.
 ///
 /// This function:
 /// 1. Parses command-line arguments
-/// 2. Executes the requested command (Generate or Run)
-/// 3. Handles errors and outputs results
+/// 2. Executes the requested command (Generate, Run, SignGeohash, Submit or Info)
+/// 3. Reports any error and exits with a non-zero status
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
 
-    match args.command {
-        Commands::Generate => {
+    if let Err(e) = execute(args.command).await {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Executes a subcommand, writing its output to stdout.
+///
+/// # Arguments
+/// * `command` - The subcommand to execute
+///
+/// # Returns
+/// * `Result<(), CliError>` - Success, or the first error encountered
+async fn execute(command: Commands) -> Result<(), CliError> {
+    match command {
+        Commands::Generate {
+            save_keyring,
+            keyring_service,
+            keyring_account,
+        } => {
             // Generate a new Ed25519 key pair
             let (secret_key, public_key) = Ed25519::generate_key();
-            println!(
-                "Private=0x{}\nPublic=0x{}",
-                env::array_to_hex(secret_key.as_bytes()),
-                env::array_to_hex(public_key.as_bytes()),
-            );
-        }
-        Commands::Run { key, accuracy } => {
-            // Attempt to get the key from environment variable first, then from command line
-            let key_result =
-                env::try_key_from_environment().or_else(|_| env::try_hex_to_array(key));
-
-            // Handle key parsing errors gracefully
-            let key = match key_result {
-                Ok(key_bytes) => Key::new(key_bytes),
-                Err(e) => {
-                    eprintln!("Error: Failed to get key: {}", e);
-                    std::process::exit(1);
-                }
-            };
+            let saved = save_keyring
+                && keystore::save_key(&keyring_service, &keyring_account, &secret_key)?;
+            if save_keyring && !saved {
+                eprintln!("Warning: no keyring available, printing the private key instead");
+            }
+            if saved {
+                println!("Public=0x{}", env::array_to_hex(public_key.as_bytes()));
+            } else {
+                println!(
+                    "Private=0x{}\nPublic=0x{}",
+                    env::array_to_hex(secret_key.as_bytes()),
+                    env::array_to_hex(public_key.as_bytes()),
+                );
+            }
+        }
+        Commands::Run {
+            keys,
+            accuracy,
+            sign,
+            privacy_precision,
+            min_accuracy,
+            jitter_meters,
+            coords_from_stdin,
+            source_order,
+            allow_bogon,
+            user_agent,
+            http_pool_size,
+            output,
+            interval,
+            count,
+            location_cache_ttl,
+        } => {
+            let attestation = Attestation::from_args(keys, &output, accuracy, sign)?;
+            if let (SignedData::Geohash, Some(minimum)) = (sign, min_accuracy) {
+                check_min_accuracy(
+                    geohash::disclosed_precision(accuracy, privacy_precision)?,
+                    minimum,
+                )?;
+            }
 
-            // Get the current location as a geohash
-            let location = match location::<Geohash>(accuracy).await {
-                Ok(loc) => loc,
-                Err(e) => {
-                    eprintln!("Error: Failed to get location: {}", e);
-                    std::process::exit(1);
-                }
+            let coordinates = if coords_from_stdin {
+                Some(stdin::read_coordinates()?)
+            } else {
+                None
             };
 
-            // Sign the location data
-            let signed_location = match sign_location::<Geohash, Ed25519, Blake2_256>(key, location).await {
-                Ok(sig) => sig,
-                Err(e) => {
-                    eprintln!("Error: Failed to sign location: {}", e);
-                    std::process::exit(1);
+            // Get the current location, sign it and output it as JSON
+            let schedule = Schedule::new(count, interval);
+            let cache_ttl = Duration::from_secs(location_cache_ttl);
+            let client = geohash::http_client(&user_agent, http_pool_size)?;
+            let out = &mut std::io::stdout();
+            match sign {
+                SignedData::Geohash => {
+                    let provider = fallback(&source_order, |source| {
+                        let provider = Geohash::new(privacy_precision, jitter_meters)
+                            .allow_bogon(allow_bogon)
+                            .http_client(client.clone())
+                            .ip_source(source);
+                        match coordinates {
+                            Some((lat, lon)) => provider.with_coordinates(lat, lon),
+                            None => provider,
+                        }
+                    });
+                    let provider = Cached::new(provider, cache_ttl);
+                    emit(&provider, &attestation, &schedule, out).await?
                 }
-            };
-
-            // Output the signed location as JSON
-            match serde_json::to_string(&signed_location) {
-                Ok(json) => println!("{}", json),
-                Err(e) => {
-                    eprintln!("Error: Failed to serialize signature: {}", e);
-                    std::process::exit(1);
+                SignedData::Coords => {
+                    let provider = fallback(&source_order, |source| {
+                        let provider = Coordinates::new(jitter_meters)
+                            .allow_bogon(allow_bogon)
+                            .http_client(client.clone())
+                            .ip_source(source);
+                        match coordinates {
+                            Some((lat, lon)) => provider.with_coordinates(lat, lon),
+                            None => provider,
+                        }
+                    });
+                    let provider = Cached::new(provider, cache_ttl);
+                    emit(&provider, &attestation, &schedule, out).await?
                 }
             }
         }
+        Commands::SignGeohash {
+            geohash,
+            keys,
+            output,
+        } => {
+            check_geohash(&geohash)?;
+            let attestation = Attestation::from_args(
+                keys,
+                &output,
+                geohash.len() as u8,
+                SignedData::Geohash,
+            )?;
+            emit(
+                &ProvidedGeohash(geohash),
+                &attestation,
+                &Schedule::new(None, None),
+                &mut std::io::stdout(),
+            )
+            .await?
+        }
+        Commands::Submit {
+            rpc_url,
+            signer_seed,
+            pallet,
+            namespace,
+        } => {
+            let signer = submit::signer(&signer_seed)?;
+            let submission = submit::read_attestation()?;
+            let receipt =
+                submit::submit(&rpc_url, &pallet, &signer, &namespace, submission).await?;
+            println!("{}", serde_json::to_string(&receipt)?);
+        }
+        Commands::Info { json, json_pretty } => {
+            let info = Info::compiled();
+            if json_pretty {
+                println!("{}", serde_json::to_string_pretty(&info)?);
+            } else if json {
+                println!("{}", serde_json::to_string(&info)?);
+            } else {
+                print!("{}", info);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The parameters shared by every attestation of a run.
+struct Attestation<'a> {
+    /// The desired accuracy level for the location data
+    accuracy: u8,
+    /// The key to sign with
+    key: SigningKey,
+    /// The public key of `key`, included in the output
+    public_key: PublicKey,
+    /// The domain-separation tag to prepend
+    tag: &'a [u8],
+    /// The challenge to bind the signature to, if any
+    challenge: Option<String>,
+    /// What kind of location data the provider returns
+    signed: SignedData,
+    /// The hash function applied to the payload
+    hasher: HashAlgorithm,
+    /// Whether to include the signed digest in the output
+    debug_digest: bool,
+    /// Whether to pretty-print the JSON output
+    json_pretty: bool,
+    /// Whether to output a compact attestation instead of JSON
+    qr: bool,
+    /// Whether to render the compact attestation as a QR code
+    qr_render: bool,
+}
+
+impl<'a> Attestation<'a> {
+    /// The attestation parameters given on the command line, loading the key.
+    ///
+    /// # Arguments
+    /// * `keys` - Where to load the signing key from
+    /// * `output` - How to sign and write the attestations
+    /// * `accuracy` - The desired accuracy level for the location data
+    /// * `signed` - What kind of location data the provider returns
+    ///
+    /// # Returns
+    /// * `Result<Attestation, CliError>` - The parameters, or the error of
+    ///   [`load_key`]
+    fn from_args(
+        keys: KeySourceArgs,
+        output: &'a OutputArgs,
+        accuracy: u8,
+        signed: SignedData,
+    ) -> Result<Self, CliError> {
+        let token = keys.pkcs11_module.map(|module| Token {
+            module,
+            slot: keys.pkcs11_slot,
+            label: keys.pkcs11_key_label,
+        });
+        let (key, public_key) = load_key(
+            keys.key,
+            keys.keyring_service,
+            &keys.keyring_account,
+            token,
+            keys.expect_public_key,
+        )?;
+        Ok(Attestation {
+            accuracy,
+            key,
+            public_key,
+            tag: output.domain_tag.as_bytes(),
+            challenge: output.challenge.clone(),
+            signed,
+            hasher: output.hasher,
+            debug_digest: output.debug_digest,
+            json_pretty: output.json_pretty,
+            qr: output.qr,
+            qr_render: output.qr_render,
+        })
+    }
+}
+
+/// The key attestations are signed with.
+enum SigningKey {
+    /// An Ed25519 private key held by the oracle
+    Local(SecretKey),
+    /// An Ed25519 key that never leaves a PKCS#11 token
+    #[cfg(feature = "pkcs11")]
+    Pkcs11(Pkcs11Ed25519),
+}
+
+impl SigningKey {
+    /// Signs a message hash with the key.
+    ///
+    /// # Arguments
+    /// * `message` - The hash of the message to sign
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>, SignerError>` - The Ed25519 signature, or an error if
+    ///   signing failed
+    fn sign(&self, message: Hash) -> Result<Vec<u8>, SignerError> {
+        match self {
+            SigningKey::Local(key) => Ed25519::sign(message, *key),
+            #[cfg(feature = "pkcs11")]
+            SigningKey::Pkcs11(token) => token.sign(message),
+        }
+    }
+
+    /// The public key that verifies the key's signatures.
+    fn public_key(&self) -> PublicKey {
+        match self {
+            SigningKey::Local(key) => Ed25519::public_key(*key),
+            #[cfg(feature = "pkcs11")]
+            SigningKey::Pkcs11(token) => token.public_key(),
+        }
     }
 }
+
+/// The key on a PKCS#11 token selected with `--pkcs11-module`.
+#[cfg_attr(not(feature = "pkcs11"), allow(dead_code))]
+struct Token {
+    /// Path of the token vendor's PKCS#11 library
+    module: PathBuf,
+    /// The slot of the token
+    slot: u64,
+    /// The label of the key pair, if any
+    label: Option<String>,
+}
+
+/// How many attestations a run emits, and how far apart.
+struct Schedule {
+    /// Number of attestations to emit, unlimited if `None`
+    count: Option<u64>,
+    /// Delay between consecutive attestations
+    interval: Duration,
+}
+
+impl Schedule {
+    /// Creates the schedule for the `--count` and `--interval` options.
+    ///
+    /// Without an interval a single attestation is emitted unless a count is
+    /// given; with one, attestations continue until the count is reached.
+    fn new(count: Option<u64>, interval: Option<u64>) -> Self {
+        Self {
+            count: count.or(interval.is_none().then_some(1)),
+            interval: Duration::from_secs(interval.unwrap_or(0)),
+        }
+    }
+}
+
+/// Chains a location provider for each service of `--source-order`.
+///
+/// The providers are tried in the order of `sources`; a chain of one fails
+/// with its provider's own error.
+///
+/// # Arguments
+/// * `sources` - The IP geolocation services, in the order to try them
+/// * `provider` - Builds the provider locating through a service
+///
+/// # Returns
+/// A provider trying each service in turn
+fn fallback<L, F>(sources: &[IpSource], provider: F) -> Fallback<String>
+where
+    L: Location<Output = String> + Send + Sync + 'static,
+    F: Fn(IpSource) -> L,
+{
+    sources.iter().fold(Fallback::new(), |chain, &source| {
+        chain.or(source.name(), provider(source))
+    })
+}
+
+/// Emits signed locations from a provider as JSON, one document per attestation.
+///
+/// With `qr` each attestation is written as a compact attestation line
+/// instead, preceded by its QR code with `qr_render`.
+///
+/// # Arguments
+/// * `provider` - The location source to query for each attestation
+/// * `attestation` - The parameters of each attestation
+/// * `schedule` - How many attestations to emit, and how far apart
+/// * `out` - Where the documents are written
+///
+/// # Returns
+/// * `Result<(), CliError>` - Success once the count is reached, or the first
+///   error encountered
+async fn emit<L, W>(
+    provider: &L,
+    attestation: &Attestation<'_>,
+    schedule: &Schedule,
+    out: &mut W,
+) -> Result<(), CliError>
+where
+    L: Location,
+    L::Output: Serialize,
+    W: Write,
+{
+    let bound = attestation.challenge.as_deref().map(str::as_bytes);
+    let mut emitted = 0;
+    while schedule.count.map_or(true, |count| emitted < count) {
+        if emitted > 0 {
+            tokio::time::sleep(schedule.interval).await;
+        }
+
+        let (location, digest, signature) = attest(
+            provider,
+            attestation.accuracy,
+            &attestation.key,
+            attestation.tag,
+            bound,
+            attestation.hasher,
+        )
+        .await?;
+
+        let signed_location = SignedLocation {
+            location,
+            signature,
+            public_key: format!("0x{}", env::array_to_hex(attestation.public_key.as_bytes())),
+            signed: attestation.signed,
+            version: PAYLOAD_VERSION,
+            challenge: attestation.challenge.clone(),
+            digest: attestation
+                .debug_digest
+                .then(|| format!("0x{}", env::array_to_hex(digest.as_bytes()))),
+        };
+
+        let output = if attestation.qr {
+            qr::encode(&signed_location)?
+        } else {
+            render(&signed_location, attestation.json_pretty)?
+        };
+        if attestation.qr_render {
+            writeln!(out, "{}", qr::render(&output)?)?;
+        }
+        writeln!(out, "{}", output)?;
+        out.flush()?;
+        emitted += 1;
+    }
+    Ok(())
+}
+
+/// Serializes a signed location as a JSON document.
+///
+/// A location type that fails to serialize, e.g. a provider returning a map
+/// with non-string keys, does not lose the attestation: the error carries the
+/// hex-encoded signature, which always encodes.
+///
+/// # Arguments
+/// * `signed_location` - The signed location to serialize
+/// * `pretty` - Whether to pretty-print the document
+///
+/// # Returns
+/// * `Result<String, CliError>` - The JSON document, or
+///   `CliError::UnserializableAttestation`
+fn render<O: Serialize>(
+    signed_location: &SignedLocation<O, Vec<u8>>,
+    pretty: bool,
+) -> Result<String, CliError> {
+    let json = if pretty {
+        serde_json::to_string_pretty(signed_location)
+    } else {
+        serde_json::to_string(signed_location)
+    };
+    json.map_err(|source| CliError::UnserializableAttestation {
+        signature: format!("0x{}", env::array_to_hex(&signed_location.signature)),
+        source,
+    })
+}
+
+/// Obtains the current location from a provider and signs it.
+///
+/// The payload is built with `signing_payload`, optionally bound to a challenge,
+/// and hashed with the hasher selected at runtime.
+///
+/// # Arguments
+/// * `provider` - The location source to query
+/// * `accuracy` - The desired accuracy level for the location data
+/// * `key` - The key to sign with
+/// * `tag` - The domain-separation tag to prepend
+/// * `challenge` - The challenge to bind the signature to, if any
+/// * `hasher` - The hash function applied to the payload
+///
+/// # Returns
+/// * `Result<(L::Output, Hash, Vec<u8>), CliError>` - The location, the signed
+///   digest and its signature
+async fn attest<L>(
+    provider: &L,
+    accuracy: u8,
+    key: &SigningKey,
+    tag: &[u8],
+    challenge: Option<&[u8]>,
+    hasher: HashAlgorithm,
+) -> Result<(L::Output, Hash, Vec<u8>), CliError>
+where
+    L: Location,
+{
+    let location = location(provider, accuracy).await?;
+    let digest = hasher.hash(signing_payload(tag, challenge, location.as_ref()));
+    let signature = key.sign(digest)?;
+    Ok((location, digest, signature))
+}
+
+/// Resolves the signing key from the command line or the environment.
+///
+/// The command-line key takes precedence; the `ORACLE_KEY` environment
+/// variable is the fallback when no key was given.
+///
+/// # Arguments
+/// * `key` - The hex key given with `--key`, if any
+///
+/// # Returns
+/// * `Result<SecretKey, CliError>` - The key, `CliError::MissingKey` if no key was
+///   provided at all, or `CliError::InvalidKey` if the provided key is malformed
+fn resolve_key(key: Option<String>) -> Result<SecretKey, CliError> {
+    match key {
+        Some(key) => Ok(env::try_hex_to_key(&key)?),
+        None => Ok(env::try_key_from_environment()?),
+    }
+}
+
+/// Resolves the signing key from the OS keyring, falling back to the
+/// `ORACLE_KEY` environment variable if this system has no keyring.
+///
+/// # Arguments
+/// * `service` - The keyring service the key is stored under
+/// * `account` - The keyring account the key is stored under
+///
+/// # Returns
+/// * `Result<SecretKey, CliError>` - The key, `CliError::Keystore` if the keyring
+///   holds no valid key, or an error of [`resolve_key`] when falling back
+fn resolve_keyring_key(service: &str, account: &str) -> Result<SecretKey, CliError> {
+    match keystore::load_key(service, account)? {
+        Some(key) => Ok(key),
+        None => {
+            eprintln!("Warning: no keyring available, using ORACLE_KEY");
+            resolve_key(None)
+        }
+    }
+}
+
+/// Opens the signing key on a PKCS#11 token, logging in with the PIN in
+/// `ORACLE_PKCS11_PIN`.
+///
+/// # Arguments
+/// * `token` - The module, slot and label of the key
+///
+/// # Returns
+/// * `Result<SigningKey, CliError>` - The token key, `CliError::MissingPin` if
+///   no PIN is set, or `CliError::Pkcs11` if the token key could not be opened
+#[cfg(feature = "pkcs11")]
+fn open_token(token: Token) -> Result<SigningKey, CliError> {
+    let pin = env::pin_from_environment().ok_or(CliError::MissingPin)?;
+    let token = Pkcs11Ed25519::open(&token.module, token.slot, &pin, token.label.as_deref())?;
+    Ok(SigningKey::Pkcs11(token))
+}
+
+/// Fails, as this binary was built without the `pkcs11` feature.
+#[cfg(not(feature = "pkcs11"))]
+fn open_token(_token: Token) -> Result<SigningKey, CliError> {
+    Err(CliError::Pkcs11Unsupported)
+}
+
+/// Loads the signing key and derives its public key.
+///
+/// The key is on a PKCS#11 token when `token` is given, comes from the OS
+/// keyring when `keyring_service` is given, and from [`resolve_key`]
+/// otherwise; clap rejects giving more than one.
+///
+/// # Arguments
+/// * `key` - The hex key given with `--key`, if any
+/// * `keyring_service` - The keyring service the key is stored under, if any
+/// * `keyring_account` - The keyring account the key is stored under
+/// * `token` - The PKCS#11 token key given with `--pkcs11-module`, if any
+/// * `expect_public_key` - The hex public key the key must correspond to, if any
+///
+/// # Returns
+/// * `Result<(SigningKey, PublicKey), CliError>` - The signing and public keys, or
+///   the error of loading the key or of [`check_public_key`]
+fn load_key(
+    key: Option<String>,
+    keyring_service: Option<String>,
+    keyring_account: &str,
+    token: Option<Token>,
+    expect_public_key: Option<String>,
+) -> Result<(SigningKey, PublicKey), CliError> {
+    let key = match (token, keyring_service) {
+        (Some(token), _) => open_token(token)?,
+        (None, Some(service)) => SigningKey::Local(resolve_keyring_key(&service, keyring_account)?),
+        (None, None) => SigningKey::Local(resolve_key(key)?),
+    };
+    let public_key = key.public_key();
+    if let Some(expected) = expect_public_key {
+        check_public_key(public_key, expected)?;
+    }
+    Ok((key, public_key))
+}
+
+/// Checks that a geohash provided for signing is one.
+///
+/// # Arguments
+/// * `geohash` - The geohash to sign
+///
+/// # Returns
+/// * `Result<(), CliError>` - Success, or `CliError::InvalidGeohash` if it is
+///   empty, longer than 12 characters or has characters outside the geohash
+///   alphabet
+fn check_geohash(geohash: &str) -> Result<(), CliError> {
+    let length = 1..=geohash_inputs::MAX_GEOHASH_LENGTH;
+    if !length.contains(&geohash.len()) || !geohash_inputs::is_geohash(geohash.as_bytes()) {
+        return Err(CliError::InvalidGeohash(geohash.to_string()));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_check_geohash() {
+    assert!(check_geohash("g").is_ok());
+    assert!(check_geohash("gcpvj0du6xyz").is_ok());
+    for geohash in ["", "gcpvj0du6xyz0", "gcpa", "GCPV", "gcp v"] {
+        assert!(matches!(
+            check_geohash(geohash),
+            Err(CliError::InvalidGeohash(g)) if g == geohash
+        ));
+    }
+    assert_eq!(
+        check_geohash("gcpa").unwrap_err().to_string(),
+        "invalid geohash \"gcpa\": expected 1 to 12 characters of the geohash alphabet"
+    );
+}
+
+/// Checks that the disclosed geohash is at least as precise as the operator's
+/// minimum.
+///
+/// # Arguments
+/// * `precision` - The length of the geohash that would be signed
+/// * `minimum` - The shortest geohash the operator allows signing
+///
+/// # Returns
+/// * `Result<(), CliError>` - Success, or `CliError::InsufficientAccuracy`
+fn check_min_accuracy(precision: u8, minimum: u8) -> Result<(), CliError> {
+    if precision < minimum {
+        return Err(CliError::InsufficientAccuracy { precision, minimum });
+    }
+    Ok(())
+}
+
+#[test]
+fn test_check_min_accuracy() {
+    assert!(check_min_accuracy(8, 8).is_ok());
+    assert!(check_min_accuracy(9, 8).is_ok());
+    assert!(matches!(
+        check_min_accuracy(geohash::disclosed_precision(8, Some(5)).unwrap(), 6),
+        Err(CliError::InsufficientAccuracy {
+            precision: 5,
+            minimum: 6
+        })
+    ));
+    assert_eq!(
+        check_min_accuracy(4, 6).unwrap_err().to_string(),
+        "refusing to sign a geohash of precision 4, below the minimum accuracy 6"
+    );
+}
+
+/// Checks that a derived public key matches the expected one.
+///
+/// # Arguments
+/// * `public_key` - The public key derived from the loaded private key
+/// * `expected` - The expected hex public key, with or without "0x" prefix
+///
+/// # Returns
+/// * `Result<(), CliError>` - Success if they match, otherwise
+///   `CliError::InvalidExpectedPublicKey` or `CliError::PublicKeyMismatch`
+fn check_public_key(public_key: PublicKey, expected: String) -> Result<(), CliError> {
+    let expected =
+        env::try_hex_to_key::<PublicKey>(&expected).map_err(CliError::InvalidExpectedPublicKey)?;
+    if expected != public_key {
+        return Err(CliError::PublicKeyMismatch {
+            expected: env::array_to_hex(expected.as_bytes()),
+            actual: env::array_to_hex(public_key.as_bytes()),
+        });
+    }
+    Ok(())
+}
+
+#[test]
+fn test_check_public_key() {
+    let (secret_key, public_key) = Ed25519::generate_key();
+    let derived = Ed25519::public_key(secret_key);
+    let hex = env::array_to_hex(public_key.as_bytes());
+
+    assert!(check_public_key(derived, hex.clone()).is_ok());
+    assert!(check_public_key(derived, format!("0x{}", hex)).is_ok());
+
+    let (_, other_public_key) = Ed25519::generate_key();
+    assert!(matches!(
+        check_public_key(derived, env::array_to_hex(other_public_key.as_bytes())),
+        Err(CliError::PublicKeyMismatch { .. })
+    ));
+    assert!(matches!(
+        check_public_key(derived, "0x2a".to_string()),
+        Err(CliError::InvalidExpectedPublicKey(_))
+    ));
+}
+
+#[cfg(test)]
+fn attestation(challenge: Option<&str>) -> (Attestation<'static>, PublicKey) {
+    let (secret_key, public_key) = Ed25519::generate_key();
+    let attestation = Attestation {
+        accuracy: 6,
+        key: SigningKey::Local(secret_key),
+        public_key,
+        tag: DOMAIN_TAG,
+        challenge: challenge.map(str::to_string),
+        signed: SignedData::Geohash,
+        hasher: HashAlgorithm::default(),
+        debug_digest: false,
+        json_pretty: false,
+        qr: false,
+        qr_render: false,
+    };
+    (attestation, public_key)
+}
+
+#[cfg(test)]
+struct MockLocation;
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl Location for MockLocation {
+    type Output = String;
+
+    async fn current_location(&self, accuracy: u8) -> Result<Self::Output, oracle::LocationError> {
+        Ok("gcpvj0dj4y8w"[..accuracy as usize].to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_count_limits_attestations() {
+    let (attestation, _) = attestation(None);
+
+    for count in [1, 3] {
+        let mut out = Vec::new();
+        emit(&MockLocation, &attestation, &Schedule::new(Some(count), Some(0)), &mut out)
+            .await
+            .expect("emit");
+        let out = String::from_utf8(out).expect("utf-8");
+        assert_eq!(out.lines().count() as u64, count);
+        for line in out.lines() {
+            let signed: SignedLocation<String, Vec<u8>> =
+                serde_json::from_str(line).expect("signed location");
+            assert_eq!(signed.location, "gcpvj0");
+        }
+    }
+
+    // A single attestation unless repeating
+    let mut out = Vec::new();
+    emit(&MockLocation, &attestation, &Schedule::new(None, None), &mut out)
+        .await
+        .expect("emit");
+    assert_eq!(String::from_utf8(out).expect("utf-8").lines().count(), 1);
+    assert_eq!(Schedule::new(None, Some(60)).count, None);
+}
+
+/// A provider with state, numbering each lookup like a source that is queried
+/// anew every time, e.g. a GPS receiver
+#[cfg(test)]
+struct CountingLocation {
+    calls: std::sync::atomic::AtomicU8,
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl Location for CountingLocation {
+    type Output = String;
+
+    async fn current_location(&self, _accuracy: u8) -> Result<Self::Output, oracle::LocationError> {
+        let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(format!("gcpvj{}", call))
+    }
+}
+
+#[tokio::test]
+async fn test_repeated_attestations_query_the_provider_again() {
+    let (attestation, public_key) = attestation(None);
+    let provider = CountingLocation {
+        calls: Default::default(),
+    };
+
+    let mut out = Vec::new();
+    emit(
+        &provider,
+        &attestation,
+        &Schedule::new(Some(3), Some(0)),
+        &mut out,
+    )
+    .await
+    .expect("emit");
+
+    // Each attestation signs a fresh lookup of the same provider
+    assert_eq!(provider.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    let out = String::from_utf8(out).expect("utf-8");
+    for (call, line) in out.lines().enumerate() {
+        let signed: SignedLocation<String, Vec<u8>> =
+            serde_json::from_str(line).expect("signed location");
+        assert_eq!(signed.location, format!("gcpvj{}", call));
+        let digest = HashAlgorithm::default().hash(signing_payload(
+            DOMAIN_TAG,
+            None,
+            signed.location.as_bytes(),
+        ));
+        assert!(<Ed25519 as oracle::Verifier>::verify(
+            digest,
+            &signed.signature,
+            public_key
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_cached_location_is_signed_for_every_attestation() {
+    let (attestation, _) = attestation(None);
+    let provider = Cached::new(
+        CountingLocation {
+            calls: Default::default(),
+        },
+        Duration::from_secs(3600),
+    );
+
+    let mut out = Vec::new();
+    emit(
+        &provider,
+        &attestation,
+        &Schedule::new(Some(3), Some(0)),
+        &mut out,
+    )
+    .await
+    .expect("emit");
+
+    // Three attestations of the one location looked up
+    let out = String::from_utf8(out).expect("utf-8");
+    assert_eq!(out.lines().count(), 3);
+    for line in out.lines() {
+        let signed: SignedLocation<String, Vec<u8>> =
+            serde_json::from_str(line).expect("signed location");
+        assert_eq!(signed.location, "gcpvj0");
+    }
+    // A second lookup would have been numbered gcpvj1
+    assert_eq!(
+        oracle::location(&provider, 6).await.expect("location"),
+        "gcpvj0"
+    );
+}
+
+#[tokio::test]
+async fn test_sign_provided_geohash() {
+    let (attestation, public_key) = attestation(Some("gcpv"));
+    let attestation = Attestation {
+        accuracy: 8,
+        ..attestation
+    };
+    let mut out = Vec::new();
+    emit(
+        &ProvidedGeohash("gcpvj0du".to_string()),
+        &attestation,
+        &Schedule::new(None, None),
+        &mut out,
+    )
+    .await
+    .expect("emit");
+
+    // Signed like a looked-up geohash, whatever the accuracy
+    let signed: SignedLocation<String, Vec<u8>> =
+        serde_json::from_slice(&out).expect("signed location");
+    assert_eq!(signed.location, "gcpvj0du");
+    assert_eq!(signed.challenge.as_deref(), Some("gcpv"));
+    let digest =
+        HashAlgorithm::default().hash(signing_payload(DOMAIN_TAG, Some(b"gcpv"), b"gcpvj0du"));
+    assert!(<Ed25519 as oracle::Verifier>::verify(
+        digest,
+        &signed.signature,
+        public_key
+    ));
+}
+
+#[tokio::test]
+async fn test_qr_output() {
+    let (attestation, public_key) = attestation(Some("gcpv"));
+    let mut attestation = Attestation {
+        debug_digest: true,
+        qr: true,
+        ..attestation
+    };
+
+    for qr_render in [false, true] {
+        attestation.qr_render = qr_render;
+        let mut out = Vec::new();
+        emit(
+            &MockLocation,
+            &attestation,
+            &Schedule::new(None, None),
+            &mut out,
+        )
+        .await
+        .expect("emit");
+        let out = String::from_utf8(out).expect("utf-8");
+        assert_eq!(out.lines().count() > 1, qr_render);
+
+        // The last line is the compact attestation, which still verifies
+        let signed = qr::decode(out.lines().last().expect("line")).expect("compact attestation");
+        assert_eq!(signed.location, "gcpvj0");
+        assert_eq!(signed.challenge.as_deref(), Some("gcpv"));
+        assert_eq!(signed.digest, None);
+        let digest = HashAlgorithm::default().hash(signing_payload(
+            DOMAIN_TAG,
+            Some(b"gcpv"),
+            signed.location.as_bytes(),
+        ));
+        assert!(<Ed25519 as oracle::Verifier>::verify(
+            digest,
+            &signed.signature,
+            public_key
+        ));
+    }
+}
+
+#[cfg(test)]
+struct Unserializable;
+
+#[cfg(test)]
+impl Serialize for Unserializable {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("location is not serializable"))
+    }
+}
+
+#[test]
+fn test_unserializable_location_keeps_signature() {
+    let signed_location = SignedLocation {
+        location: Unserializable,
+        signature: vec![0xab, 0xcd],
+        public_key: "0x00".to_string(),
+        signed: SignedData::Geohash,
+        version: PAYLOAD_VERSION,
+        challenge: None,
+        digest: None,
+    };
+
+    for pretty in [false, true] {
+        match render(&signed_location, pretty) {
+            Err(CliError::UnserializableAttestation { signature, source }) => {
+                assert_eq!(signature, "0xabcd");
+                assert_eq!(source.to_string(), "location is not serializable");
+            }
+            other => panic!("expected an unserializable attestation, got {:?}", other),
+        }
+    }
+
+    let readable = SignedLocation {
+        location: "gcpvj0".to_string(),
+        signature: signed_location.signature,
+        public_key: signed_location.public_key,
+        signed: signed_location.signed,
+        version: signed_location.version,
+        challenge: None,
+        digest: None,
+    };
+    assert!(render(&readable, false)
+        .expect("render")
+        .starts_with(r#"{"location":"gcpvj0","signature":[171,205]"#));
+}
+
+#[test]
+fn test_source_order() {
+    let parse = |args: &[&str]| {
+        let mut argv = vec!["oracle", "run"];
+        argv.extend_from_slice(args);
+        match Args::try_parse_from(argv).map(|args| args.command) {
+            Ok(Commands::Run { source_order, .. }) => Ok(source_order),
+            Ok(_) => panic!("expected the run command"),
+            Err(e) => Err(e.kind()),
+        }
+    };
+
+    assert_eq!(parse(&[]), Ok(vec![IpSource::IpInfo]));
+    assert_eq!(
+        parse(&["--source-order=ip-api,ipinfo"]),
+        Ok(vec![IpSource::IpApi, IpSource::IpInfo])
+    );
+    assert_eq!(
+        parse(&["--source-order=gps,ipinfo"]),
+        Err(clap::error::ErrorKind::ValueValidation)
+    );
+    assert_eq!(
+        parse(&["--source-order=ipinfo", "--coords-from-stdin"]),
+        Err(clap::error::ErrorKind::ArgumentConflict)
+    );
+}
+
+#[test]
+fn test_accuracy_flag() {
+    let parse = |args: &[&str]| {
+        let mut argv = vec!["oracle", "run"];
+        argv.extend_from_slice(args);
+        match Args::try_parse_from(argv).map(|args| args.command) {
+            Ok(Commands::Run { accuracy, .. }) => Ok(accuracy),
+            Ok(_) => panic!("expected the run command"),
+            Err(e) => Err(e.kind()),
+        }
+    };
+
+    assert_eq!(parse(&[]), Ok(6));
+    assert_eq!(parse(&["--accuracy=8"]), Ok(8));
+    assert_eq!(
+        parse(&["--accuracy=13"]),
+        Err(clap::error::ErrorKind::ValueValidation)
+    );
+    assert_eq!(parse(&["8"]), Err(clap::error::ErrorKind::UnknownArgument));
+}
+
+#[test]
+fn test_key_sources_are_exclusive() {
+    let key = format!("--key=0x{}2a", "0".repeat(62));
+    let parse = |args: &[&str]| {
+        let mut argv = vec!["oracle"];
+        argv.extend_from_slice(args);
+        Args::try_parse_from(argv)
+    };
+
+    for command in [vec!["run"], vec!["sign-geohash", "--geohash=gcpvj0"]] {
+        let with = |sources: &[&str]| parse(&[command.as_slice(), sources].concat());
+        // One source, or none to fall back to ORACLE_KEY
+        assert!(with(&[]).is_ok());
+        assert!(with(&[key.as_str()]).is_ok());
+        assert!(with(&["--keyring-service=aoi-oracle"]).is_ok());
+        assert!(with(&["--pkcs11-module=libsofthsm2.so", "--pkcs11-slot=1"]).is_ok());
+
+        for sources in [
+            [key.as_str(), "--keyring-service=aoi-oracle"],
+            [key.as_str(), "--pkcs11-module=libsofthsm2.so"],
+            [
+                "--keyring-service=aoi-oracle",
+                "--pkcs11-module=libsofthsm2.so",
+            ],
+        ] {
+            let error = with(&sources).err().expect("conflicting key sources");
+            assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+
+        // A slot means nothing without a module
+        let error = with(&["--pkcs11-slot=1"])
+            .err()
+            .expect("slot without module");
+        assert_eq!(
+            error.kind(),
+            clap::error::ErrorKind::MissingRequiredArgument
+        );
+    }
+}
+
+#[test]
+fn test_output_args_are_shared() {
+    let parse = |args: &[&str]| {
+        let mut argv = vec!["oracle"];
+        argv.extend_from_slice(args);
+        match Args::try_parse_from(argv).map(|args| args.command) {
+            Ok(Commands::Run { output, .. } | Commands::SignGeohash { output, .. }) => {
+                Ok((output.domain_tag, output.hasher, output.qr_render))
+            }
+            Ok(_) => panic!("expected a signing command"),
+            Err(e) => Err(e.kind()),
+        }
+    };
+
+    for command in [vec!["run"], vec!["sign-geohash", "--geohash=gcpvj0"]] {
+        let with = |args: &[&str]| parse(&[command.as_slice(), args].concat());
+        assert_eq!(
+            with(&[
+                "--domain-tag=my-attendance",
+                "--hasher=sha256",
+                "--qr",
+                "--qr-render"
+            ]),
+            Ok(("my-attendance".to_string(), HashAlgorithm::Sha256, true))
+        );
+        assert_eq!(
+            with(&["--qr", "--json-pretty"]),
+            Err(clap::error::ErrorKind::ArgumentConflict)
+        );
+    }
+}
+
+#[cfg(not(feature = "pkcs11"))]
+#[test]
+fn test_pkcs11_needs_the_feature() {
+    let token = Token {
+        module: PathBuf::from("libsofthsm2.so"),
+        slot: 0,
+        label: None,
+    };
+    assert!(matches!(
+        load_key(None, None, keystore::ACCOUNT, Some(token), None),
+        Err(CliError::Pkcs11Unsupported)
+    ));
+}
+
+#[test]
+fn test_command_line_key_takes_precedence() {
+    let mut array = [0; 32];
+    array[31] = 0x2a;
+    // Whatever ORACLE_KEY holds, the given key is used or rejected
+    let key = resolve_key(Some(format!("0x{}2a", "0".repeat(62)))).expect("key");
+    assert_eq!(*key.as_bytes(), array);
+    assert!(matches!(
+        resolve_key(Some("0x2a".to_string())),
+        Err(CliError::InvalidKey(_))
+    ));
+}
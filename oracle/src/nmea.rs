@@ -0,0 +1,220 @@
+//! NMEA 0183 sentence parsing.
+//!
+//! This module extracts coordinates and fix quality from the `GGA` and `RMC`
+//! sentences emitted by GPS receivers, as needed by a GPS location source.
+//! Both the `$GP` talker and others such as the multi-constellation `$GN`
+//! are accepted.
+
+use thiserror::Error;
+
+/// Errors that can occur while parsing an NMEA sentence.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum NmeaError {
+    /// The sentence does not start with `$`.
+    #[error("sentence does not start with '$'")]
+    MissingStart,
+
+    /// The checksum after `*` does not match the sentence.
+    #[error("checksum mismatch")]
+    Checksum,
+
+    /// The sentence type is neither `GGA` nor `RMC`.
+    ///
+    /// # Fields
+    /// * String - The address field of the sentence, e.g. `GPGSV`
+    #[error("unsupported sentence: {0}")]
+    UnsupportedSentence(String),
+
+    /// A required field is missing or malformed.
+    ///
+    /// # Fields
+    /// * &'static str - The name of the field
+    #[error("invalid field: {0}")]
+    InvalidField(&'static str),
+
+    /// The receiver reported that it has no position fix.
+    #[error("no fix")]
+    NoFix,
+}
+
+/// The quality of a position fix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixQuality {
+    /// A standalone GPS fix, GGA quality `1`. Active RMC sentences report this.
+    Gps,
+    /// A differential GPS fix, GGA quality `2`.
+    Differential,
+    /// Any other valid GGA quality, e.g. `4` for RTK.
+    Other(u8),
+}
+
+/// A position fix parsed from an NMEA sentence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fix {
+    /// Latitude in decimal degrees, negative in the southern hemisphere.
+    pub latitude: f64,
+    /// Longitude in decimal degrees, negative in the western hemisphere.
+    pub longitude: f64,
+    /// The quality of the fix.
+    pub quality: FixQuality,
+}
+
+/// Parses a `GGA` or `RMC` sentence into a position fix.
+///
+/// A trailing `*hh` checksum is verified when present.
+///
+/// # Arguments
+/// * `sentence` - The sentence, with or without a trailing line ending
+///
+/// # Returns
+/// * `Result<Fix, NmeaError>` - The fix, or `NmeaError::NoFix` if the receiver
+///   reported none
+pub fn parse(sentence: &str) -> Result<Fix, NmeaError> {
+    let sentence = sentence.trim_end();
+    let body = sentence.strip_prefix('$').ok_or(NmeaError::MissingStart)?;
+    let body = match body.split_once('*') {
+        Some((body, checksum)) => {
+            let expected = u8::from_str_radix(checksum, 16).map_err(|_| NmeaError::Checksum)?;
+            if body.bytes().fold(0, |acc, b| acc ^ b) != expected {
+                return Err(NmeaError::Checksum);
+            }
+            body
+        }
+        None => body,
+    };
+
+    let fields: Vec<&str> = body.split(',').collect();
+    let address = fields[0];
+    let field = |index: usize| fields.get(index).copied().unwrap_or("");
+
+    match address.get(2..) {
+        Some("GGA") => {
+            let quality = match field(6) {
+                "0" => return Err(NmeaError::NoFix),
+                "1" => FixQuality::Gps,
+                "2" => FixQuality::Differential,
+                other => FixQuality::Other(
+                    other.parse().map_err(|_| NmeaError::InvalidField("fix quality"))?,
+                ),
+            };
+            Ok(Fix {
+                latitude: coordinate(field(2), field(3), 2, 'N', 'S')?,
+                longitude: coordinate(field(4), field(5), 3, 'E', 'W')?,
+                quality,
+            })
+        }
+        Some("RMC") => {
+            match field(2) {
+                "A" => {}
+                "V" => return Err(NmeaError::NoFix),
+                _ => return Err(NmeaError::InvalidField("status")),
+            }
+            Ok(Fix {
+                latitude: coordinate(field(3), field(4), 2, 'N', 'S')?,
+                longitude: coordinate(field(5), field(6), 3, 'E', 'W')?,
+                quality: FixQuality::Gps,
+            })
+        }
+        _ => Err(NmeaError::UnsupportedSentence(address.to_string())),
+    }
+}
+
+/// Converts an NMEA `(d)ddmm.mmmm` value and hemisphere into decimal degrees.
+fn coordinate(
+    value: &str,
+    hemisphere: &str,
+    degree_digits: usize,
+    positive: char,
+    negative: char,
+) -> Result<f64, NmeaError> {
+    let name = if positive == 'N' { "latitude" } else { "longitude" };
+    let invalid = NmeaError::InvalidField(name);
+    if value.len() <= degree_digits || !value.is_char_boundary(degree_digits) {
+        return Err(invalid);
+    }
+
+    let (degrees, minutes) = value.split_at(degree_digits);
+    let degrees: f64 = degrees.parse().map_err(|_| invalid.clone())?;
+    let minutes: f64 = minutes.parse().map_err(|_| invalid.clone())?;
+    if minutes >= 60.0 {
+        return Err(invalid);
+    }
+
+    let magnitude = degrees + minutes / 60.0;
+    match hemisphere.chars().next() {
+        Some(c) if c == positive && hemisphere.len() == 1 => Ok(magnitude),
+        Some(c) if c == negative && hemisphere.len() == 1 => Ok(-magnitude),
+        _ => Err(invalid),
+    }
+}
+
+#[cfg(test)]
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+}
+
+#[test]
+fn test_parse_gga() {
+    let fix = parse("$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n")
+        .expect("fix");
+    assert_close(fix.latitude, 48.0 + 7.038 / 60.0);
+    assert_close(fix.longitude, 11.0 + 31.0 / 60.0);
+    assert_eq!(fix.quality, FixQuality::Gps);
+
+    let fix = parse("$GNGGA,092750.000,5321.6802,N,00630.3372,W,2,8,1.03,61.7,M,55.2,M,,*6B")
+        .expect("fix");
+    assert_close(fix.latitude, 53.0 + 21.6802 / 60.0);
+    assert_close(fix.longitude, -(6.0 + 30.3372 / 60.0));
+    assert_eq!(fix.quality, FixQuality::Differential);
+}
+
+#[test]
+fn test_parse_rmc() {
+    let fix = parse("$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A")
+        .expect("fix");
+    assert_close(fix.latitude, 48.0 + 7.038 / 60.0);
+    assert_close(fix.longitude, 11.0 + 31.0 / 60.0);
+
+    let fix = parse("$GPRMC,225446,A,4916.45,N,12311.12,W,000.5,054.7,191194,020.3,E*68")
+        .expect("fix");
+    assert_close(fix.latitude, 49.0 + 16.45 / 60.0);
+    assert_close(fix.longitude, -(123.0 + 11.12 / 60.0));
+    assert_eq!(fix.quality, FixQuality::Gps);
+}
+
+#[test]
+fn test_no_fix_is_rejected() {
+    assert_eq!(
+        parse("$GPGGA,002153.000,,,,,0,00,,,M,,M,,*7D"),
+        Err(NmeaError::NoFix)
+    );
+    assert_eq!(
+        parse("$GPRMC,002153.000,V,,,,,,,150124,,,N*4B"),
+        Err(NmeaError::NoFix)
+    );
+}
+
+#[test]
+fn test_malformed_sentences_are_rejected() {
+    // Corrupted checksum
+    assert_eq!(
+        parse("$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*48"),
+        Err(NmeaError::Checksum)
+    );
+    assert_eq!(
+        parse("$GPGSV,3,1,11,03,03,111,00,04,15,270,00,06,01,010,00,13,06,292,00"),
+        Err(NmeaError::UnsupportedSentence("GPGSV".to_string()))
+    );
+    assert_eq!(
+        parse("GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,"),
+        Err(NmeaError::MissingStart)
+    );
+    assert_eq!(
+        parse("$GPGGA,123519,4807.038,X,01131.000,E,1,08,0.9,545.4,M,46.9,M,,"),
+        Err(NmeaError::InvalidField("latitude"))
+    );
+    assert_eq!(
+        parse("$GPRMC,123519,A,4807.038,N,,E,022.4,084.4,230394,003.1,W"),
+        Err(NmeaError::InvalidField("longitude"))
+    );
+}
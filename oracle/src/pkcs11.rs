@@ -0,0 +1,188 @@
+//! Ed25519 signing with a key held by a PKCS#11 token, such as a hardware
+//! security module.
+//!
+//! The [`Signer`](crate::Signer) trait is handed the private key, which a
+//! token never releases, so a token key is used through [`Pkcs11Ed25519`]
+//! instead. It opens a session on a slot of the vendor's PKCS#11 module, logs
+//! in with the user PIN and signs digests on the token with the `CKM_EDDSA`
+//! mechanism. The signatures are the same 64-byte Ed25519 signatures as those
+//! of [`Ed25519`](crate::ed25519::Ed25519), so verifiers and the attendance
+//! pallet cannot tell them apart.
+//!
+//! Only compiled with the `pkcs11` feature.
+
+use std::path::Path;
+
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+use thiserror::Error;
+
+use crate::{Hash, PublicKey, SignerError};
+
+/// Errors that can occur while opening a PKCS#11 token key.
+#[derive(Error, Debug)]
+pub enum Pkcs11Error {
+    /// The PKCS#11 module failed, e.g. it could not be loaded or the PIN was
+    /// rejected.
+    ///
+    /// # Fields
+    /// * String - The underlying PKCS#11 error
+    #[error("PKCS#11 error: {0}")]
+    Token(String),
+
+    /// No token is present in the slot.
+    ///
+    /// # Fields
+    /// * u64 - The slot that was opened
+    #[error("no token in PKCS#11 slot {0}")]
+    NoToken(u64),
+
+    /// The token holds no Ed25519 key of this kind with the given label.
+    ///
+    /// # Fields
+    /// * &'static str - `private key` or `public key`
+    #[error("no Ed25519 {0} on the token")]
+    KeyNotFound(&'static str),
+
+    /// The token holds several Ed25519 keys and no label was given to choose.
+    #[error("several Ed25519 keys on the token, choose one by label")]
+    AmbiguousKey,
+
+    /// The token's public key is not a 32-byte Ed25519 point.
+    #[error("the token's public key is not an Ed25519 key")]
+    InvalidPublicKey,
+}
+
+impl From<cryptoki::error::Error> for Pkcs11Error {
+    fn from(e: cryptoki::error::Error) -> Self {
+        Pkcs11Error::Token(e.to_string())
+    }
+}
+
+/// An Ed25519 key pair on a PKCS#11 token, with a logged-in session to sign with.
+///
+/// The session is closed, and the module finalized, when this is dropped.
+pub struct Pkcs11Ed25519 {
+    /// The logged-in session on the token
+    session: Session,
+    /// The handle of the private key on the token
+    private_key: ObjectHandle,
+    /// The public key, read from the token once
+    public_key: PublicKey,
+}
+
+impl Pkcs11Ed25519 {
+    /// Opens the Ed25519 key on the token in `slot`.
+    ///
+    /// # Arguments
+    /// * `module` - Path of the vendor's PKCS#11 library, e.g. `libsofthsm2.so`
+    /// * `slot` - The ID of the slot holding the token
+    /// * `pin` - The user PIN of the token
+    /// * `label` - The `CKA_LABEL` of the key pair, required if the token
+    ///   holds several Ed25519 keys
+    ///
+    /// # Returns
+    /// * `Result<Self, Pkcs11Error>` - The token key, or an error if the module
+    ///   fails or the token holds no single matching key pair
+    pub fn open(
+        module: &Path,
+        slot: u64,
+        pin: &str,
+        label: Option<&str>,
+    ) -> Result<Self, Pkcs11Error> {
+        let pkcs11 = Pkcs11::new(module)?;
+        pkcs11.initialize(CInitializeArgs::OsThreads)?;
+        let token_slot = pkcs11
+            .get_slots_with_token()?
+            .into_iter()
+            .find(|token_slot| token_slot.id() == slot)
+            .ok_or(Pkcs11Error::NoToken(slot))?;
+        let session = pkcs11.open_ro_session(token_slot)?;
+        session.login(UserType::User, Some(&AuthPin::new(pin.to_string())))?;
+
+        let private_key = find_key(&session, ObjectClass::PRIVATE_KEY, "private key", label)?;
+        let public_key = find_key(&session, ObjectClass::PUBLIC_KEY, "public key", label)?;
+        let public_key = match session
+            .get_attributes(public_key, &[AttributeType::EcPoint])?
+            .as_slice()
+        {
+            [Attribute::EcPoint(point)] => ec_point_to_key(point)?,
+            _ => return Err(Pkcs11Error::InvalidPublicKey),
+        };
+        Ok(Self {
+            session,
+            private_key,
+            public_key,
+        })
+    }
+
+    /// The public key of the token's key pair.
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    /// Signs a message hash on the token.
+    ///
+    /// # Arguments
+    /// * `message` - The hash of the message to sign
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>, SignerError>` - The Ed25519 signature, or an error if
+    ///   the token refused to sign
+    pub fn sign(&self, message: Hash) -> Result<Vec<u8>, SignerError> {
+        self.session
+            .sign(&Mechanism::Eddsa, self.private_key, message.as_bytes())
+            .map_err(|e| SignerError::SignatureFailed(e.to_string()))
+    }
+}
+
+/// Finds the single Ed25519 key of `class`, named `kind` in errors, on the
+/// token, with `label` if given.
+fn find_key(
+    session: &Session,
+    class: ObjectClass,
+    kind: &'static str,
+    label: Option<&str>,
+) -> Result<ObjectHandle, Pkcs11Error> {
+    let mut template = vec![
+        Attribute::Class(class),
+        Attribute::KeyType(KeyType::EC_EDWARDS),
+    ];
+    if let Some(label) = label {
+        template.push(Attribute::Label(label.as_bytes().to_vec()));
+    }
+    match session.find_objects(&template)?.as_slice() {
+        [key] => Ok(*key),
+        [] => Err(Pkcs11Error::KeyNotFound(kind)),
+        _ => Err(Pkcs11Error::AmbiguousKey),
+    }
+}
+
+/// Reads an Ed25519 public key from a token's `CKA_EC_POINT`.
+///
+/// PKCS#11 3.0 stores the point DER-encoded as an OCTET STRING, but some
+/// tokens store the 32 raw bytes, so both are accepted.
+fn ec_point_to_key(point: &[u8]) -> Result<PublicKey, Pkcs11Error> {
+    let raw = match point {
+        [0x04, 32, raw @ ..] if raw.len() == 32 => raw,
+        raw => raw,
+    };
+    raw.try_into()
+        .map(PublicKey::new)
+        .map_err(|_| Pkcs11Error::InvalidPublicKey)
+}
+
+#[test]
+fn test_ec_point_to_key() {
+    let key = [0x2a; 32];
+    let der = [&[0x04, 32][..], &key].concat();
+    assert!(matches!(ec_point_to_key(&der), Ok(k) if *k.as_bytes() == key));
+    assert!(matches!(ec_point_to_key(&key), Ok(k) if *k.as_bytes() == key));
+    assert!(matches!(
+        ec_point_to_key(&der[..33]),
+        Err(Pkcs11Error::InvalidPublicKey)
+    ));
+}
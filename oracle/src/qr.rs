@@ -0,0 +1,218 @@
+//! Compact encoding of signed locations for QR codes.
+//!
+//! Mobile check-in flows carry the attestation in a QR code that the attendee
+//! scans, where the JSON output is needlessly large. The compact form is a
+//! single line: the `aoi:` prefix followed by the unpadded URL-safe base64 of
+//! the SCALE encoding of the version, the kind of signed data, the challenge,
+//! the location, the public key and the signature, in that order. The prefix
+//! identifies the format and the version comes first, so a scanner can tell
+//! which attestations it understands before decoding the rest. The debugging
+//! digest is left out.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use codec::{Decode, Encode};
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+use thiserror::Error;
+
+use crate::{ParseHexError, PublicKey, SignedData, SignedLocation};
+
+/// The prefix of every compact attestation.
+pub const PREFIX: &str = "aoi:";
+
+/// Errors that can occur while encoding or decoding a compact attestation.
+#[derive(Error, Debug, PartialEq)]
+pub enum QrError {
+    /// The public key of the signed location is not a hex key.
+    ///
+    /// # Fields
+    /// * ParseHexError - Why the public key could not be parsed
+    #[error("invalid public key: {0}")]
+    InvalidPublicKey(ParseHexError),
+
+    /// The string does not start with [`PREFIX`].
+    #[error("not a compact attestation, expected the `aoi:` prefix")]
+    MissingPrefix,
+
+    /// The string is not a valid compact attestation.
+    ///
+    /// # Fields
+    /// * String - Why the attestation could not be decoded
+    #[error("malformed compact attestation: {0}")]
+    Malformed(String),
+
+    /// The attestation does not fit in a QR code.
+    ///
+    /// # Fields
+    /// * String - The underlying QR code error
+    #[error("failed to render QR code: {0}")]
+    Render(String),
+}
+
+/// The encoded fields of a compact attestation.
+#[derive(Encode, Decode)]
+struct Compact {
+    version: u8,
+    signed: u8,
+    challenge: Option<Vec<u8>>,
+    location: Vec<u8>,
+    public_key: [u8; 32],
+    signature: Vec<u8>,
+}
+
+/// Encodes a signed location as a compact attestation.
+///
+/// # Arguments
+/// * `signed_location` - The signed location to encode
+///
+/// # Returns
+/// * `Result<String, QrError>` - The `aoi:`-prefixed attestation, or
+///   `QrError::InvalidPublicKey`
+pub fn encode<O: AsRef<[u8]>>(
+    signed_location: &SignedLocation<O, Vec<u8>>,
+) -> Result<String, QrError> {
+    let public_key = signed_location
+        .public_key
+        .parse::<PublicKey>()
+        .map_err(QrError::InvalidPublicKey)?;
+    let compact = Compact {
+        version: signed_location.version,
+        signed: match signed_location.signed {
+            SignedData::Geohash => 0,
+            SignedData::Coords => 1,
+        },
+        challenge: signed_location
+            .challenge
+            .as_ref()
+            .map(|challenge| challenge.as_bytes().to_vec()),
+        location: signed_location.location.as_ref().to_vec(),
+        public_key: *public_key.as_bytes(),
+        signature: signed_location.signature.clone(),
+    };
+    Ok(format!(
+        "{}{}",
+        PREFIX,
+        URL_SAFE_NO_PAD.encode(compact.encode())
+    ))
+}
+
+/// Decodes a compact attestation into a signed location.
+///
+/// # Arguments
+/// * `compact` - The `aoi:`-prefixed attestation, as scanned
+///
+/// # Returns
+/// * `Result<SignedLocation<String, Vec<u8>>, QrError>` - The signed location,
+///   without a digest, or the reason the attestation could not be decoded
+pub fn decode(compact: &str) -> Result<SignedLocation<String, Vec<u8>>, QrError> {
+    let encoded = compact
+        .trim()
+        .strip_prefix(PREFIX)
+        .ok_or(QrError::MissingPrefix)?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|e| QrError::Malformed(e.to_string()))?;
+    let compact =
+        Compact::decode(&mut &bytes[..]).map_err(|e| QrError::Malformed(e.to_string()))?;
+    let text = |bytes: Vec<u8>| {
+        String::from_utf8(bytes).map_err(|_| QrError::Malformed("text is not UTF-8".to_string()))
+    };
+    Ok(SignedLocation {
+        location: text(compact.location)?,
+        signature: compact.signature,
+        public_key: format!("0x{}", hex::encode(compact.public_key)),
+        signed: match compact.signed {
+            0 => SignedData::Geohash,
+            1 => SignedData::Coords,
+            other => return Err(QrError::Malformed(format!("unknown signed data {}", other))),
+        },
+        version: compact.version,
+        challenge: compact.challenge.map(text).transpose()?,
+        digest: None,
+    })
+}
+
+/// Renders a compact attestation as a QR code for the terminal.
+///
+/// Each character covers two modules stacked vertically, with the quiet zone
+/// around the code that scanners need.
+///
+/// # Arguments
+/// * `compact` - The attestation to render
+///
+/// # Returns
+/// * `Result<String, QrError>` - The lines of the QR code, or
+///   `QrError::Render` if the attestation is too long for one
+pub fn render(compact: &str) -> Result<String, QrError> {
+    let code = QrCode::new(compact.as_bytes()).map_err(|e| QrError::Render(e.to_string()))?;
+    Ok(code.render::<Dense1x2>().quiet_zone(true).build())
+}
+
+#[cfg(test)]
+fn signed_location(challenge: Option<&str>) -> SignedLocation<String, Vec<u8>> {
+    SignedLocation {
+        location: "gcpvj0du6".to_string(),
+        signature: vec![7; 64],
+        public_key: format!("0x{}", "11".repeat(32)),
+        signed: SignedData::Geohash,
+        version: crate::PAYLOAD_VERSION,
+        challenge: challenge.map(str::to_string),
+        digest: None,
+    }
+}
+
+#[test]
+fn test_compact_round_trip() {
+    for challenge in [None, Some("gcpvj")] {
+        let signed = signed_location(challenge);
+        let compact = encode(&signed).unwrap();
+        assert!(compact.starts_with(PREFIX));
+        assert!(compact[PREFIX.len()..]
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+        assert_eq!(decode(&compact), Ok(signed));
+    }
+
+    // Far smaller than the JSON output, and the digest is not carried
+    let signed = SignedLocation {
+        digest: Some(format!("0x{}", "22".repeat(32))),
+        ..signed_location(Some("gcpvj"))
+    };
+    let compact = encode(&signed).unwrap();
+    assert!(compact.len() * 2 < serde_json::to_string(&signed).unwrap().len());
+    assert_eq!(decode(&compact).unwrap().digest, None);
+}
+
+#[test]
+fn test_malformed_compact_attestations() {
+    let compact = encode(&signed_location(None)).unwrap();
+    assert_eq!(
+        decode(&compact[PREFIX.len()..]),
+        Err(QrError::MissingPrefix)
+    );
+    assert!(matches!(
+        decode(&compact[..compact.len() - 8]),
+        Err(QrError::Malformed(_))
+    ));
+    assert!(matches!(decode("aoi:!!"), Err(QrError::Malformed(_))));
+    assert!(matches!(
+        encode(&SignedLocation {
+            public_key: "0x2a".to_string(),
+            ..signed_location(None)
+        }),
+        Err(QrError::InvalidPublicKey(ParseHexError::InvalidLength))
+    ));
+}
+
+#[test]
+fn test_render_qr_code() {
+    let compact = encode(&signed_location(Some("gcpvj"))).unwrap();
+    let code = render(&compact).unwrap();
+    let lines = code.lines().collect::<Vec<_>>();
+    assert!(lines.len() > 10);
+    assert!(lines
+        .iter()
+        .all(|line| line.chars().count() == lines[0].chars().count()));
+    assert!(matches!(render(&"a".repeat(8000)), Err(QrError::Render(_))));
+}
@@ -0,0 +1,40 @@
+//! SHA-256 hash implementation.
+//!
+//! This module provides an implementation of the `Hasher` trait
+//! using the SHA-256 cryptographic hash function.
+
+use sp_io::hashing::sha2_256;
+use crate::{Hash, Hasher};
+
+/// Implementation of the `Hasher` trait using SHA-256.
+///
+/// SHA-256 is the widely deployed SHA-2 hash function producing a 32-byte hash,
+/// for runtimes whose payload hasher is `Sha2_256`.
+pub struct Sha256;
+
+impl Hasher for Sha256 {
+    /// Computes a SHA-256 hash of the provided message.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The data to hash, which can be any type that can be
+    ///   referenced as a byte slice.
+    ///
+    /// # Returns
+    ///
+    /// A 32-byte `Hash` containing the SHA-256 hash of the input data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use oracle::{sha2_256::Sha256, Hasher};
+    /// let message = "Hello, world!";
+    /// let hash = Sha256::hash(message);
+    /// ```
+    fn hash<T>(message: T) -> Hash
+    where
+        T: AsRef<[u8]>,
+    {
+        Hash::new(sha2_256(message.as_ref()))
+    }
+}
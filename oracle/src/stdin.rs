@@ -0,0 +1,170 @@
+//! Coordinates read from standard input.
+//!
+//! This module lets another tool provide the coordinates the oracle signs,
+//! instead of the built-in IP geolocation. The input is either a
+//! `latitude,longitude` pair or a JSON object such as
+//! `{"latitude": 51.5074, "longitude": -0.1278}`.
+
+use std::io::Read;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Errors that can occur while reading coordinates from stdin.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum StdinError {
+    /// Standard input could not be read.
+    ///
+    /// # Fields
+    /// * String - The underlying I/O error
+    #[error("failed to read stdin: {0}")]
+    Read(String),
+
+    /// Standard input contained only whitespace.
+    #[error("no coordinates on stdin")]
+    Empty,
+
+    /// The input is neither a `latitude,longitude` pair nor a coordinate object.
+    ///
+    /// # Fields
+    /// * String - Why the input could not be parsed
+    #[error("malformed coordinates on stdin: {0}")]
+    Malformed(String),
+
+    /// The latitude is not a number within -90 to 90 degrees.
+    ///
+    /// # Fields
+    /// * String - The latitude as given
+    #[error("latitude must be between -90 and 90 degrees, got `{0}`")]
+    Latitude(String),
+
+    /// The longitude is not a number within -180 to 180 degrees.
+    ///
+    /// # Fields
+    /// * String - The longitude as given
+    #[error("longitude must be between -180 and 180 degrees, got `{0}`")]
+    Longitude(String),
+}
+
+/// Structure for deserializing coordinates given as JSON.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Coordinates {
+    /// Latitude in degrees
+    #[serde(alias = "lat")]
+    latitude: f64,
+    /// Longitude in degrees
+    #[serde(alias = "lon")]
+    longitude: f64,
+}
+
+/// Reads all of stdin and parses it as coordinates.
+///
+/// # Returns
+/// * `Result<(f64, f64), StdinError>` - The `(latitude, longitude)`, or why
+///   the input was rejected
+pub fn read_coordinates() -> Result<(f64, f64), StdinError> {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| StdinError::Read(e.to_string()))?;
+    parse_coordinates(&input)
+}
+
+/// Parses coordinates given as `latitude,longitude` or as a JSON object.
+///
+/// The JSON object has `latitude` and `longitude` fields, or the shorter
+/// `lat` and `lon`. Surrounding whitespace is ignored.
+///
+/// # Arguments
+/// * `input` - The text to parse
+///
+/// # Returns
+/// * `Result<(f64, f64), StdinError>` - The `(latitude, longitude)`, or why
+///   the input was rejected
+pub fn parse_coordinates(input: &str) -> Result<(f64, f64), StdinError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(StdinError::Empty);
+    }
+
+    if input.starts_with('{') {
+        let coordinates: Coordinates =
+            serde_json::from_str(input).map_err(|e| StdinError::Malformed(e.to_string()))?;
+        return checked(coordinates.latitude, coordinates.longitude);
+    }
+
+    let (lat, lon) = input.split_once(',').ok_or_else(|| {
+        StdinError::Malformed("expected `latitude,longitude` or a JSON object".to_string())
+    })?;
+    let (lat, lon) = (lat.trim(), lon.trim());
+    let latitude = lat
+        .parse::<f64>()
+        .map_err(|_| StdinError::Latitude(lat.to_string()))?;
+    let longitude = lon
+        .parse::<f64>()
+        .map_err(|_| StdinError::Longitude(lon.to_string()))?;
+    checked(latitude, longitude)
+}
+
+/// Checks that coordinates are finite and within range.
+fn checked(latitude: f64, longitude: f64) -> Result<(f64, f64), StdinError> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err(StdinError::Latitude(latitude.to_string()));
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err(StdinError::Longitude(longitude.to_string()));
+    }
+    Ok((latitude, longitude))
+}
+
+#[test]
+fn test_parse_coordinates() {
+    assert_eq!(parse_coordinates("51.5074,-0.1278"), Ok((51.5074, -0.1278)));
+    assert_eq!(parse_coordinates(" 51.5074 , -0.1278\n"), Ok((51.5074, -0.1278)));
+    assert_eq!(
+        parse_coordinates(r#"{"latitude": -33.8688, "longitude": 151.2093}"#),
+        Ok((-33.8688, 151.2093))
+    );
+    assert_eq!(
+        parse_coordinates(r#"{"lat": 48.85, "lon": 2.35}"#),
+        Ok((48.85, 2.35))
+    );
+}
+
+#[test]
+fn test_malformed_coordinates_are_rejected() {
+    assert_eq!(parse_coordinates(" \n"), Err(StdinError::Empty));
+    assert!(matches!(
+        parse_coordinates("51.5074 -0.1278"),
+        Err(StdinError::Malformed(_))
+    ));
+    assert!(matches!(
+        parse_coordinates(r#"{"latitude": 51.5074}"#),
+        Err(StdinError::Malformed(_))
+    ));
+    assert!(matches!(
+        parse_coordinates(r#"{"latitude": "51.5", "longitude": 0.1}"#),
+        Err(StdinError::Malformed(_))
+    ));
+    assert_eq!(
+        parse_coordinates("north,-0.1278"),
+        Err(StdinError::Latitude("north".to_string()))
+    );
+    assert_eq!(
+        parse_coordinates("51.5074,"),
+        Err(StdinError::Longitude("".to_string()))
+    );
+    assert_eq!(
+        parse_coordinates("91,0"),
+        Err(StdinError::Latitude("91".to_string()))
+    );
+    assert_eq!(
+        parse_coordinates("0,-180.5"),
+        Err(StdinError::Longitude("-180.5".to_string()))
+    );
+    assert_eq!(
+        parse_coordinates("NaN,0"),
+        Err(StdinError::Latitude("NaN".to_string()))
+    );
+}
@@ -0,0 +1,253 @@
+//! Submission of attestations to an attendance node.
+//!
+//! This module turns a signed location produced by `oracle run` into a
+//! `submission_with_signature` extrinsic, signs it with the attendee's account
+//! and submits it over RPC, waiting until it is finalized. The call is built
+//! dynamically from the node's metadata, so no generated runtime interface is
+//! needed.
+
+use std::io::Read;
+use std::str::FromStr;
+
+use oracle::{SignedData, SignedLocation, PAYLOAD_VERSION};
+use serde::Serialize;
+use subxt::dynamic::Value;
+use subxt::{OnlineClient, PolkadotConfig};
+use subxt_signer::sr25519::Keypair;
+use subxt_signer::SecretUri;
+use thiserror::Error;
+
+/// The name the template runtime gives the attendance pallet.
+pub const PALLET: &str = "AttendanceModule";
+
+/// Errors that can occur while submitting an attestation.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SubmitError {
+    /// The attestation cannot be submitted to the attendance pallet.
+    ///
+    /// # Fields
+    /// * String - Why the attestation was refused
+    #[error("attestation cannot be submitted: {0}")]
+    Attestation(String),
+
+    /// The signer seed is not a valid secret URI.
+    ///
+    /// # Fields
+    /// * String - The underlying parsing error
+    #[error("invalid signer seed: {0}")]
+    Seed(String),
+
+    /// The node could not be reached, or the transaction could not be sent.
+    ///
+    /// # Fields
+    /// * String - The underlying RPC error
+    #[error("rpc error: {0}")]
+    Rpc(String),
+
+    /// The transaction was included but the call failed.
+    ///
+    /// # Fields
+    /// * String - The dispatch error reported by the runtime
+    #[error("submission rejected: {0}")]
+    Rejected(String),
+}
+
+/// The arguments of a `submission_with_signature` call.
+#[derive(Debug, PartialEq)]
+pub struct Submission {
+    /// The challenge geohash the attestation is bound to
+    pub challenge: Vec<u8>,
+    /// The signed location geohash
+    pub location: Vec<u8>,
+    /// The layout version of the signed payload
+    pub version: u8,
+    /// The oracle's signature over the payload
+    pub signature: Vec<u8>,
+}
+
+/// The outcome of a finalized submission, written out as JSON.
+#[derive(Serialize, Debug)]
+pub struct Receipt {
+    /// The `0x`-prefixed hex hash of the submitted extrinsic
+    pub extrinsic: String,
+    /// The `0x`-prefixed hex hash of the block that finalized it
+    pub block: String,
+}
+
+impl TryFrom<SignedLocation<String, Vec<u8>>> for Submission {
+    type Error = SubmitError;
+
+    /// Checks that an attestation is one the attendance pallet can accept.
+    ///
+    /// The pallet only accepts signed geohashes bound to a challenge, in the
+    /// payload version it supports, with a 64-byte Ed25519 signature.
+    fn try_from(attestation: SignedLocation<String, Vec<u8>>) -> Result<Self, Self::Error> {
+        if attestation.signed != SignedData::Geohash {
+            return Err(SubmitError::Attestation(
+                "only signed geohashes can be submitted".to_string(),
+            ));
+        }
+        if attestation.version != PAYLOAD_VERSION {
+            return Err(SubmitError::Attestation(format!(
+                "unsupported payload version {}, expected {}",
+                attestation.version, PAYLOAD_VERSION
+            )));
+        }
+        let challenge = attestation.challenge.ok_or_else(|| {
+            SubmitError::Attestation("not bound to a challenge, pass --challenge".to_string())
+        })?;
+        if attestation.signature.len() != 64 {
+            return Err(SubmitError::Attestation(format!(
+                "signature must be 64 bytes, got {}",
+                attestation.signature.len()
+            )));
+        }
+        Ok(Self {
+            challenge: challenge.into_bytes(),
+            location: attestation.location.into_bytes(),
+            version: attestation.version,
+            signature: attestation.signature,
+        })
+    }
+}
+
+/// Reads an attestation from stdin, as written by `oracle run`.
+///
+/// # Returns
+/// * `Result<Submission, SubmitError>` - The call arguments, or
+///   `SubmitError::Attestation` if stdin holds no submittable attestation
+pub fn read_attestation() -> Result<Submission, SubmitError> {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| SubmitError::Attestation(format!("failed to read stdin: {}", e)))?;
+    let attestation: SignedLocation<String, Vec<u8>> =
+        serde_json::from_str(&input).map_err(|e| SubmitError::Attestation(e.to_string()))?;
+    attestation.try_into()
+}
+
+/// Parses a signer seed into an sr25519 key pair.
+///
+/// # Arguments
+/// * `seed` - A secret URI, such as a mnemonic phrase, a `0x` hex seed or a
+///   dev account like `//Alice`, optionally with derivation junctions
+///
+/// # Returns
+/// * `Result<Keypair, SubmitError>` - The key pair, or `SubmitError::Seed`
+pub fn signer(seed: &str) -> Result<Keypair, SubmitError> {
+    let uri = SecretUri::from_str(seed).map_err(|e| SubmitError::Seed(e.to_string()))?;
+    Keypair::from_uri(&uri).map_err(|e| SubmitError::Seed(e.to_string()))
+}
+
+/// Submits an attestation and waits for it to be finalized.
+///
+/// # Arguments
+/// * `rpc_url` - The WebSocket RPC endpoint of the node
+/// * `pallet` - The name of the attendance pallet in the runtime
+/// * `signer` - The account submitting, which is credited with the attendance
+/// * `namespace` - The namespace of the challenge, empty for the default one
+/// * `submission` - The call arguments
+///
+/// # Returns
+/// * `Result<Receipt, SubmitError>` - The extrinsic and block hashes once the
+///   call succeeded, `SubmitError::Rejected` if the runtime refused it, or
+///   `SubmitError::Rpc` if it was never included
+pub async fn submit(
+    rpc_url: &str,
+    pallet: &str,
+    signer: &Keypair,
+    namespace: &str,
+    submission: Submission,
+) -> Result<Receipt, SubmitError> {
+    let api = OnlineClient::<PolkadotConfig>::from_url(rpc_url)
+        .await
+        .map_err(|e| SubmitError::Rpc(e.to_string()))?;
+
+    let call = subxt::dynamic::tx(
+        pallet,
+        "submission_with_signature",
+        vec![
+            Value::from_bytes(namespace),
+            Value::from_bytes(submission.challenge),
+            Value::from_bytes(submission.location),
+            Value::u128(submission.version.into()),
+            Value::from_bytes(submission.signature),
+        ],
+    );
+
+    let progress = api
+        .tx()
+        .sign_and_submit_then_watch_default(&call, signer)
+        .await
+        .map_err(|e| SubmitError::Rpc(e.to_string()))?;
+    let extrinsic = progress.extrinsic_hash();
+
+    let events = progress
+        .wait_for_finalized_success()
+        .await
+        .map_err(|e| match e {
+            subxt::Error::Runtime(e) => SubmitError::Rejected(e.to_string()),
+            e => SubmitError::Rpc(e.to_string()),
+        })?;
+
+    Ok(Receipt {
+        extrinsic: format!("0x{}", hex::encode(extrinsic)),
+        block: format!("0x{}", hex::encode(events.block_hash())),
+    })
+}
+
+#[cfg(test)]
+fn attestation(challenge: Option<&str>) -> SignedLocation<String, Vec<u8>> {
+    SignedLocation {
+        location: "gcpvj0".to_string(),
+        signature: vec![7; 64],
+        public_key: format!("0x{}", "00".repeat(32)),
+        signed: SignedData::Geohash,
+        version: PAYLOAD_VERSION,
+        challenge: challenge.map(str::to_string),
+        digest: None,
+    }
+}
+
+#[test]
+fn test_submission_from_attestation() {
+    assert_eq!(
+        Submission::try_from(attestation(Some("gcpv"))),
+        Ok(Submission {
+            challenge: b"gcpv".to_vec(),
+            location: b"gcpvj0".to_vec(),
+            version: PAYLOAD_VERSION,
+            signature: vec![7; 64],
+        })
+    );
+}
+
+#[test]
+fn test_unsubmittable_attestations_are_refused() {
+    let refused = |attestation| {
+        matches!(
+            Submission::try_from(attestation),
+            Err(SubmitError::Attestation(_))
+        )
+    };
+
+    assert!(refused(attestation(None)));
+    assert!(refused(SignedLocation {
+        signed: SignedData::Coords,
+        ..attestation(Some("gcpv"))
+    }));
+    assert!(refused(SignedLocation {
+        version: PAYLOAD_VERSION + 1,
+        ..attestation(Some("gcpv"))
+    }));
+    assert!(refused(SignedLocation {
+        signature: vec![7; 63],
+        ..attestation(Some("gcpv"))
+    }));
+}
+
+#[test]
+fn test_signer_seed() {
+    assert!(signer("//Alice").is_ok());
+    assert!(matches!(signer("not a seed"), Err(SubmitError::Seed(_))));
+}
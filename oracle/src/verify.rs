@@ -0,0 +1,185 @@
+//! Oracle Attestation Verifier
+//!
+//! This binary validates attestations produced by `oracle run` before they are
+//! submitted on-chain. It reads the oracle's JSON output from stdin, reconstructs
+//! the hashed payload and verifies the Ed25519 signature against the embedded
+//! public key, exiting non-zero if the attestation is invalid.
+//!
+//! # Usage
+//!
+//! ```
+//! oracle run --accuracy=8 | oracle-verify && submit-attestation
+//! ```
+//!
+//! When the hasher of the target runtime is unknown, `--try-hashers` reports
+//! every supported hasher the attestation verifies with:
+//!
+//! ```
+//! oracle run --accuracy=8 --hasher=keccak256 | oracle-verify --try-hashers
+//! ```
+
+mod hasher;
+
+use std::io::Read;
+
+use clap::Parser;
+use hasher::HashAlgorithm;
+use oracle::ed25519::Ed25519;
+use oracle::{signing_payload, PublicKey, SignedLocation, Verifier, DOMAIN_TAG, PAYLOAD_VERSION};
+
+/// Command-line arguments for the verifier.
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Domain-separation tag the attestation was signed with.
+    ///
+    /// Must match the `--domain-tag` passed to `oracle run`.
+    #[arg(long, default_value_t = String::from_utf8_lossy(DOMAIN_TAG).into_owned())]
+    domain_tag: String,
+
+    /// Hash function the attestation was signed with.
+    ///
+    /// Must match the `--hasher` passed to `oracle run`.
+    #[arg(long, default_value = "blake2_256")]
+    hasher: HashAlgorithm,
+
+    /// Try every supported hasher and report those the attestation verifies with.
+    ///
+    /// A diagnostic for hasher mismatches between the oracle and the target
+    /// runtime; `--hasher` is ignored.
+    #[arg(long)]
+    try_hashers: bool,
+}
+
+/// The hashers `--try-hashers` tries, with their `--hasher` names.
+const HASHERS: [(HashAlgorithm, &str); 3] = [
+    (HashAlgorithm::Blake2_256, "blake2_256"),
+    (HashAlgorithm::Keccak256, "keccak256"),
+    (HashAlgorithm::Sha256, "sha256"),
+];
+
+/// Main entry point for the verifier.
+///
+/// Exits with status 0 if the attestation on stdin is valid, 1 otherwise.
+fn main() {
+    let args = Args::parse();
+
+    let mut input = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut input) {
+        eprintln!("Error: Failed to read attestation: {}", e);
+        std::process::exit(1);
+    }
+
+    let signed_location: SignedLocation<String, Vec<u8>> = match serde_json::from_str(&input) {
+        Ok(signed_location) => signed_location,
+        Err(e) => {
+            eprintln!("Error: Failed to parse attestation: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if signed_location.version != PAYLOAD_VERSION {
+        eprintln!(
+            "Error: Unsupported payload version {}, expected {}",
+            signed_location.version, PAYLOAD_VERSION
+        );
+        std::process::exit(1);
+    }
+
+    let public_key = match signed_location.public_key.parse::<PublicKey>() {
+        Ok(public_key) => public_key,
+        Err(e) => {
+            eprintln!("Error: Invalid public key: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let tag = args.domain_tag.as_bytes();
+    if args.try_hashers {
+        let matching = matching_hashers(&signed_location, tag, public_key);
+        if matching.is_empty() {
+            eprintln!("Error: Invalid signature with every hasher");
+            std::process::exit(1);
+        }
+        for name in matching {
+            println!("valid with {}", name);
+        }
+    } else if verifies(&signed_location, tag, args.hasher, public_key) {
+        println!("valid");
+    } else {
+        eprintln!("Error: Invalid signature");
+        std::process::exit(1);
+    }
+}
+
+/// Whether the signature of an attestation verifies with a hasher.
+///
+/// # Arguments
+/// * `signed_location` - The attestation to check
+/// * `tag` - The domain-separation tag it was signed with
+/// * `hasher` - The hash function applied to the payload
+/// * `public_key` - The public key of the signing oracle
+fn verifies(
+    signed_location: &SignedLocation<String, Vec<u8>>,
+    tag: &[u8],
+    hasher: HashAlgorithm,
+    public_key: PublicKey,
+) -> bool {
+    let payload = signing_payload(
+        tag,
+        signed_location.challenge.as_deref().map(str::as_bytes),
+        signed_location.location.as_bytes(),
+    );
+    Ed25519::verify(hasher.hash(payload), &signed_location.signature, public_key)
+}
+
+/// The names of the supported hashers an attestation verifies with.
+///
+/// # Arguments
+/// * `signed_location` - The attestation to check
+/// * `tag` - The domain-separation tag it was signed with
+/// * `public_key` - The public key of the signing oracle
+///
+/// # Returns
+/// * `Vec<&'static str>` - The `--hasher` names, empty if the signature is
+///   invalid with every hasher
+fn matching_hashers(
+    signed_location: &SignedLocation<String, Vec<u8>>,
+    tag: &[u8],
+    public_key: PublicKey,
+) -> Vec<&'static str> {
+    HASHERS
+        .iter()
+        .filter(|(hasher, _)| verifies(signed_location, tag, *hasher, public_key))
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+#[test]
+fn test_try_hashers_identifies_the_hasher() {
+    use oracle::{SignedData, Signer};
+
+    let (secret_key, public_key) = Ed25519::generate_key();
+    for (hasher, name) in HASHERS {
+        let digest = hasher.hash(signing_payload(DOMAIN_TAG, Some(b"gcpv"), b"gcpvj0"));
+        let signed_location = SignedLocation {
+            location: "gcpvj0".to_string(),
+            signature: Ed25519::sign(digest, secret_key).unwrap(),
+            public_key: format!("0x{}", hex::encode(public_key.as_bytes())),
+            signed: SignedData::Geohash,
+            version: PAYLOAD_VERSION,
+            challenge: Some("gcpv".to_string()),
+            digest: None,
+        };
+        assert_eq!(
+            matching_hashers(&signed_location, DOMAIN_TAG, public_key),
+            vec![name]
+        );
+        assert_eq!(name.parse::<HashAlgorithm>(), Ok(hasher));
+
+        // Neither a wrong tag nor a wrong key verifies with any hasher
+        assert!(matching_hashers(&signed_location, b"other", public_key).is_empty());
+        let (_, other_key) = Ed25519::generate_key();
+        assert!(matching_hashers(&signed_location, DOMAIN_TAG, other_key).is_empty());
+    }
+}
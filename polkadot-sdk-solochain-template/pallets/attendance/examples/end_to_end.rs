@@ -0,0 +1,170 @@
+//! The intended attendance flow, from the oracle to the pallet:
+//!
+//! 1. an oracle generates an Ed25519 key,
+//! 2. it locates the attendee, here with a mock location source, and signs the geohash bound to
+//!    the challenge, exactly as `oracle run --challenge` does,
+//! 3. governance sets the oracle's public key in a minimal runtime and an organizer creates
+//!    the challenge,
+//! 4. the attendee submits the signature with `submission_with_signature`.
+//!
+//! The runtime uses the oracle's defaults, Ed25519 signatures over the Blake2-256 digest of the
+//! payload, so types crossing the two crates are the real ones. Run it with:
+//!
+//! ```bash
+//! cargo run -p pallet-attendance --example end_to_end
+//! ```
+
+use frame_support::{derive_impl, parameter_types, BoundedVec};
+use oracle::blake2_256::Blake2_256;
+use oracle::ed25519::Ed25519;
+use oracle::{signing_payload, Hasher as _, Location, LocationError, Signer as _, DOMAIN_TAG};
+use pallet_attendance::Mintable;
+use sp_core::ed25519;
+use sp_runtime::traits::BlakeTwo256;
+use sp_runtime::BuildStorage;
+
+type Block = frame_system::mocking::MockBlock<Runtime>;
+
+frame_support::construct_runtime!(
+    pub enum Runtime
+    {
+        System: frame_system,
+        Attendance: pallet_attendance,
+    }
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Runtime {
+    type Block = Block;
+}
+
+parameter_types! {
+    pub const MaxGeohashLength: u32 = 12;
+    pub const DomainTag: &'static [u8] = DOMAIN_TAG;
+    pub const MaxVerifyingKeyVersions: u32 = 2;
+    pub const MaxExpiringPerBlock: u32 = 2;
+    pub const MaxOracles: u32 = 2;
+    pub const MaxProofLength: u32 = 256;
+    pub const MaxDistanceRadius: u64 = 1_000;
+    pub const MaxOracleSigs: u32 = 3;
+    pub const AttestationThreshold: u32 = 1;
+    pub const MaxHistory: u32 = 2;
+    pub const AllowExactMatch: bool = true;
+    pub const MaxLabelLength: u32 = 16;
+    pub const MaxArchivedPerBlock: u32 = 3;
+    pub const PackedChallengeKeys: bool = false;
+    pub const MaxNamespace: u32 = 8;
+    pub const SubmissionTtl: u64 = 0;
+}
+
+/// Rewards nobody, the example only follows the submission
+pub struct NoReward;
+
+impl<AccountId> Mintable<AccountId> for NoReward {
+    type Balance = ();
+
+    fn mint(_account: &AccountId, _amount: ()) {}
+}
+
+impl pallet_attendance::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type WeightInfo = ();
+    type MaxGeohashLength = MaxGeohashLength;
+    type DomainTag = DomainTag;
+    type MaxVerifyingKeyVersions = MaxVerifyingKeyVersions;
+    type MaxExpiringPerBlock = MaxExpiringPerBlock;
+    type MaxOracles = MaxOracles;
+    type MaxProofLength = MaxProofLength;
+    type MaxDistanceRadius = MaxDistanceRadius;
+    type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type MaxOracleSigs = MaxOracleSigs;
+    type AttestationThreshold = AttestationThreshold;
+    type MaxHistory = MaxHistory;
+    type AllowExactMatch = AllowExactMatch;
+    type MaxLabelLength = MaxLabelLength;
+    type MaxArchivedPerBlock = MaxArchivedPerBlock;
+    type PackedChallengeKeys = PackedChallengeKeys;
+    type MaxNamespace = MaxNamespace;
+    type SubmissionTtl = SubmissionTtl;
+    type RewardAmount = ();
+    type Mint = NoReward;
+    type PublicKeyOfOracle = ed25519::Public;
+    type PayloadHasher = BlakeTwo256;
+    type Signature = ed25519::Signature;
+    type Verify = ed25519::Pair;
+    type ProofVerifier = pallet_attendance::Groth16Bn254;
+}
+
+/// Stands in for GPS or IP geolocation, always locating the attendee in central London
+struct MockLocation;
+
+#[async_trait::async_trait]
+impl Location for MockLocation {
+    type Output = String;
+
+    async fn current_location(&self, accuracy: u8) -> Result<String, LocationError> {
+        let mut geohash = String::from("gcpvj0du");
+        geohash.truncate(accuracy as usize);
+        Ok(geohash)
+    }
+}
+
+const ATTENDEE: u64 = 1;
+const ORGANIZER: u64 = 2;
+
+fn bounded<S: frame_support::traits::Get<u32>>(bytes: &[u8]) -> BoundedVec<u8, S> {
+    bytes.to_vec().try_into().expect("within bound")
+}
+
+fn main() {
+    let challenge = "gcpvj";
+
+    // The oracle side
+    let (secret_key, public_key) = Ed25519::generate_key();
+    let location =
+        futures::executor::block_on(oracle::location(&MockLocation, 7)).expect("mock location");
+    let digest = Blake2_256::hash(signing_payload(
+        DOMAIN_TAG,
+        Some(challenge.as_bytes()),
+        location.as_bytes(),
+    ));
+    let signature = Ed25519::sign(digest, secret_key).expect("signed");
+    println!("oracle signed {} for challenge {}", location, challenge);
+
+    // The chain side
+    let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::<Runtime>::default()
+        .build_storage()
+        .expect("genesis storage")
+        .into();
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        Attendance::set_oracle_public_key(
+            RuntimeOrigin::root(),
+            bounded(public_key.as_bytes()),
+            false,
+        )
+        .expect("oracle key set");
+        let challenge = Attendance::geohash_from_str(challenge).expect("challenge geohash");
+        Attendance::create_challenge(
+            RuntimeOrigin::signed(ORGANIZER),
+            bounded(b""),
+            challenge.clone(),
+            None,
+            None,
+        )
+        .expect("challenge created");
+
+        Attendance::submission_with_signature(
+            RuntimeOrigin::signed(ATTENDEE),
+            bounded(b""),
+            challenge,
+            Attendance::geohash_from_str(&location).expect("location geohash"),
+            oracle::PAYLOAD_VERSION,
+            bounded(&signature),
+        )
+        .expect("submission accepted");
+
+        let event = System::events().pop().expect("an event").event;
+        println!("{:?}", event);
+    });
+}
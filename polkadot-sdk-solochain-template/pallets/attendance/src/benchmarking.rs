@@ -1,34 +1,248 @@
 //! Benchmarking setup for pallet-attendance
+//!
+//! The two attestation methods are benchmarked in their worst case, so their weights can be
+//! compared: `submission_with_signature` is dominated by Ed25519 verification, and
+//! `submission_with_proof` by Groth16 verification over BN254. `set_verifying_key` is
+//! benchmarked over the length of the key it stores.
 #![cfg(feature = "runtime-benchmarks")]
 use super::*;
 
 #[allow(unused)]
 use crate::Pallet as Attendance;
+use ark_bn254::{Bn254, Fr, G1Projective, G2Projective};
+use ark_ec::{CurveGroup, Group};
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use frame_benchmarking::v2::*;
+use frame_support::traits::{ConstU32, Get};
+use frame_support::BoundedVec;
 use frame_system::RawOrigin;
+use sp_core::crypto::Pair;
+use sp_core::Hasher;
+use sp_runtime::app_crypto::ByteArray;
+use sp_runtime::Vec;
+
+/// A valid geohash at least as long as any benchmarked challenge
+const GEOHASH: &[u8] = b"gcpvj0dj4y8w";
+
+/// The bound of `RawVerifyingKey`
+const MAX_VERIFYING_KEY_LENGTH: u32 = 2048;
+
+/// The longest challenge that a strictly more precise location can still attend
+fn longest_challenge<T: Config>() -> Challenge<T> {
+    let length = (T::MaxGeohashLength::get() as usize)
+        .saturating_sub(1)
+        .min(GEOHASH.len() - 1);
+    GEOHASH[..length]
+        .to_vec()
+        .try_into()
+        .expect("shorter than MaxGeohashLength")
+}
+
+/// Registers `challenge` in the default namespace, without capacity or expiry
+fn create<T: Config>(challenge: &Challenge<T>) {
+    let owner: T::AccountId = account("owner", 0, 0);
+    Attendance::<T>::create_challenge(
+        RawOrigin::Signed(owner).into(),
+        Namespace::<T>::default(),
+        challenge.clone(),
+        None,
+        None,
+    )
+    .expect("valid challenge");
+}
+
+/// Fills the attendance history of `who`, so the submission also drops the oldest entry
+fn fill_history<T: Config>(who: &T::AccountId, challenge: &Challenge<T>) {
+    let history = (0..T::MaxHistory::get())
+        .map(|_| (Namespace::<T>::default(), challenge.clone()))
+        .collect::<Vec<_>>();
+    AttendanceHistory::<T>::insert(who, BoundedVec::truncate_from(history));
+}
+
+/// The oracle key pair derived from `seed`
+fn oracle<T: Config>(seed: u8) -> T::Verify {
+    T::Verify::from_seed_slice(&[seed; 32]).expect("32-byte seed")
+}
+
+/// The raw public key of an oracle key pair
+fn raw_public_key<T: Config>(pair: &T::Verify) -> BoundedVec<u8, ConstU32<32>> {
+    pair.public()
+        .to_raw_vec()
+        .try_into()
+        .expect("32-byte public key")
+}
+
+/// A verifying key and a proof that it accepts for `public_inputs`.
+///
+/// The key is built from known discrete logarithms, so a proof can be computed without the
+/// circuit. Such a key is worthless for soundness, but it has the shape of a real prefix
+/// circuit key with as many public inputs, and verification costs the same: one multi-Miller
+/// loop over three pairs, a final exponentiation and a scalar multiplication per input.
+fn forged_proof(public_inputs: &[Fr]) -> (Vec<u8>, Vec<u8>) {
+    let (g1, g2) = (G1Projective::generator(), G2Projective::generator());
+    let (alpha, beta, a) = (Fr::from(2u64), Fr::from(3u64), Fr::from(5u64));
+    let gamma_abc: Vec<Fr> = (1..=public_inputs.len() as u64 + 1).map(Fr::from).collect();
+
+    // With gamma = delta = g2 and b = g2, verification checks in the exponent that
+    // a = alpha * beta + gamma_abc[0] + sum(input_i * gamma_abc[i + 1]) + c
+    let prepared_inputs = gamma_abc[0]
+        + public_inputs
+            .iter()
+            .zip(&gamma_abc[1..])
+            .map(|(input, k)| *input * k)
+            .sum::<Fr>();
+    let c = a - alpha * beta - prepared_inputs;
+
+    let verifying_key = VerifyingKey::<Bn254> {
+        alpha_g1: (g1 * alpha).into_affine(),
+        beta_g2: (g2 * beta).into_affine(),
+        gamma_g2: g2.into_affine(),
+        delta_g2: g2.into_affine(),
+        gamma_abc_g1: gamma_abc.iter().map(|k| (g1 * k).into_affine()).collect(),
+    };
+    let proof = Proof::<Bn254> {
+        a: (g1 * a).into_affine(),
+        b: g2.into_affine(),
+        c: (g1 * c).into_affine(),
+    };
+
+    let mut verifying_key_bytes = Vec::new();
+    verifying_key
+        .serialize_uncompressed(&mut verifying_key_bytes)
+        .expect("serializable");
+    let mut proof_bytes = Vec::new();
+    proof
+        .serialize_uncompressed(&mut proof_bytes)
+        .expect("serializable");
+    (verifying_key_bytes, proof_bytes)
+}
+
+/// A verifying key with the shape of a prefix circuit key for as many public inputs as
+/// `RawVerifyingKey` holds: 520 bytes of points, and 64 more per input, uncompressed.
+fn largest_verifying_key() -> Vec<u8> {
+    let inputs = (MAX_VERIFYING_KEY_LENGTH as usize - 520) / 64;
+    forged_proof(&vec![Fr::from(0u64); inputs]).0
+}
 
 #[benchmarks]
 mod benchmarks {
     use super::*;
 
+    /// Worst case: the longest challenge and location, a signature only one of `MaxOracles`
+    /// added oracles verifies after the primary oracle failed, and a full history.
     #[benchmark]
-    fn do_something() {
-        let value = 100u32;
+    fn submission_with_signature() {
+        let challenge = longest_challenge::<T>();
+        let location: Challenge<T> = GEOHASH[..challenge.len() + 1]
+            .to_vec()
+            .try_into()
+            .expect("valid location");
+        create::<T>(&challenge);
+
+        let signer = oracle::<T>(0);
+        Oracle::<T>::put(raw_public_key::<T>(&oracle::<T>(1)));
+        let others = T::MaxOracles::get().saturating_sub(1) as u8;
+        for seed in 2..others + 2 {
+            Oracles::<T>::insert(raw_public_key::<T>(&oracle::<T>(seed)), None);
+        }
+        Oracles::<T>::insert(raw_public_key::<T>(&signer), None);
+
         let caller: T::AccountId = whitelisted_caller();
+        fill_history::<T>(&caller, &challenge);
+
+        let message =
+            T::PayloadHasher::hash(&Attendance::<T>::signing_payload(&challenge, &location));
+        let signature: BoundedVec<u8, ConstU32<64>> = signer
+            .sign(message.as_ref())
+            .to_raw_vec()
+            .try_into()
+            .expect("64-byte signature");
+
         #[extrinsic_call]
-        do_something(RawOrigin::Signed(caller), value);
+        submission_with_signature(
+            RawOrigin::Signed(caller.clone()),
+            Namespace::<T>::default(),
+            challenge.clone(),
+            location,
+            PAYLOAD_VERSION,
+            signature,
+        );
 
-        assert_eq!(Something::<T>::get(), Some(value));
+        assert!(Submissions::<T>::contains_key(
+            Attendance::<T>::challenge_key(&Namespace::<T>::default(), &challenge),
+            &caller
+        ));
     }
 
+    /// Worst case: the longest challenge, so the most public inputs, and a full history. The
+    /// forged proof is a Groth16 proof over BN254, so the runtime's `ProofVerifier` must be
+    /// `Groth16Bn254`.
     #[benchmark]
-    fn cause_error() {
-        Something::<T>::put(100u32);
+    fn submission_with_proof() {
+        let challenge = longest_challenge::<T>();
+        let precision = challenge.len() as u32 + 1;
+        create::<T>(&challenge);
+
+        let public_inputs: Vec<Fr> = Attendance::<T>::public_inputs(&challenge, precision)
+            .iter()
+            .map(|input| Fr::deserialize_uncompressed(&input[..]).expect("integer input"))
+            .collect();
+        let (verifying_key, proof) = forged_proof(&public_inputs);
+        let version = Attendance::<T>::rotate_verifying_key(
+            verifying_key
+                .try_into()
+                .expect("verifying key within bound"),
+        );
+
         let caller: T::AccountId = whitelisted_caller();
+        fill_history::<T>(&caller, &challenge);
+
+        #[extrinsic_call]
+        submission_with_proof(
+            RawOrigin::Signed(caller.clone()),
+            Namespace::<T>::default(),
+            challenge.clone(),
+            version,
+            precision,
+            proof.try_into().expect("proof within MaxProofLength"),
+        );
+
+        assert!(Submissions::<T>::contains_key(
+            Attendance::<T>::challenge_key(&Namespace::<T>::default(), &challenge),
+            &caller
+        ));
+    }
+
+    /// Worst case: `MaxVerifyingKeyVersions` of the largest keys are held, so the oldest is
+    /// retired and the others are rewritten with the new key of about `l` bytes. The key must
+    /// decode to be registered, and keys only come in lengths of 520 bytes and 64 more per
+    /// input, so it is the forged key with as many inputs as fit in `l` bytes.
+    #[benchmark]
+    fn set_verifying_key(l: Linear<520, MAX_VERIFYING_KEY_LENGTH>) -> Result<(), BenchmarkError> {
+        let origin =
+            T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let largest = largest_verifying_key();
+        for _ in 0..T::MaxVerifyingKeyVersions::get() {
+            Attendance::<T>::rotate_verifying_key(
+                largest
+                    .clone()
+                    .try_into()
+                    .expect("verifying key within bound"),
+            );
+        }
+        let inputs = (l as usize - 520) / 64;
+        let verifying_key: RawVerifyingKey = forged_proof(&vec![Fr::from(0u64); inputs])
+            .0
+            .try_into()
+            .expect("verifying key within bound");
+        let version = T::MaxVerifyingKeyVersions::get();
+
         #[extrinsic_call]
-        cause_error(RawOrigin::Signed(caller));
+        _(origin as T::RuntimeOrigin, verifying_key.clone());
 
-        assert_eq!(Something::<T>::get(), Some(101u32));
+        assert_eq!(Attendance::<T>::verifying_key(version), Some(verifying_key));
+        Ok(())
     }
 
     impl_benchmark_test_suite!(Attendance, crate::mock::new_test_ext(), crate::mock::Test);
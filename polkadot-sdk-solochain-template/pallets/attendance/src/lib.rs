@@ -45,9 +45,21 @@ pub use pallet::*;
 #[cfg(test)]
 mod mock;
 
+#[cfg(test)]
+mod mock_assets;
+
+#[cfg(test)]
+mod mock_ed25519;
+
+#[cfg(test)]
+mod mock_verifier;
+
 #[cfg(test)]
 mod tests;
 
+pub mod migrations;
+pub mod runtime_api;
+
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 pub mod weights;
@@ -59,25 +71,209 @@ pub mod pallet {
     // Import various useful types required by all FRAME pallets.
     use super::*;
     use frame_support::pallet_prelude::*;
+    use frame_support::traits::fungibles;
     use frame_system::{ensure_signed, pallet_prelude::*};
     use sp_core::crypto::{Pair, Public, Signature};
     use sp_core::Hasher;
     use sp_runtime::app_crypto::ByteArray;
+    use sp_runtime::traits::{One, Saturating, Zero};
+
+    /// How many consecutive blocks are tried when a block's expiry index is full
+    pub const MAX_EXPIRY_SPILL_BLOCKS: u32 = 16;
+
+    /// Version of the signed payload layout, the first byte of every signed payload
+    pub const PAYLOAD_VERSION: u8 = 1;
+
+    /// Log target of this pallet
+    const LOG_TARGET: &str = "runtime::attendance";
 
     pub trait Mintable<T> {
-        fn mint(account: &T);
+        /// The amount of a reward, `Config::RewardAmount` is minted for every submission
+        type Balance;
+
+        fn mint(account: &T, amount: Self::Balance);
+
+        /// Mints the reward, failing the submission if it cannot be minted. Defaults to
+        /// `mint`, which cannot fail. Submissions call it once every check has passed and
+        /// their storage is written, so a reward with effects outside storage, which are not
+        /// rolled back, is only issued for a submission that is accepted.
+        fn try_mint(account: &T, amount: Self::Balance) -> DispatchResult {
+            Self::mint(account, amount);
+            Ok(())
+        }
+    }
+
+    /// The reward amount of the runtime's `Config::Mint`
+    pub type BalanceOf<T> =
+        <<T as Config>::Mint as Mintable<<T as frame_system::Config>::AccountId>>::Balance;
+
+    /// Rewards attendees with `Config::RewardAmount` of the `RewardAsset` held in `Assets`,
+    /// for use as `Config::Mint` on multi-asset chains.
+    pub struct FungiblesReward<Assets, RewardAsset>(PhantomData<(Assets, RewardAsset)>);
+
+    impl<AccountId, Assets, RewardAsset> Mintable<AccountId> for FungiblesReward<Assets, RewardAsset>
+    where
+        Assets: fungibles::Mutate<AccountId>,
+        RewardAsset: Get<Assets::AssetId>,
+    {
+        type Balance = Assets::Balance;
+
+        fn mint(account: &AccountId, amount: Assets::Balance) {
+            let _ = Self::try_mint(account, amount);
+        }
+
+        fn try_mint(account: &AccountId, amount: Assets::Balance) -> DispatchResult {
+            Assets::mint_into(RewardAsset::get(), account, amount).map(|_| ())
+        }
+    }
+
+    /// A public input of a proof: a 32-byte little-endian integer, which must be an element of
+    /// the proof system's scalar field. Integers such as geohash bytes and precisions are
+    /// zero-extended, see [`Pallet::integer_input`].
+    pub type PublicInput = [u8; 32];
+
+    /// A proof system verifying serialized proofs against serialized verifying keys, for use as
+    /// `Config::ProofVerifier`. [`Groth16Bn254`] verifies the circuits of `geohash_prover`.
+    pub trait ProofVerifier {
+        /// Why a proof could not be checked, such as a malformed key or proof. It is logged and
+        /// the submission fails with `InvalidProof`.
+        type Error: core::fmt::Debug;
+
+        /// Whether `proof` proves the statement of `verifying_key` for `public_inputs`.
+        fn verify(
+            verifying_key: &[u8],
+            public_inputs: &[PublicInput],
+            proof: &[u8],
+        ) -> Result<bool, Self::Error>;
+
+        /// Whether `input` is an element of the scalar field, so it can be a public input
+        fn valid_public_input(input: &PublicInput) -> bool;
+
+        /// The number of public inputs `verifying_key` expects, or `None` if it cannot tell,
+        /// such as for a malformed key. Proofs are only verified with that many inputs. It is
+        /// read before every verification, so it should not decode and validate the whole key.
+        fn public_input_count(verifying_key: &[u8]) -> Option<usize>;
+
+        /// Whether `verifying_key` is a well-formed key, so it can be registered
+        fn valid_verifying_key(verifying_key: &[u8]) -> bool;
+    }
+
+    /// Groth16 proofs over BN254, with keys, proofs and public inputs in the uncompressed
+    /// arkworks serialization.
+    pub struct Groth16Bn254;
+
+    /// Why a Groth16 proof over BN254 could not be checked
+    #[derive(Debug)]
+    pub enum Groth16Error {
+        /// The verifying key, the proof or a public input is malformed
+        Serialization(SerializationError),
+        /// The proof could not be verified, e.g. for a public input count that does not
+        /// match the verifying key
+        Verification(<Groth16<Bn254> as SNARK<Fr>>::Error),
+    }
+
+    impl ProofVerifier for Groth16Bn254 {
+        type Error = Groth16Error;
+
+        fn verify(
+            verifying_key: &[u8],
+            public_inputs: &[PublicInput],
+            proof: &[u8],
+        ) -> Result<bool, Groth16Error> {
+            let verifying_key = VerifyingKey::<Bn254>::deserialize_uncompressed(verifying_key)
+                .map_err(Groth16Error::Serialization)?;
+            let proof = Proof::<Bn254>::deserialize_uncompressed(proof)
+                .map_err(Groth16Error::Serialization)?;
+            let public_inputs = public_inputs
+                .iter()
+                .map(|input| Fr::deserialize_uncompressed(&input[..]))
+                .collect::<Result<sp_runtime::Vec<_>, _>>()
+                .map_err(Groth16Error::Serialization)?;
+            Groth16::<Bn254>::verify(&verifying_key, &public_inputs, &proof)
+                .map_err(Groth16Error::Verification)
+        }
+
+        fn valid_public_input(input: &PublicInput) -> bool {
+            Fr::deserialize_uncompressed(&input[..]).is_ok()
+        }
+
+        fn public_input_count(verifying_key: &[u8]) -> Option<usize> {
+            // The key holds one point per public input, after the one for the constant term.
+            // Only the length of `gamma_abc_g1` is read, after skipping `alpha_g1`, `beta_g2`,
+            // `gamma_g2` and `delta_g2` unvalidated, as `verify` decodes the key in full.
+            let mut reader = verifying_key;
+            G1Affine::deserialize_with_mode(&mut reader, Compress::No, Validate::No).ok()?;
+            for _ in 0..3 {
+                G2Affine::deserialize_with_mode(&mut reader, Compress::No, Validate::No).ok()?;
+            }
+            let points =
+                u64::deserialize_with_mode(&mut reader, Compress::No, Validate::No).ok()?;
+            (points as usize).checked_sub(1)
+        }
+
+        fn valid_verifying_key(verifying_key: &[u8]) -> bool {
+            VerifyingKey::<Bn254>::deserialize_uncompressed(verifying_key).is_ok()
+        }
     }
 
+    /// The in-code storage version. Version 1 keys challenges by namespace and geohash.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
     // The `Pallet` struct serves as a placeholder to implement traits, methods and dispatchables
     // (`Call`s) in this pallet.
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
-    type Challenge<T> = BoundedVec<u8, <T as pallet::Config>::MaxGeohashLength>;
+    /// A geohash of at most `MaxGeohashLength` bytes. Longer geohashes are never truncated:
+    /// they fail to convert on the client (see [`Pallet::bounded_geohash`]) and fail to
+    /// decode in an extrinsic, which is rejected before dispatch.
+    pub type Challenge<T> = BoundedVec<u8, <T as pallet::Config>::MaxGeohashLength>;
+    /// The organizer's namespace of a challenge, at most `MaxNamespace` bytes. Every namespace
+    /// can hold its own challenge for a geohash; the empty namespace is the default one.
+    pub type Namespace<T> = BoundedVec<u8, <T as pallet::Config>::MaxNamespace>;
+    /// The key a challenge is stored under, see [`Pallet::challenge_key`]
+    pub type ChallengeKey<T> = (Namespace<T>, Challenge<T>);
     type RawPublicKey = BoundedVec<u8, ConstU32<32>>;
     type RawSignature = BoundedVec<u8, ConstU32<64>>;
-    type RawVerifyingKey = BoundedVec<u8, ConstU32<64>>;
-    type RawProof = BoundedVec<u8, ConstU32<64>>;
+    /// An uncompressed Groth16 verifying key. The prefix circuit of a 12-character challenge,
+    /// with 13 public inputs, has a 1352-byte key.
+    type RawVerifyingKey = BoundedVec<u8, ConstU32<2048>>;
+    type RawProof<T> = BoundedVec<u8, <T as pallet::Config>::MaxProofLength>;
+    /// A commitment to a hidden challenge, an uncompressed field element as produced by the
+    /// prover's `commitment::commitment_bytes`
+    pub type RawCommitment = [u8; 32];
+    /// The key a hidden challenge is stored under
+    pub type CommittedChallengeKey<T> = (Namespace<T>, RawCommitment);
+    /// A human-readable name for an oracle, such as its operator or deployment
+    pub type OracleLabel<T> = BoundedVec<u8, <T as pallet::Config>::MaxLabelLength>;
+    type OracleSignatures<T> =
+        BoundedVec<(RawPublicKey, RawSignature), <T as pallet::Config>::MaxOracleSigs>;
+
+    /// A verifying key tagged with the circuit version it verifies
+    #[derive(Encode, Decode, MaxEncodedLen, TypeInfo, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct VersionedVerifyingKey {
+        pub version: u32,
+        pub key: RawVerifyingKey,
+    }
+
+    /// Parameters of a registered challenge
+    #[derive(Encode, Decode, MaxEncodedLen, TypeInfo, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct ChallengeInfo<AccountId, BlockNumber> {
+        /// Account that created the challenge and may close it
+        pub owner: AccountId,
+        /// Maximum number of accepted submissions, unlimited if `None`
+        pub capacity: Option<u32>,
+        /// Last block at which submissions are accepted, never expires if `None`
+        pub expires_at: Option<BlockNumber>,
+    }
+
+    pub type ChallengeInfoOf<T> =
+        ChallengeInfo<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+    /// Distinct location precisions a challenge accepts, in ascending order. There are at most
+    /// `MaxGeohashLength` of them.
+    pub type AcceptedPrecisionsOf<T> = BoundedVec<u8, <T as pallet::Config>::MaxGeohashLength>;
 
     /// The pallet's configuration trait.
     #[pallet::config]
@@ -94,24 +290,240 @@ pub mod pallet {
         type Signature: Signature;
         /// Verification
         type Verify: Pair<Public = Self::PublicKeyOfOracle, Signature = Self::Signature>;
-        /// Mint to Account
+        /// Proof system of the prefix, distance and committed prefix circuits, e.g.
+        /// `Groth16Bn254` for the circuits of `geohash_prover`
+        type ProofVerifier: ProofVerifier;
+        /// Mint to Account, e.g. `FungiblesReward` to reward in a configured asset
         type Mint: Mintable<Self::AccountId>;
         /// Maximum length allowed for geohash
         type MaxGeohashLength: Get<u32>;
+        /// Domain-separation tag prepended to the signed payload, must match the oracle's tag
+        type DomainTag: Get<&'static [u8]>;
+        /// Number of recent verifying key versions accepted for proofs, at least 1
+        #[pallet::constant]
+        type MaxVerifyingKeyVersions: Get<u32>;
+        /// Maximum number of challenges scheduled to expire in a single block
+        #[pallet::constant]
+        type MaxExpiringPerBlock: Get<u32>;
+        /// Maximum number of oracles added with `add_oracle`, besides the primary oracle
+        #[pallet::constant]
+        type MaxOracles: Get<u32>;
+        /// Maximum length of a serialized proof. An uncompressed BN254 Groth16 proof is
+        /// 256 bytes, see `geohash_prover::UNCOMPRESSED_PROOF_SIZE`.
+        #[pallet::constant]
+        type MaxProofLength: Get<u32>;
+        /// Largest radius accepted by distance proofs, in fixed-point coordinate units
+        #[pallet::constant]
+        type MaxDistanceRadius: Get<u64>;
+        /// Origin allowed to change the precision policy, the oracles and the verifying keys,
+        /// and to pause submissions
+        type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Maximum number of oracle signatures in a multi-oracle submission
+        #[pallet::constant]
+        type MaxOracleSigs: Get<u32>;
+        /// Number of distinct oracles that must sign a multi-oracle submission, at least one
+        #[pallet::constant]
+        type AttestationThreshold: Get<u32>;
+        /// Number of challenges kept in each account's attendance history, 0 to keep none.
+        /// Submissions never fail on a full history, its oldest challenge is dropped instead.
+        #[pallet::constant]
+        type MaxHistory: Get<u32>;
+        /// Maximum length of an oracle's label
+        #[pallet::constant]
+        type MaxLabelLength: Get<u32>;
+        /// Whether a location equal to the challenge, the challenge cell itself, attends it.
+        /// When false the location must be strictly more precise than the challenge.
+        #[pallet::constant]
+        type AllowExactMatch: Get<bool>;
+        /// Maximum number of submission entries of expired challenges cleared in a single
+        /// block. Challenges with more submissions are archived over several blocks.
+        #[pallet::constant]
+        type MaxArchivedPerBlock: Get<u32>;
+        /// Whether challenges are stored under keys packed at 5 bits per character, see
+        /// [`Pallet::challenge_key`]. Changing it requires migrating the stored challenges.
+        #[pallet::constant]
+        type PackedChallengeKeys: Get<bool>;
+        /// Maximum length of a challenge namespace
+        #[pallet::constant]
+        type MaxNamespace: Get<u32>;
+        /// Blocks after which a submission expires and its account may attend the same
+        /// challenge again, e.g. a day of blocks for daily check-ins. 0 for submissions that
+        /// never expire.
+        #[pallet::constant]
+        type SubmissionTtl: Get<BlockNumberFor<Self>>;
+        /// Amount `Mint` rewards each accepted submission with
+        type RewardAmount: Get<BalanceOf<Self>>;
     }
 
+    /// Registered challenges. This and the other maps keyed by challenge use
+    /// [`Pallet::challenge_key`], while values and events hold the geohash itself. Counted, so
+    /// the number of challenges is known without iterating them.
+    #[pallet::storage]
+    pub type Challenges<T: Config> =
+        CountedStorageMap<_, Blake2_128Concat, ChallengeKey<T>, ChallengeInfoOf<T>>;
+
+    /// Keys of the challenges to prune, keyed by the block in which they are pruned. A
+    /// challenge is scheduled for the block after its expiry, spilling to later blocks when
+    /// full.
+    #[pallet::storage]
+    pub type ChallengeExpiries<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<ChallengeKey<T>, T::MaxExpiringPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Expired or closed challenges whose submission entries are still being cleared. They
+    /// cannot be created again until archived.
     #[pallet::storage]
-    pub type Challenges<T: Config> = StorageMap<_, Blake2_128Concat, Challenge<T>, bool>;
+    pub type PendingArchival<T: Config> = StorageMap<_, Blake2_128Concat, ChallengeKey<T>, ()>;
+
+    /// Location precisions accepted by challenges whose owner restricted them. Challenges
+    /// without an entry accept any precision within the precision policy.
+    #[pallet::storage]
+    pub type AcceptedPrecisions<T: Config> =
+        StorageMap<_, Blake2_128Concat, ChallengeKey<T>, AcceptedPrecisionsOf<T>>;
+
+    /// Number of accepted submissions per challenge
+    #[pallet::storage]
+    pub type SubmissionCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, ChallengeKey<T>, u32, ValueQuery>;
 
     #[pallet::storage]
     pub type Oracle<T: Config> = StorageValue<_, RawPublicKey>;
 
+    /// Oracles trusted in addition to the primary `Oracle`, at most `MaxOracles`, with their
+    /// labels. Labels are unique among the oracles that have one.
+    #[pallet::storage]
+    pub type Oracles<T: Config> =
+        CountedStorageMap<_, Blake2_128Concat, RawPublicKey, Option<OracleLabel<T>>>;
+
+    #[pallet::storage]
+    pub type Submissions<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        ChallengeKey<T>,
+        Blake2_128Concat,
+        T::AccountId,
+        bool,
+    >;
+
+    /// The block of each submission, only recorded while `SubmissionTtl` is nonzero. An
+    /// expired entry is replaced when its account submits again, and removed with the
+    /// challenge's submissions when the challenge is archived.
+    #[pallet::storage]
+    pub type SubmittedAt<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        ChallengeKey<T>,
+        Blake2_128Concat,
+        T::AccountId,
+        BlockNumberFor<T>,
+    >;
+
+    /// Recent verifying key versions, oldest first. Versions no longer held are retired.
+    #[pallet::storage]
+    pub type ProofVerifyingKey<T: Config> = StorageValue<
+        _,
+        BoundedVec<VersionedVerifyingKey, T::MaxVerifyingKeyVersions>,
+        ValueQuery,
+    >;
+
+    /// Whether submissions are halted by an emergency stop
+    #[pallet::storage]
+    pub type Paused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Verifying key of the distance circuit
+    #[pallet::storage]
+    pub type DistanceVerifyingKey<T: Config> = StorageValue<_, RawVerifyingKey>;
+
+    /// Owners of hidden challenges, published as a commitment instead of a geohash
+    #[pallet::storage]
+    pub type CommittedChallenges<T: Config> =
+        StorageMap<_, Blake2_128Concat, CommittedChallengeKey<T>, T::AccountId>;
+
+    /// Accounts that proved their attendance of a hidden challenge
     #[pallet::storage]
-    pub type Submissions<T: Config> =
-        StorageDoubleMap<_, Blake2_128Concat, Challenge<T>, Blake2_128Concat, T::AccountId, bool>;
+    pub type CommittedSubmissions<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        CommittedChallengeKey<T>,
+        Blake2_128Concat,
+        T::AccountId,
+        bool,
+    >;
 
+    /// Verifying key of the committed prefix circuit
     #[pallet::storage]
-    pub type ProofVerifyingKey<T: Config> = StorageValue<_, RawVerifyingKey>;
+    pub type CommittedVerifyingKey<T: Config> = StorageValue<_, RawVerifyingKey>;
+
+    /// The most recent `MaxHistory` challenges each account submitted to, as namespace and
+    /// geohash, oldest first. Once full, the oldest entry is dropped for each new submission.
+    #[pallet::storage]
+    pub type AttendanceHistory<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<(Namespace<T>, Challenge<T>), T::MaxHistory>,
+        ValueQuery,
+    >;
+
+    #[pallet::type_value]
+    pub fn DefaultMaxPrecision<T: Config>() -> u32 {
+        T::MaxGeohashLength::get()
+    }
+
+    /// Shortest geohash accepted for challenges and signed locations, 0 if unrestricted
+    #[pallet::storage]
+    pub type MinPrecision<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Longest geohash accepted for challenges and signed locations, at most
+    /// `MaxGeohashLength` which bounds the encoding itself
+    #[pallet::storage]
+    pub type MaxPrecision<T: Config> = StorageValue<_, u32, ValueQuery, DefaultMaxPrecision<T>>;
+
+    /// Keys seeded by the chain spec, so a new chain accepts submissions without governance
+    /// calls. Both are optional; keys that `set_oracle_public_key` or `set_verifying_key`
+    /// would reject fail the genesis build.
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// The primary oracle's public key, a `PublicKeyOfOracle` in raw bytes
+        pub oracle_public_key: Option<sp_runtime::Vec<u8>>,
+        /// The prefix circuit's verifying key, registered as version 0
+        pub verifying_key: Option<sp_runtime::Vec<u8>>,
+        #[serde(skip)]
+        pub _config: PhantomData<T>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            if let Some(public_key) = &self.oracle_public_key {
+                assert!(
+                    T::PublicKeyOfOracle::from_slice(public_key).is_ok(),
+                    "genesis oracle public key is not a PublicKeyOfOracle"
+                );
+                let public_key: RawPublicKey = public_key
+                    .clone()
+                    .try_into()
+                    .expect("genesis oracle public key longer than RawPublicKey");
+                Oracle::<T>::put(public_key);
+            }
+            if let Some(verifying_key) = &self.verifying_key {
+                assert!(
+                    T::ProofVerifier::valid_verifying_key(verifying_key),
+                    "genesis verifying key is not a valid ProofVerifier key"
+                );
+                let verifying_key: RawVerifyingKey = verifying_key
+                    .clone()
+                    .try_into()
+                    .expect("genesis verifying key longer than RawVerifyingKey");
+                Pallet::<T>::rotate_verifying_key(verifying_key);
+            }
+        }
+    }
 
     /// Events that functions in this pallet can emit.
     ///
@@ -120,13 +532,94 @@ pub mod pallet {
     pub enum Event<T: Config> {
         ChallengeCreated {
             who: T::AccountId,
+            namespace: Namespace<T>,
             challenge: Challenge<T>,
         },
         SubmissionAccepted {
             who: T::AccountId,
+            namespace: Namespace<T>,
             challenge: Challenge<T>,
             signature: RawSignature,
         },
+        ChallengeCapacityReached {
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+        },
+        ChallengeClosed {
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+        },
+        ChallengePruned {
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+        },
+        ChallengeArchived {
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+        },
+        DistanceProofAccepted {
+            who: T::AccountId,
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+            center_latitude: u64,
+            center_longitude: u64,
+            radius: u64,
+        },
+        Paused,
+        Unpaused,
+        OracleAdded {
+            public_key: RawPublicKey,
+            label: Option<OracleLabel<T>>,
+        },
+        OracleRemoved {
+            public_key: RawPublicKey,
+        },
+        PrecisionUpdated {
+            min: u32,
+            max: u32,
+        },
+        MultisigSubmissionAccepted {
+            who: T::AccountId,
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+            attestations: u32,
+        },
+        VerifyingKeyUpdated {
+            version: u32,
+        },
+        CommittedChallengeCreated {
+            who: T::AccountId,
+            namespace: Namespace<T>,
+            commitment: RawCommitment,
+        },
+        CommittedChallengeClosed {
+            namespace: Namespace<T>,
+            commitment: RawCommitment,
+        },
+        CommittedProofAccepted {
+            who: T::AccountId,
+            namespace: Namespace<T>,
+            commitment: RawCommitment,
+            precision: u32,
+        },
+        AcceptedPrecisionsUpdated {
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+            precisions: AcceptedPrecisionsOf<T>,
+        },
+        AttendanceHistoryTruncated {
+            who: T::AccountId,
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+        },
+        ProofAccepted {
+            who: T::AccountId,
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+            precision: u32,
+        },
+        DistanceVerifyingKeyUpdated,
+        CommittedVerifyingKeyUpdated,
     }
 
     /// Errors that can be returned by this pallet.
@@ -138,133 +631,1239 @@ pub mod pallet {
         InvalidSignature,
         AlreadySubmitted,
         InvalidProof,
+        VerifyingKeyVersionMismatch,
+        ChallengeNotFound,
+        ChallengeFull,
+        InvalidExpiry,
+        ExpiryIndexFull,
+        NotChallengeOwner,
+        RadiusTooLarge,
+        DistanceVerifyingKeyNotSet,
+        PalletPaused,
+        TooManyOracles,
+        OracleAlreadyAdded,
+        OracleNotFound,
+        OracleLabelInUse,
+        GeohashTooLong,
+        UnsupportedPayloadVersion,
+        InvalidPrecisionPolicy,
+        PrecisionOutOfRange,
+        InsufficientAttestations,
+        InsufficientPrecision,
+        ChallengeBeingArchived,
+        ChallengeExpired,
+        ChallengeAlreadyExists,
+        InvalidCommitment,
+        CommittedVerifyingKeyNotSet,
+        InvalidCoordinates,
+        PrecisionNotAccepted,
+        KeyAlreadySet,
+        PublicInputCountMismatch,
+        VerifyingKeyNotSet,
+        InvalidVerifyingKey,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+            let expired = ChallengeExpiries::<T>::take(n);
+            for key in expired.iter() {
+                Challenges::<T>::remove(key);
+                SubmissionCount::<T>::remove(key);
+                AcceptedPrecisions::<T>::remove(key);
+                PendingArchival::<T>::insert(key, ());
+                let (namespace, challenge) = Self::challenge_of_key(key);
+                Self::deposit_event(Event::ChallengePruned {
+                    namespace,
+                    challenge,
+                });
+            }
+            // Removing a counted challenge reads it and updates the counter
+            let pruned = expired.len() as u64;
+            T::DbWeight::get()
+                .reads_writes(1 + 2 * pruned, 1 + 5 * pruned)
+                .saturating_add(Self::archive_submissions(T::MaxArchivedPerBlock::get()))
+        }
+
+        fn integrity_test() {
+            // `rotate_verifying_key` could not hold the version it returns
+            assert!(
+                T::MaxVerifyingKeyVersions::get() >= 1,
+                "MaxVerifyingKeyVersions must hold at least one verifying key"
+            );
+        }
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
+        /// Creates a challenge for the geohash `challenge` in `namespace`, the empty namespace
+        /// being the default one.
         #[pallet::call_index(0)]
         #[pallet::weight(0)]
-        pub fn create_challenge(origin: OriginFor<T>, challenge: Challenge<T>) -> DispatchResult {
+        pub fn create_challenge(
+            origin: OriginFor<T>,
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+            capacity: Option<u32>,
+            expires_at: Option<BlockNumberFor<T>>,
+        ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
             // Create a challenge
             ensure!(Self::valid_geohash(&challenge), Error::<T>::InvalidGeohash);
+            let key = Self::challenge_key(&namespace, &challenge);
             ensure!(
-                Challenges::<T>::contains_key(&challenge) == false,
-                Error::<T>::InvalidGeohash
+                !Challenges::<T>::contains_key(&key),
+                Error::<T>::ChallengeAlreadyExists
             );
+            ensure!(
+                !PendingArchival::<T>::contains_key(&key),
+                Error::<T>::ChallengeBeingArchived
+            );
+            ensure!(
+                Self::within_precision(challenge.len() as u32),
+                Error::<T>::PrecisionOutOfRange
+            );
+            if let Some(expires_at) = expires_at {
+                ensure!(
+                    expires_at >= frame_system::Pallet::<T>::block_number(),
+                    Error::<T>::InvalidExpiry
+                );
+                Self::schedule_expiry(&key, expires_at)?;
+            }
             // Store the validated geohash
-            Challenges::<T>::insert(challenge.clone(), true);
+            Challenges::<T>::insert(
+                key,
+                ChallengeInfo {
+                    owner: who.clone(),
+                    capacity,
+                    expires_at,
+                },
+            );
 
-            Self::deposit_event(Event::ChallengeCreated { who, challenge });
+            Self::deposit_event(Event::ChallengeCreated {
+                who,
+                namespace,
+                challenge,
+            });
             Ok(())
         }
 
         #[pallet::call_index(1)]
-        #[pallet::weight(0)]
+        #[pallet::weight(T::WeightInfo::submission_with_signature())]
         pub fn submission_with_signature(
             origin: OriginFor<T>,
+            namespace: Namespace<T>,
             challenge: Challenge<T>,
             location: Challenge<T>,
+            payload_version: u8,
             signature: RawSignature,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            Self::log_rejection("submission_with_signature", || {
+                let (info, key) = Self::validate_signature_submission(
+                    &who,
+                    &namespace,
+                    &challenge,
+                    &location,
+                    payload_version,
+                    &signature,
+                )?;
+
+                Self::record_submission(&key, &who);
+                Self::count_submission(&info, &namespace, &challenge);
+                Self::record_attendance(&who, &namespace, &challenge);
+                T::Mint::try_mint(&who, T::RewardAmount::get())?;
+
+                Self::deposit_event(Event::SubmissionAccepted {
+                    who,
+                    namespace,
+                    challenge,
+                    signature,
+                });
+
+                Ok(())
+            })
+        }
+
+        /// Sets the primary oracle's public key, which must be a `PublicKeyOfOracle`, so a
+        /// malformed key fails here with `InvalidPublicKey` instead of failing every later
+        /// signature check. A key already set is only replaced with `force`, and fails with
+        /// `KeyAlreadySet` otherwise.
+        #[pallet::call_index(2)]
+        #[pallet::weight(0)]
+        pub fn set_oracle_public_key(
+            origin: OriginFor<T>,
+            public_key: RawPublicKey,
+            force: bool,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                T::PublicKeyOfOracle::from_slice(&public_key).is_ok(),
+                Error::<T>::InvalidPublicKey
+            );
+            ensure!(force || !Oracle::<T>::exists(), Error::<T>::KeyAlreadySet);
+            Oracle::<T>::put(public_key);
+            Ok(())
+        }
+
+        /// Submits a proof that the attendee's location, a geohash of `precision` characters,
+        /// starts with the challenge. The precision is a public input of the proof, so it is
+        /// held to the same requirements as the location of a signed submission.
+        #[pallet::call_index(3)]
+        #[pallet::weight(T::WeightInfo::submission_with_proof())]
+        pub fn submission_with_proof(
+            origin: OriginFor<T>,
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+            vk_version: u32,
+            precision: u32,
+            proof: RawProof<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::log_rejection("submission_with_proof", || {
+                ensure!(!Paused::<T>::get(), Error::<T>::PalletPaused);
+                let info = Self::ensure_challenge_submittable(&namespace, &challenge)?;
+                let key = Self::challenge_key(&namespace, &challenge);
+                ensure!(
+                    !Self::already_submitted(&key, &who),
+                    Error::<T>::AlreadySubmitted
+                );
+                ensure!(
+                    Self::precise_enough(precision, &challenge),
+                    Error::<T>::InsufficientPrecision
+                );
+                ensure!(
+                    Self::within_precision(precision),
+                    Error::<T>::PrecisionOutOfRange
+                );
+                ensure!(
+                    Self::precision_accepted(&key, precision),
+                    Error::<T>::PrecisionNotAccepted
+                );
+                ensure!(
+                    ProofVerifyingKey::<T>::decode_len().unwrap_or(0) > 0,
+                    Error::<T>::VerifyingKeyNotSet
+                );
+                let verifying_key = Self::verifying_key(vk_version)
+                    .ok_or(Error::<T>::VerifyingKeyVersionMismatch)?;
+                Self::verify_zkp(&proof, &challenge, precision, &verifying_key)?;
+                Self::record_submission(&key, &who);
+                Self::count_submission(&info, &namespace, &challenge);
+                Self::record_attendance(&who, &namespace, &challenge);
+                T::Mint::try_mint(&who, T::RewardAmount::get())?;
+
+                Self::deposit_event(Event::ProofAccepted {
+                    who,
+                    namespace,
+                    challenge,
+                    precision,
+                });
+                Ok(())
+            })
+        }
+
+        #[pallet::call_index(4)]
+        #[pallet::weight(0)]
+        pub fn close_challenge(
+            origin: OriginFor<T>,
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let key = Self::challenge_key(&namespace, &challenge);
+            let info = Challenges::<T>::get(&key).ok_or(Error::<T>::ChallengeNotFound)?;
+            ensure!(info.owner == who, Error::<T>::NotChallengeOwner);
+
+            if let Some(expires_at) = info.expires_at {
+                Self::unschedule_expiry(&key, expires_at);
+            }
+            Challenges::<T>::remove(&key);
+            SubmissionCount::<T>::remove(&key);
+            AcceptedPrecisions::<T>::remove(&key);
+            PendingArchival::<T>::insert(&key, ());
+
+            Self::deposit_event(Event::ChallengeClosed {
+                namespace,
+                challenge,
+            });
+            Ok(())
+        }
+
+        /// Sets the distance circuit's verifying key. A key already set is only replaced with
+        /// `force`, and fails with `KeyAlreadySet` otherwise. Keys the `ProofVerifier` cannot
+        /// read are rejected with `InvalidVerifyingKey`.
+        #[pallet::call_index(5)]
+        #[pallet::weight(0)]
+        pub fn set_distance_verifying_key(
+            origin: OriginFor<T>,
+            verifying_key: RawVerifyingKey,
+            force: bool,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
             ensure!(
-                !Submissions::<T>::contains_key(&challenge, &who),
-                Error::<T>::AlreadySubmitted
+                force || !DistanceVerifyingKey::<T>::exists(),
+                Error::<T>::KeyAlreadySet
             );
             ensure!(
-                Self::geohash_in_geohash(&location, &challenge),
-                Error::<T>::InvalidGeohash
+                T::ProofVerifier::valid_verifying_key(&verifying_key),
+                Error::<T>::InvalidVerifyingKey
             );
+            DistanceVerifyingKey::<T>::put(verifying_key);
+            Self::deposit_event(Event::DistanceVerifyingKeyUpdated);
+            Ok(())
+        }
 
-            let message = T::PayloadHasher::hash(&location);
-            let public_key = Oracle::<T>::get().expect("oracle key");
-            let public_key = T::PublicKeyOfOracle::from_slice(&public_key)
-                .map_err(|_| Error::<T>::InvalidPublicKey)?;
+        /// Submits a proof that the attendee is within `radius` of the center, without
+        /// revealing their coordinates. Coordinates and radius are fixed-point units as
+        /// produced by the prover's `distance::to_fixed_point`; a center outside the
+        /// `geohash_inputs::coordinates` encoding, such as a negative coordinate cast to
+        /// `u64`, fails with `InvalidCoordinates`.
+        #[pallet::call_index(6)]
+        #[pallet::weight(0)]
+        pub fn submission_with_distance_proof(
+            origin: OriginFor<T>,
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+            center_latitude: u64,
+            center_longitude: u64,
+            radius: u64,
+            proof: RawProof<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::log_rejection("submission_with_distance_proof", || {
+                ensure!(!Paused::<T>::get(), Error::<T>::PalletPaused);
+                let info = Self::ensure_challenge_submittable(&namespace, &challenge)?;
+                let key = Self::challenge_key(&namespace, &challenge);
+                ensure!(
+                    !Self::already_submitted(&key, &who),
+                    Error::<T>::AlreadySubmitted
+                );
+                ensure!(
+                    geohash_inputs::coordinates::decode((center_latitude, center_longitude))
+                        .is_some(),
+                    Error::<T>::InvalidCoordinates
+                );
+                ensure!(
+                    radius <= T::MaxDistanceRadius::get(),
+                    Error::<T>::RadiusTooLarge
+                );
+                let verifying_key = DistanceVerifyingKey::<T>::get()
+                    .ok_or(Error::<T>::DistanceVerifyingKeyNotSet)?;
+                Self::verify_distance_zkp(
+                    &proof,
+                    [center_latitude, center_longitude, radius],
+                    &verifying_key,
+                )?;
 
+                Self::record_submission(&key, &who);
+                Self::count_submission(&info, &namespace, &challenge);
+                Self::record_attendance(&who, &namespace, &challenge);
+                T::Mint::try_mint(&who, T::RewardAmount::get())?;
+
+                Self::deposit_event(Event::DistanceProofAccepted {
+                    who,
+                    namespace,
+                    challenge,
+                    center_latitude,
+                    center_longitude,
+                    radius,
+                });
+                Ok(())
+            })
+        }
+
+        /// Halts all submissions until `unpause` is called
+        #[pallet::call_index(7)]
+        #[pallet::weight(0)]
+        pub fn pause(origin: OriginFor<T>) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            Paused::<T>::put(true);
+            Self::deposit_event(Event::Paused);
+            Ok(())
+        }
+
+        /// Resumes the submissions halted by `pause`
+        #[pallet::call_index(8)]
+        #[pallet::weight(0)]
+        pub fn unpause(origin: OriginFor<T>) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            Paused::<T>::put(false);
+            Self::deposit_event(Event::Unpaused);
+            Ok(())
+        }
+
+        /// Trusts `public_key` in addition to the primary oracle, optionally naming it with a
+        /// `label` that no other oracle has.
+        #[pallet::call_index(9)]
+        #[pallet::weight(0)]
+        pub fn add_oracle(
+            origin: OriginFor<T>,
+            public_key: RawPublicKey,
+            label: Option<OracleLabel<T>>,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
             ensure!(
-                T::Verify::verify(
-                    &T::Signature::from_slice(&signature)
-                        .map_err(|_| Error::<T>::InvalidSignature)?,
-                    message,
-                    &public_key
-                ),
-                Error::<T>::InvalidSignature
+                T::PublicKeyOfOracle::from_slice(&public_key).is_ok(),
+                Error::<T>::InvalidPublicKey
+            );
+            ensure!(
+                !Oracles::<T>::contains_key(&public_key),
+                Error::<T>::OracleAlreadyAdded
+            );
+            ensure!(
+                Oracles::<T>::count() < T::MaxOracles::get(),
+                Error::<T>::TooManyOracles
+            );
+            if let Some(label) = &label {
+                ensure!(
+                    Self::oracle_by_label(label).is_none(),
+                    Error::<T>::OracleLabelInUse
+                );
+            }
+            Oracles::<T>::insert(public_key.clone(), label.clone());
+            Self::deposit_event(Event::OracleAdded { public_key, label });
+            Ok(())
+        }
+
+        #[pallet::call_index(10)]
+        #[pallet::weight(0)]
+        pub fn remove_oracle(origin: OriginFor<T>, public_key: RawPublicKey) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                Oracles::<T>::contains_key(&public_key),
+                Error::<T>::OracleNotFound
+            );
+            Oracles::<T>::remove(&public_key);
+            Self::deposit_event(Event::OracleRemoved { public_key });
+            Ok(())
+        }
+
+        /// Sets the range of geohash lengths accepted for new challenges and signed
+        /// locations. Existing challenges outside the range are kept, but signed submissions
+        /// to them are rejected while it applies.
+        #[pallet::call_index(11)]
+        #[pallet::weight(0)]
+        pub fn set_precision(origin: OriginFor<T>, min: u32, max: u32) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                min <= max && max <= T::MaxGeohashLength::get(),
+                Error::<T>::InvalidPrecisionPolicy
+            );
+            MinPrecision::<T>::put(min);
+            MaxPrecision::<T>::put(max);
+            Self::deposit_event(Event::PrecisionUpdated { min, max });
+            Ok(())
+        }
+
+        /// Submits a location attested by several oracles. Each signature is over the same
+        /// payload as `submission_with_signature`; at least `AttestationThreshold` distinct
+        /// trusted oracles must have signed it. Signatures by untrusted keys, repeated keys
+        /// and invalid signatures are not counted.
+        #[pallet::call_index(12)]
+        #[pallet::weight(0)]
+        pub fn submission_with_multisig(
+            origin: OriginFor<T>,
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+            location: Challenge<T>,
+            payload_version: u8,
+            signatures: OracleSignatures<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::log_rejection("submission_with_multisig", || {
+                ensure!(!Paused::<T>::get(), Error::<T>::PalletPaused);
+                ensure!(
+                    payload_version == PAYLOAD_VERSION,
+                    Error::<T>::UnsupportedPayloadVersion
+                );
+                let info = Self::ensure_challenge_submittable(&namespace, &challenge)?;
+                let key = Self::challenge_key(&namespace, &challenge);
+                ensure!(
+                    !Self::already_submitted(&key, &who),
+                    Error::<T>::AlreadySubmitted
+                );
+                ensure!(
+                    Self::geohash_in_geohash(&location, &challenge),
+                    Error::<T>::InvalidGeohash
+                );
+                ensure!(
+                    Self::within_precision(location.len() as u32),
+                    Error::<T>::PrecisionOutOfRange
+                );
+                ensure!(
+                    Self::precision_accepted(&key, location.len() as u32),
+                    Error::<T>::PrecisionNotAccepted
+                );
+
+                let message = T::PayloadHasher::hash(&Self::signing_payload(&challenge, &location));
+                let attestations = Self::count_attestations(&signatures, &message);
+                ensure!(
+                    attestations >= T::AttestationThreshold::get().max(1),
+                    Error::<T>::InsufficientAttestations
+                );
+
+                Self::record_submission(&key, &who);
+                Self::count_submission(&info, &namespace, &challenge);
+                Self::record_attendance(&who, &namespace, &challenge);
+                T::Mint::try_mint(&who, T::RewardAmount::get())?;
+
+                Self::deposit_event(Event::MultisigSubmissionAccepted {
+                    who,
+                    namespace,
+                    challenge,
+                    attestations,
+                });
+                Ok(())
+            })
+        }
+
+        /// Registers `verifying_key` as the newest version of the prefix circuit's verifying
+        /// key, retiring the oldest version once `MaxVerifyingKeyVersions` are held. Keys the
+        /// `ProofVerifier` cannot read are rejected with `InvalidVerifyingKey`.
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::WeightInfo::set_verifying_key(verifying_key.len() as u32))]
+        pub fn set_verifying_key(
+            origin: OriginFor<T>,
+            verifying_key: RawVerifyingKey,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                T::ProofVerifier::valid_verifying_key(&verifying_key),
+                Error::<T>::InvalidVerifyingKey
             );
+            let version = Self::rotate_verifying_key(verifying_key);
+            Self::deposit_event(Event::VerifyingKeyUpdated { version });
+            Ok(())
+        }
+
+        /// Creates a challenge like `create_challenge`, but succeeds without change if the
+        /// caller already created it with the same capacity and expiry, so clients can retry
+        /// a creation whose outcome they did not see. A challenge created by someone else or
+        /// with other parameters fails with `ChallengeAlreadyExists`.
+        #[pallet::call_index(14)]
+        #[pallet::weight(0)]
+        pub fn create_challenge_idempotent(
+            origin: OriginFor<T>,
+            namespace: Namespace<T>,
+            challenge: Challenge<T>,
+            capacity: Option<u32>,
+            expires_at: Option<BlockNumberFor<T>>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin.clone())?;
+            if let Some(info) = Challenges::<T>::get(Self::challenge_key(&namespace, &challenge)) {
+                ensure!(
+                    info.owner == who && info.capacity == capacity && info.expires_at == expires_at,
+                    Error::<T>::ChallengeAlreadyExists
+                );
+                return Ok(());
+            }
+            Self::create_challenge(origin, namespace, challenge, capacity, expires_at)
+        }
 
-            T::Mint::mint(&who);
-            Submissions::<T>::insert(challenge.clone(), who.clone(), true);
+        /// Sets the committed prefix circuit's verifying key. A key already set is only
+        /// replaced with `force`, and fails with `KeyAlreadySet` otherwise. Keys the
+        /// `ProofVerifier` cannot read are rejected with `InvalidVerifyingKey`.
+        #[pallet::call_index(15)]
+        #[pallet::weight(0)]
+        pub fn set_committed_verifying_key(
+            origin: OriginFor<T>,
+            verifying_key: RawVerifyingKey,
+            force: bool,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                force || !CommittedVerifyingKey::<T>::exists(),
+                Error::<T>::KeyAlreadySet
+            );
+            ensure!(
+                T::ProofVerifier::valid_verifying_key(&verifying_key),
+                Error::<T>::InvalidVerifyingKey
+            );
+            CommittedVerifyingKey::<T>::put(verifying_key);
+            Self::deposit_event(Event::CommittedVerifyingKeyUpdated);
+            Ok(())
+        }
 
-            Self::deposit_event(Event::SubmissionAccepted {
+        /// Creates a hidden challenge in `namespace`, published as a commitment to its
+        /// geohash and a secret salt. Only attendees given both can prove their attendance.
+        #[pallet::call_index(16)]
+        #[pallet::weight(0)]
+        pub fn create_committed_challenge(
+            origin: OriginFor<T>,
+            namespace: Namespace<T>,
+            commitment: RawCommitment,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                T::ProofVerifier::valid_public_input(&commitment),
+                Error::<T>::InvalidCommitment
+            );
+            let key = (namespace.clone(), commitment);
+            ensure!(
+                !CommittedChallenges::<T>::contains_key(&key),
+                Error::<T>::ChallengeAlreadyExists
+            );
+            CommittedChallenges::<T>::insert(key, who.clone());
+
+            Self::deposit_event(Event::CommittedChallengeCreated {
                 who,
-                challenge,
-                signature,
+                namespace,
+                commitment,
             });
-
             Ok(())
         }
 
-        #[pallet::call_index(2)]
+        #[pallet::call_index(17)]
         #[pallet::weight(0)]
-        pub fn set_oracle_public_key(
+        pub fn close_committed_challenge(
             origin: OriginFor<T>,
-            public_key: RawPublicKey,
+            namespace: Namespace<T>,
+            commitment: RawCommitment,
         ) -> DispatchResult {
-            ensure_root(origin)?;
-            Oracle::<T>::put(public_key);
+            let who = ensure_signed(origin)?;
+            let key = (namespace.clone(), commitment);
+            let owner = CommittedChallenges::<T>::get(&key).ok_or(Error::<T>::ChallengeNotFound)?;
+            ensure!(owner == who, Error::<T>::NotChallengeOwner);
+            CommittedChallenges::<T>::remove(&key);
+
+            Self::deposit_event(Event::CommittedChallengeClosed {
+                namespace,
+                commitment,
+            });
             Ok(())
         }
 
-        #[pallet::call_index(3)]
+        /// Submits a proof that the attendee's location, a geohash of `precision` characters,
+        /// starts with the challenge behind `commitment`, revealing neither of them.
+        #[pallet::call_index(18)]
         #[pallet::weight(0)]
-        pub fn submission_with_proof(
+        pub fn submission_with_committed_proof(
             origin: OriginFor<T>,
+            namespace: Namespace<T>,
+            commitment: RawCommitment,
+            precision: u32,
+            proof: RawProof<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::log_rejection("submission_with_committed_proof", || {
+                ensure!(!Paused::<T>::get(), Error::<T>::PalletPaused);
+                let key = (namespace.clone(), commitment);
+                ensure!(
+                    CommittedChallenges::<T>::contains_key(&key),
+                    Error::<T>::ChallengeNotFound
+                );
+                ensure!(
+                    !CommittedSubmissions::<T>::contains_key(&key, &who),
+                    Error::<T>::AlreadySubmitted
+                );
+                ensure!(
+                    Self::within_precision(precision),
+                    Error::<T>::PrecisionOutOfRange
+                );
+                let verifying_key = CommittedVerifyingKey::<T>::get()
+                    .ok_or(Error::<T>::CommittedVerifyingKeyNotSet)?;
+                Self::verify_committed_zkp(&proof, &commitment, precision, &verifying_key)?;
+
+                CommittedSubmissions::<T>::insert(key, who.clone(), true);
+                T::Mint::try_mint(&who, T::RewardAmount::get())?;
+
+                Self::deposit_event(Event::CommittedProofAccepted {
+                    who,
+                    namespace,
+                    commitment,
+                    precision,
+                });
+                Ok(())
+            })
+        }
+
+        /// Restricts the location precisions `challenge` accepts to `precisions`, e.g. only
+        /// geohashes of exactly 7 or 8 characters, on top of the precision policy. Only the
+        /// challenge's owner may set them; an empty list lifts the restriction. Submissions of
+        /// other precisions fail with `PrecisionNotAccepted`.
+        #[pallet::call_index(19)]
+        #[pallet::weight(0)]
+        pub fn set_accepted_precisions(
+            origin: OriginFor<T>,
+            namespace: Namespace<T>,
             challenge: Challenge<T>,
-            proof: RawProof,
+            mut precisions: AcceptedPrecisionsOf<T>,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            let key = Self::challenge_key(&namespace, &challenge);
+            let info = Challenges::<T>::get(&key).ok_or(Error::<T>::ChallengeNotFound)?;
+            ensure!(info.owner == who, Error::<T>::NotChallengeOwner);
+            let max = T::MaxGeohashLength::get();
             ensure!(
-                Self::verify_zkp(&proof, &challenge),
-                Error::<T>::InvalidProof
+                precisions.iter().all(|p| *p >= 1 && *p as u32 <= max),
+                Error::<T>::InvalidPrecisionPolicy
             );
-            T::Mint::mint(&who);
-            Submissions::<T>::insert(challenge.clone(), who.clone(), true);
 
+            precisions.sort_unstable();
+            precisions.dedup();
+            if precisions.is_empty() {
+                AcceptedPrecisions::<T>::remove(&key);
+            } else {
+                AcceptedPrecisions::<T>::insert(&key, &precisions);
+            }
+            Self::deposit_event(Event::AcceptedPrecisionsUpdated {
+                namespace,
+                challenge,
+                precisions,
+            });
             Ok(())
         }
     }
 
     use ark_bn254::Bn254;
-    use ark_bn254::Fr;
+    use ark_bn254::{Fr, G1Affine, G2Affine};
     use ark_groth16::Groth16;
     use ark_groth16::{Proof, VerifyingKey};
-    use ark_serialize::CanonicalDeserialize;
+    use ark_serialize::{CanonicalDeserialize, Compress, SerializationError, Validate};
     use ark_snark::SNARK;
 
     impl<T: Config> Pallet<T> {
         pub fn valid_geohash(geohash: &Challenge<T>) -> bool {
-            geohash
-                .iter()
-                .all(|c| "0123456789bcdefghjkmnpqrstuvwxyz".contains(*c as char))
+            geohash_inputs::is_geohash(geohash)
+        }
+
+        /// Whether a geohash length is within the precision policy
+        fn within_precision(precision: u32) -> bool {
+            MinPrecision::<T>::get() <= precision && precision <= MaxPrecision::<T>::get()
+        }
+
+        /// Whether the challenge stored under `key` accepts locations of `precision`
+        /// characters, which it does unless its owner restricted them with
+        /// `set_accepted_precisions`
+        fn precision_accepted(key: &ChallengeKey<T>, precision: u32) -> bool {
+            AcceptedPrecisions::<T>::get(key).map_or(true, |accepted| {
+                u8::try_from(precision).map_or(false, |precision| accepted.contains(&precision))
+            })
+        }
+
+        /// Converts geohash bytes into a challenge, failing with `GeohashTooLong` rather than
+        /// truncating when they exceed `MaxGeohashLength`. Clients building extrinsics should
+        /// convert through this to get a clear error instead of an opaque decoding failure.
+        pub fn bounded_geohash(geohash: sp_runtime::Vec<u8>) -> Result<Challenge<T>, Error<T>> {
+            Challenge::<T>::try_from(geohash).map_err(|_| Error::<T>::GeohashTooLong)
+        }
+
+        /// Parses a geohash, such as `"gcpvj0"`, into a challenge or location for a call,
+        /// failing with `InvalidGeohash` for an empty string or a character outside the
+        /// geohash alphabet, and with `GeohashTooLong` beyond `MaxGeohashLength`.
+        pub fn geohash_from_str(geohash: &str) -> Result<Challenge<T>, Error<T>> {
+            ensure!(
+                !geohash.is_empty() && geohash_inputs::is_geohash(geohash.as_bytes()),
+                Error::<T>::InvalidGeohash
+            );
+            Self::bounded_geohash(geohash.as_bytes().to_vec())
+        }
+
+        /// The payload signed by the oracle, bound to the challenge:
+        /// `PAYLOAD_VERSION || DomainTag || len(challenge) as u32 LE || challenge || location`
+        ///
+        /// The length prefix keeps the boundary between challenge and location unambiguous,
+        /// so a signature for one challenge cannot verify against another.
+        pub fn signing_payload(challenge: &[u8], location: &[u8]) -> sp_runtime::Vec<u8> {
+            let tag = T::DomainTag::get();
+            let mut payload = sp_runtime::Vec::with_capacity(
+                1 + tag.len() + 4 + challenge.len() + location.len(),
+            );
+            payload.push(PAYLOAD_VERSION);
+            payload.extend_from_slice(tag);
+            payload.extend_from_slice(&(challenge.len() as u32).to_le_bytes());
+            payload.extend_from_slice(challenge);
+            payload.extend_from_slice(location);
+            payload
+        }
+
+        /// Adds the challenge stored under `key` to the expiry index for the block after
+        /// `expires_at`, spilling to following blocks when that block already holds
+        /// `MaxExpiringPerBlock` challenges.
+        fn schedule_expiry(key: &ChallengeKey<T>, expires_at: BlockNumberFor<T>) -> DispatchResult {
+            let mut block = expires_at.saturating_add(One::one());
+            for _ in 0..MAX_EXPIRY_SPILL_BLOCKS {
+                if ChallengeExpiries::<T>::try_mutate(block, |keys| keys.try_push(key.clone()))
+                    .is_ok()
+                {
+                    return Ok(());
+                }
+                block = block.saturating_add(One::one());
+            }
+            Err(Error::<T>::ExpiryIndexFull.into())
+        }
+
+        /// Removes the challenge stored under `key` from the expiry index, searching the blocks
+        /// it may have spilled to.
+        fn unschedule_expiry(key: &ChallengeKey<T>, expires_at: BlockNumberFor<T>) {
+            let mut block = expires_at.saturating_add(One::one());
+            for _ in 0..MAX_EXPIRY_SPILL_BLOCKS {
+                let mut keys = ChallengeExpiries::<T>::get(block);
+                if let Some(index) = keys.iter().position(|k| k == key) {
+                    keys.remove(index);
+                    if keys.is_empty() {
+                        ChallengeExpiries::<T>::remove(block);
+                    } else {
+                        ChallengeExpiries::<T>::insert(block, keys);
+                    }
+                    return;
+                }
+                block = block.saturating_add(One::one());
+            }
+        }
+
+        /// The key the challenge for `challenge` in `namespace` is stored under: the namespace
+        /// and the geohash itself, or with `PackedChallengeKeys` the geohash packed at 5 bits
+        /// per character, which saves a third of the geohash's bytes in every map keyed by
+        /// challenge. A challenge that is not a geohash cannot be packed and gets the empty
+        /// geohash, which no packed geohash has, so it is never found.
+        pub fn challenge_key(
+            namespace: &Namespace<T>,
+            challenge: &Challenge<T>,
+        ) -> ChallengeKey<T> {
+            if !T::PackedChallengeKeys::get() {
+                return (namespace.clone(), challenge.clone());
+            }
+            let packed = geohash_inputs::packed::pack(challenge)
+                .and_then(|key| Challenge::<T>::try_from(key).ok())
+                .unwrap_or_default();
+            (namespace.clone(), packed)
+        }
+
+        /// The namespace and geohash of the challenge stored under `key`, the inverse of
+        /// [`Self::challenge_key`]
+        pub fn challenge_of_key(
+            (namespace, key): &ChallengeKey<T>,
+        ) -> (Namespace<T>, Challenge<T>) {
+            if !T::PackedChallengeKeys::get() {
+                return (namespace.clone(), key.clone());
+            }
+            let challenge = geohash_inputs::packed::unpack(key)
+                .and_then(|challenge| Challenge::<T>::try_from(challenge).ok())
+                .unwrap_or_default();
+            (namespace.clone(), challenge)
+        }
+
+        /// Clears at most `limit` submission entries of pruned challenges, removing each
+        /// challenge from its attendees' histories. A challenge is archived once none of its
+        /// entries remain; the rest are cleared in the following blocks.
+        fn archive_submissions(limit: u32) -> Weight {
+            let mut remaining = limit;
+            let (mut reads, mut writes) = (0u64, 0u64);
+            loop {
+                reads += 1;
+                let Some(key) = PendingArchival::<T>::iter_keys().next() else {
+                    break;
+                };
+                let (namespace, challenge) = Self::challenge_of_key(&key);
+                let attendees = Submissions::<T>::iter_key_prefix(&key)
+                    .take(remaining as usize)
+                    .collect::<sp_runtime::Vec<_>>();
+                let cleared = attendees.len() as u32;
+                for who in attendees {
+                    Submissions::<T>::remove(&key, &who);
+                    SubmittedAt::<T>::remove(&key, &who);
+                    AttendanceHistory::<T>::mutate(&who, |history| {
+                        history.retain(|(n, c)| n != &namespace || c != &challenge)
+                    });
+                }
+                reads += 2 * cleared as u64;
+                writes += 3 * cleared as u64;
+
+                if cleared == remaining {
+                    // The limit is used up, but the challenge is archived if nothing is left
+                    reads += 1;
+                    if Submissions::<T>::iter_key_prefix(&key).next().is_some() {
+                        break;
+                    }
+                }
+                remaining -= cleared;
+                PendingArchival::<T>::remove(&key);
+                writes += 1;
+                Self::deposit_event(Event::ChallengeArchived {
+                    namespace,
+                    challenge,
+                });
+                if remaining == 0 {
+                    break;
+                }
+            }
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+
+        /// Whether `signature` over `message` was made by the primary oracle or one of the
+        /// added oracles. At most `MaxOracles + 1` keys are tried.
+        fn signed_by_oracle(
+            signature: &T::Signature,
+            message: &T::Hash,
+        ) -> Result<bool, DispatchError> {
+            if let Some(public_key) = Oracle::<T>::get() {
+                let public_key = T::PublicKeyOfOracle::from_slice(&public_key)
+                    .map_err(|_| Error::<T>::InvalidPublicKey)?;
+                if T::Verify::verify(signature, message, &public_key) {
+                    return Ok(true);
+                }
+            }
+            Ok(Oracles::<T>::iter_keys().any(|public_key| {
+                T::PublicKeyOfOracle::from_slice(&public_key)
+                    .map_or(false, |public_key| T::Verify::verify(signature, message, &public_key))
+            }))
+        }
+
+        /// Whether `public_key` is the primary oracle or one of the added oracles
+        fn is_oracle(public_key: &RawPublicKey) -> bool {
+            Oracle::<T>::get().as_ref() == Some(public_key) || Oracles::<T>::contains_key(public_key)
+        }
+
+        /// Number of distinct trusted oracles with a valid signature over `message`
+        fn count_attestations(signatures: &OracleSignatures<T>, message: &T::Hash) -> u32 {
+            let mut attested = sp_runtime::Vec::<&RawPublicKey>::new();
+            for (public_key, signature) in signatures.iter() {
+                if attested.contains(&public_key) || !Self::is_oracle(public_key) {
+                    continue;
+                }
+                let (Ok(parsed_key), Ok(parsed_signature)) = (
+                    T::PublicKeyOfOracle::from_slice(public_key),
+                    T::Signature::from_slice(signature),
+                ) else {
+                    continue;
+                };
+                if T::Verify::verify(&parsed_signature, message, &parsed_key) {
+                    attested.push(public_key);
+                }
+            }
+            attested.len() as u32
+        }
+
+        /// Runs every check of `submission_with_signature` without changing any state,
+        /// returning the challenge and its storage key for the submission to record. The runtime
+        /// API's `would_accept_signature` shares it, so a dry run cannot accept a signature the
+        /// call would reject.
+        fn validate_signature_submission(
+            who: &T::AccountId,
+            namespace: &Namespace<T>,
+            challenge: &Challenge<T>,
+            location: &Challenge<T>,
+            payload_version: u8,
+            signature: &RawSignature,
+        ) -> Result<(ChallengeInfoOf<T>, ChallengeKey<T>), DispatchError> {
+            ensure!(!Paused::<T>::get(), Error::<T>::PalletPaused);
+            ensure!(
+                payload_version == PAYLOAD_VERSION,
+                Error::<T>::UnsupportedPayloadVersion
+            );
+            let info = Self::ensure_challenge_submittable(namespace, challenge)?;
+            let key = Self::challenge_key(namespace, challenge);
+            ensure!(
+                !Self::already_submitted(&key, who),
+                Error::<T>::AlreadySubmitted
+            );
+            ensure!(
+                Self::geohash_in_geohash(location, challenge),
+                Error::<T>::InvalidGeohash
+            );
+            ensure!(
+                Self::within_precision(location.len() as u32),
+                Error::<T>::PrecisionOutOfRange
+            );
+            ensure!(
+                Self::precision_accepted(&key, location.len() as u32),
+                Error::<T>::PrecisionNotAccepted
+            );
+
+            let message = T::PayloadHasher::hash(&Self::signing_payload(challenge, location));
+            let parsed_signature =
+                T::Signature::from_slice(signature).map_err(|_| Error::<T>::InvalidSignature)?;
+            ensure!(
+                Self::signed_by_oracle(&parsed_signature, &message)?,
+                Error::<T>::InvalidSignature
+            );
+            Ok((info, key))
+        }
+
+        /// Loads the challenge for `challenge` in `namespace` if it accepts submissions: it
+        /// exists, has not expired and has capacity left. Every submission call starts with it,
+        /// so their preconditions on the challenge cannot drift apart.
+        pub fn ensure_challenge_submittable(
+            namespace: &Namespace<T>,
+            challenge: &Challenge<T>,
+        ) -> Result<ChallengeInfoOf<T>, Error<T>> {
+            let key = Self::challenge_key(namespace, challenge);
+            let info = Challenges::<T>::get(&key).ok_or(Error::<T>::ChallengeNotFound)?;
+            // A challenge spilled to a later block of the expiry index outlives its expiry
+            // until pruned, but no longer accepts submissions
+            if let Some(expires_at) = info.expires_at {
+                ensure!(
+                    frame_system::Pallet::<T>::block_number() <= expires_at,
+                    Error::<T>::ChallengeExpired
+                );
+            }
+            ensure!(Self::has_capacity(&info, &key), Error::<T>::ChallengeFull);
+            Ok(info)
+        }
+
+        fn has_capacity(info: &ChallengeInfoOf<T>, key: &ChallengeKey<T>) -> bool {
+            info.capacity
+                .map_or(true, |capacity| SubmissionCount::<T>::get(key) < capacity)
+        }
+
+        /// Counts an accepted submission, signalling when it filled the last slot
+        fn count_submission(
+            info: &ChallengeInfoOf<T>,
+            namespace: &Namespace<T>,
+            challenge: &Challenge<T>,
+        ) {
+            let key = Self::challenge_key(namespace, challenge);
+            let count = SubmissionCount::<T>::mutate(key, |count| {
+                *count = count.saturating_add(1);
+                *count
+            });
+            if info.capacity == Some(count) {
+                Self::deposit_event(Event::ChallengeCapacityReached {
+                    namespace: namespace.clone(),
+                    challenge: challenge.clone(),
+                });
+            }
+        }
+
+        /// Whether `who` attended the challenge stored under `key` with a submission that has
+        /// not expired, i.e. one made less than `SubmissionTtl` blocks ago, if it is nonzero
+        fn already_submitted(key: &ChallengeKey<T>, who: &T::AccountId) -> bool {
+            if !Submissions::<T>::contains_key(key, who) {
+                return false;
+            }
+            let ttl = T::SubmissionTtl::get();
+            if ttl.is_zero() {
+                return true;
+            }
+            // Entries from before a TTL was configured have no block and never expire
+            SubmittedAt::<T>::get(key, who).map_or(true, |submitted_at| {
+                frame_system::Pallet::<T>::block_number() < submitted_at.saturating_add(ttl)
+            })
+        }
+
+        /// Records the submission of `who` to the challenge stored under `key`, replacing an
+        /// expired one
+        fn record_submission(key: &ChallengeKey<T>, who: &T::AccountId) {
+            Submissions::<T>::insert(key, who, true);
+            if !T::SubmissionTtl::get().is_zero() {
+                SubmittedAt::<T>::insert(key, who, frame_system::Pallet::<T>::block_number());
+            }
+        }
+
+        /// Appends the challenge to the attendance history of `who`, dropping the oldest entry
+        /// when the history is full and emitting `AttendanceHistoryTruncated` with it
+        fn record_attendance(
+            who: &T::AccountId,
+            namespace: &Namespace<T>,
+            challenge: &Challenge<T>,
+        ) {
+            if T::MaxHistory::get() == 0 {
+                return;
+            }
+            let dropped = AttendanceHistory::<T>::mutate(who, |history| {
+                let dropped = history.is_full().then(|| history.remove(0));
+                let _ = history.try_push((namespace.clone(), challenge.clone()));
+                dropped
+            });
+            if let Some((namespace, challenge)) = dropped {
+                Self::deposit_event(Event::AttendanceHistoryTruncated {
+                    who: who.clone(),
+                    namespace,
+                    challenge,
+                });
+            }
         }
 
-        fn geohash_in_geohash(geohash: &Challenge<T>, challenge: &Challenge<T>) -> bool {
-            geohash.starts_with(challenge)
+        /// The challenges `who` submitted to as raw namespace and geohash bytes, oldest first,
+        /// as served by the runtime API
+        pub fn attendance_history(
+            who: &T::AccountId,
+        ) -> sp_runtime::Vec<(sp_runtime::Vec<u8>, sp_runtime::Vec<u8>)> {
+            AttendanceHistory::<T>::get(who)
+                .into_iter()
+                .map(|(namespace, challenge)| (namespace.into_inner(), challenge.into_inner()))
+                .collect()
         }
 
-        fn verify_zkp(proof: &RawProof, challenge: &Challenge<T>) -> bool {
-            let proof = Proof::<Bn254>::deserialize_uncompressed(proof.as_slice()).expect("proof");
-            let verifying_key_bytes = ProofVerifyingKey::<T>::get().expect("verifying key");
+        /// The cells around `challenge` at its precision, clockwise from the north, as served
+        /// by the runtime API. Empty if `challenge` is not a geohash.
+        pub fn geohash_neighbors(challenge: &[u8]) -> sp_runtime::Vec<sp_runtime::Vec<u8>> {
+            geohash_inputs::neighbors::neighbors(challenge)
+        }
 
-            let verifying_key =
-                VerifyingKey::deserialize_uncompressed(verifying_key_bytes.as_slice())
-                    .expect("verifying key");
+        /// The number of registered challenges across all namespaces, as served by the runtime
+        /// API. Challenges being archived are no longer counted.
+        pub fn challenge_count() -> u32 {
+            Challenges::<T>::count()
+        }
 
-            let public_input: sp_runtime::Vec<Fr> =
-                challenge.iter().map(|c| (*c as u64).into()).collect();
+        /// The public key of the added oracle labelled `label`, as served by the runtime API
+        pub fn oracle_by_label(label: &[u8]) -> Option<RawPublicKey> {
+            Oracles::<T>::iter()
+                .find(|(_, l)| l.as_deref() == Some(label))
+                .map(|(public_key, _)| public_key)
+        }
 
-            Groth16::<Bn254>::verify(&verifying_key, &public_input, &proof).expect("verified")
+        /// Whether `who` submitting `signature` of `location` for `challenge` in `namespace`
+        /// would be accepted now, as served by the runtime API. It runs the checks of
+        /// `submission_with_signature` for the current `PAYLOAD_VERSION` without recording or
+        /// minting anything, and fails with the error the call would fail with. Bytes too
+        /// long to submit fail as the call's arguments would: a namespace with
+        /// `ChallengeNotFound`, a geohash with `GeohashTooLong` and a signature with
+        /// `InvalidSignature`.
+        pub fn would_accept_signature(
+            who: &T::AccountId,
+            namespace: &[u8],
+            challenge: &[u8],
+            location: &[u8],
+            signature: &[u8],
+        ) -> DispatchResult {
+            let namespace = Namespace::<T>::try_from(namespace.to_vec())
+                .map_err(|_| Error::<T>::ChallengeNotFound)?;
+            let challenge = Self::bounded_geohash(challenge.to_vec())?;
+            let location = Self::bounded_geohash(location.to_vec())?;
+            let signature = RawSignature::try_from(signature.to_vec())
+                .map_err(|_| Error::<T>::InvalidSignature)?;
+            Self::validate_signature_submission(
+                who,
+                &namespace,
+                &challenge,
+                &location,
+                PAYLOAD_VERSION,
+                &signature,
+            )
+            .map(|_| ())
+        }
+
+        /// Whether the location `geohash` lies in the cell of `challenge`.
+        ///
+        /// A geohash lies in every cell whose geohash is a prefix of it, including its own
+        /// cell, which is only accepted when `AllowExactMatch` is set.
+        pub fn geohash_in_geohash(geohash: &Challenge<T>, challenge: &Challenge<T>) -> bool {
+            geohash.starts_with(challenge) && Self::precise_enough(geohash.len() as u32, challenge)
+        }
+
+        /// Whether a location of `precision` characters is precise enough to attend
+        /// `challenge`: at least as precise, or strictly more without `AllowExactMatch`.
+        fn precise_enough(precision: u32, challenge: &Challenge<T>) -> bool {
+            let required = challenge.len() as u32;
+            precision > required || (precision == required && T::AllowExactMatch::get())
+        }
+
+        /// Registers `key` as the newest verifying key version and returns that version.
+        ///
+        /// Once `MaxVerifyingKeyVersions` are held the oldest version is retired, so proofs
+        /// targeting it are rejected.
+        pub fn rotate_verifying_key(key: RawVerifyingKey) -> u32 {
+            ProofVerifyingKey::<T>::mutate(|keys| {
+                let version = keys.last().map_or(0, |k| k.version.saturating_add(1));
+                if keys.is_full() && !keys.is_empty() {
+                    keys.remove(0);
+                }
+                let _ = keys.try_push(VersionedVerifyingKey { version, key });
+                version
+            })
+        }
+
+        /// The verifying key for `version`, if that version has not been retired
+        pub fn verifying_key(version: u32) -> Option<RawVerifyingKey> {
+            ProofVerifyingKey::<T>::get()
+                .into_iter()
+                .find(|k| k.version == version)
+                .map(|k| k.key)
+        }
+
+        fn verify_zkp(
+            proof: &RawProof<T>,
+            challenge: &Challenge<T>,
+            precision: u32,
+            verifying_key: &RawVerifyingKey,
+        ) -> Result<(), Error<T>> {
+            let public_inputs = Self::public_inputs(challenge, precision);
+            Self::verify_proof(verifying_key, &public_inputs, proof, "proof")
+        }
+
+        /// The public inputs of the prefix circuit for `challenge` and a location of
+        /// `precision` characters: one input per challenge byte, then the precision. They are
+        /// the integers `geohash_inputs::prefix_public_inputs` encodes the prover's field
+        /// elements from.
+        pub fn public_inputs(
+            challenge: &Challenge<T>,
+            precision: u32,
+        ) -> sp_runtime::Vec<PublicInput> {
+            let bytes = challenge.iter().map(|c| Self::integer_input(*c as u64));
+            bytes
+                .chain([Self::integer_input(precision as u64)])
+                .collect()
+        }
+
+        /// The public input holding `value`, zero-extended to 32 bytes
+        pub fn integer_input(value: u64) -> PublicInput {
+            let mut input = [0u8; 32];
+            input[..8].copy_from_slice(&value.to_le_bytes());
+            input
+        }
+
+        /// Verifies a committed prefix proof. The public inputs are the commitment, then the
+        /// precision, matching the prover's `commitment::public_inputs`.
+        fn verify_committed_zkp(
+            proof: &RawProof<T>,
+            commitment: &RawCommitment,
+            precision: u32,
+            verifying_key: &RawVerifyingKey,
+        ) -> Result<(), Error<T>> {
+            let public_inputs = [*commitment, Self::integer_input(precision as u64)];
+            Self::verify_proof(verifying_key, &public_inputs, proof, "committed proof")
+        }
+
+        /// Verifies a distance proof. The public inputs are, in order, the center latitude,
+        /// the center longitude and the radius, matching the prover's `distance::public_inputs`.
+        fn verify_distance_zkp(
+            proof: &RawProof<T>,
+            public_inputs: [u64; 3],
+            verifying_key: &RawVerifyingKey,
+        ) -> Result<(), Error<T>> {
+            let public_inputs = public_inputs.map(Self::integer_input);
+            Self::verify_proof(verifying_key, &public_inputs, proof, "distance proof")
+        }
+
+        /// Runs the body of the submission `call`, logging its error at `debug` level when
+        /// it is rejected. Only the error reaches the log, never the submitted location, proof
+        /// or signature, so operators can follow rejections with `-lruntime::attendance=debug`.
+        fn log_rejection(
+            call: &str,
+            submission: impl FnOnce() -> DispatchResult,
+        ) -> DispatchResult {
+            submission().inspect_err(|error| {
+                log::debug!(target: LOG_TARGET, "{} rejected: {:?}", call, error);
+            })
+        }
+
+        /// Verifies `proof` with `Config::ProofVerifier`, logging why a proof could not be
+        /// checked, such as a malformed key or proof, at `debug` level. The detail only reaches
+        /// node logs, the extrinsic fails with a pallet error: `PublicInputCountMismatch` when
+        /// the key expects another number of public inputs, `InvalidProof` otherwise.
+        fn verify_proof(
+            verifying_key: &[u8],
+            public_inputs: &[PublicInput],
+            proof: &[u8],
+            what: &str,
+        ) -> Result<(), Error<T>> {
+            if let Some(expected) = T::ProofVerifier::public_input_count(verifying_key) {
+                ensure!(
+                    expected == public_inputs.len(),
+                    Error::<T>::PublicInputCountMismatch
+                );
+            }
+            let verified = T::ProofVerifier::verify(verifying_key, public_inputs, proof)
+                .unwrap_or_else(|e| {
+                    log::debug!(target: LOG_TARGET, "{} not verified: {:?}", what, e);
+                    false
+                });
+            ensure!(verified, Error::<T>::InvalidProof);
+            Ok(())
         }
     }
 }
@@ -0,0 +1,204 @@
+//! Storage migrations of the attendance pallet.
+//!
+//! Each module migrates to the storage version it is named after. Runtimes run the migrations
+//! they need from their `Executive`, e.g. `pallet_attendance::migrations::v1::MigrateV0ToV1`.
+
+use frame_support::migrations::VersionedMigration;
+use frame_support::pallet_prelude::*;
+use frame_support::traits::UncheckedOnRuntimeUpgrade;
+use sp_runtime::traits::TrailingZeroInput;
+use sp_runtime::Vec;
+
+use crate::{
+    AttendanceHistory, Challenge, ChallengeExpiries, ChallengeInfo, Challenges, Config, Namespace,
+    Pallet, PendingArchival, ProofVerifyingKey, SubmissionCount, Submissions,
+    VersionedVerifyingKey,
+};
+
+/// Version 1 keys challenges by namespace and geohash, see [`crate::ChallengeKey`].
+pub mod v1 {
+    use super::*;
+
+    /// The storage of version 0, in which challenges were keyed by their geohash alone and
+    /// only marked as registered
+    pub mod v0 {
+        use super::*;
+
+        #[frame_support::storage_alias]
+        pub type Challenges<T: Config> =
+            StorageMap<Pallet<T>, Blake2_128Concat, Challenge<T>, bool>;
+
+        #[frame_support::storage_alias]
+        pub type ChallengeExpiries<T: Config> = StorageMap<
+            Pallet<T>,
+            Twox64Concat,
+            frame_system::pallet_prelude::BlockNumberFor<T>,
+            BoundedVec<Challenge<T>, <T as Config>::MaxExpiringPerBlock>,
+            ValueQuery,
+        >;
+
+        #[frame_support::storage_alias]
+        pub type PendingArchival<T: Config> =
+            StorageMap<Pallet<T>, Blake2_128Concat, Challenge<T>, ()>;
+
+        #[frame_support::storage_alias]
+        pub type SubmissionCount<T: Config> =
+            StorageMap<Pallet<T>, Blake2_128Concat, Challenge<T>, u32, ValueQuery>;
+
+        #[frame_support::storage_alias]
+        pub type Submissions<T: Config> = StorageDoubleMap<
+            Pallet<T>,
+            Blake2_128Concat,
+            Challenge<T>,
+            Blake2_128Concat,
+            <T as frame_system::Config>::AccountId,
+            bool,
+        >;
+
+        #[frame_support::storage_alias]
+        pub type AttendanceHistory<T: Config> = StorageMap<
+            Pallet<T>,
+            Blake2_128Concat,
+            <T as frame_system::Config>::AccountId,
+            BoundedVec<Challenge<T>, <T as Config>::MaxHistory>,
+            ValueQuery,
+        >;
+
+        /// The single, unversioned verifying key of the prefix circuit
+        #[frame_support::storage_alias]
+        pub type ProofVerifyingKey<T: Config> =
+            StorageValue<Pallet<T>, BoundedVec<u8, ConstU32<64>>>;
+    }
+
+    /// The owner given to challenges registered in version 0, which had none: the account
+    /// decoded from zero bytes. Nobody holds its key, so these challenges cannot be closed.
+    pub fn legacy_owner<T: Config>() -> T::AccountId {
+        T::AccountId::decode(&mut TrailingZeroInput::zeroes())
+            .expect("infinite input decodes any account; qed")
+    }
+
+    /// Moves every challenge into the default, empty namespace.
+    ///
+    /// Entries of the maps keyed by challenge are moved to their new keys, keeping the stored
+    /// geohash, which is already packed on runtimes with `PackedChallengeKeys`. Each challenge
+    /// becomes a `ChallengeInfo` owned by [`legacy_owner`], without capacity or expiry, as in
+    /// version 0. Reinserting the challenges also initializes the counter of `Challenges`. The
+    /// expiry index now holds keys, and the attendance histories namespaces. The verifying key
+    /// becomes version 0 of the versioned keys. All entries are migrated in a single block.
+    pub struct InnerMigrateV0ToV1<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV0ToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let default = Namespace::<T>::default;
+            let mut moved = 0u64;
+
+            // Old and new keys share a prefix, so each map is drained before reinserting
+            let challenges = v0::Challenges::<T>::drain().collect::<Vec<_>>();
+            let counted = challenges.len() as u64;
+            moved += counted;
+            let owner = legacy_owner::<T>();
+            for (key, _) in challenges {
+                let info = ChallengeInfo {
+                    owner: owner.clone(),
+                    capacity: None,
+                    expires_at: None,
+                };
+                Challenges::<T>::insert((default(), key), info);
+            }
+            let pending = v0::PendingArchival::<T>::drain().collect::<Vec<_>>();
+            moved += pending.len() as u64;
+            for (key, ()) in pending {
+                PendingArchival::<T>::insert((default(), key), ());
+            }
+            let counts = v0::SubmissionCount::<T>::drain().collect::<Vec<_>>();
+            moved += counts.len() as u64;
+            for (key, count) in counts {
+                SubmissionCount::<T>::insert((default(), key), count);
+            }
+            let submissions = v0::Submissions::<T>::drain().collect::<Vec<_>>();
+            moved += submissions.len() as u64;
+            for (key, who, submitted) in submissions {
+                Submissions::<T>::insert((default(), key), who, submitted);
+            }
+
+            let mut translated = 0u64;
+            ChallengeExpiries::<T>::translate(
+                |_, challenges: BoundedVec<Challenge<T>, T::MaxExpiringPerBlock>| {
+                    translated += 1;
+                    let keys = challenges
+                        .iter()
+                        .map(|challenge| Pallet::<T>::challenge_key(&default(), challenge));
+                    Some(BoundedVec::truncate_from(keys.collect()))
+                },
+            );
+            AttendanceHistory::<T>::translate(
+                |_, history: BoundedVec<Challenge<T>, T::MaxHistory>| {
+                    translated += 1;
+                    let history = history.into_iter().map(|challenge| (default(), challenge));
+                    Some(BoundedVec::truncate_from(history.collect()))
+                },
+            );
+
+            // The value is read as the old key before its new encoding is written
+            if let Some(key) = v0::ProofVerifyingKey::<T>::take() {
+                let key = VersionedVerifyingKey {
+                    version: 0,
+                    key: BoundedVec::truncate_from(key.into_inner()),
+                };
+                ProofVerifyingKey::<T>::put(BoundedVec::truncate_from(Vec::from([key])));
+            }
+            translated += 1;
+
+            // Each counted insertion also reads the key and updates the counter
+            T::DbWeight::get().reads_writes(
+                moved + translated + 2 * counted,
+                2 * moved + translated + counted,
+            )
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+            let challenges = v0::Challenges::<T>::iter_keys().collect::<Vec<_>>();
+            Ok((challenges, v0::ProofVerifyingKey::<T>::exists()).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            let (challenges, verifying_key) = <(Vec<Challenge<T>>, bool)>::decode(&mut &state[..])
+                .map_err(|_| "invalid pre-upgrade state")?;
+            ensure!(
+                Challenges::<T>::iter_keys().all(|(namespace, _)| namespace.is_empty()),
+                "challenge outside the default namespace"
+            );
+            // Every challenge decodes under its new key, so none was dropped by the drain
+            ensure!(
+                challenges.iter().all(|challenge| {
+                    Challenges::<T>::get((Namespace::<T>::default(), challenge.clone())).is_some()
+                }),
+                "challenges lost in the migration"
+            );
+            ensure!(
+                Challenges::<T>::iter().count() == challenges.len(),
+                "challenges lost in the migration"
+            );
+            ensure!(
+                Challenges::<T>::count() as usize == challenges.len(),
+                "challenges miscounted in the migration"
+            );
+            ensure!(
+                ProofVerifyingKey::<T>::get().len() == verifying_key as usize,
+                "verifying key lost in the migration"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrates from storage version 0 to 1, and only from version 0
+    pub type MigrateV0ToV1<T> = VersionedMigration<
+        0,
+        1,
+        InnerMigrateV0ToV1<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
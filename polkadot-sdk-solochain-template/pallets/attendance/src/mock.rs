@@ -23,26 +23,63 @@ frame_support::construct_runtime!(
 impl frame_system::Config for Test {
     type Block = Block;
 }
-use lazy_static::lazy_static;
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::Mutex;
 
-lazy_static! {
-    static ref MINTS: Mutex<HashMap<Vec<u8>, bool>> = Mutex::new(HashMap::new());
+thread_local! {
+    // Outside storage, like a reward with external effects, so it is not rolled back
+    static MINTS: RefCell<HashMap<Vec<u8>, Vec<u64>>> = RefCell::new(HashMap::new());
 }
+
+/// The amounts of the rewards `MockMinter` minted for `account` in this test
+fn rewards<T: Encode>(account: &T) -> Vec<u64> {
+    let account = account.encode();
+    MINTS.with(|mints| mints.borrow().get(&account).cloned().unwrap_or_default())
+}
+
+/// How many rewards `MockMinter` minted for `account` in this test
+pub fn mints<T: Encode>(account: &T) -> u32 {
+    rewards(account).len() as u32
+}
+
+/// The total amount `MockMinter` minted for `account` in this test
+pub fn minted<T: Encode>(account: &T) -> u64 {
+    rewards(account).iter().sum()
+}
+
 pub struct MockMinter<T>(PhantomData<T>);
 
 impl<T> Mintable<T> for MockMinter<T>
 where
     T: Encode,
 {
-    fn mint(account: &T) {
-        let mut mints = MINTS.lock().unwrap();
-        mints.insert(account.encode(), true);
+    type Balance = u64;
+
+    fn mint(account: &T, amount: u64) {
+        MINTS.with(|mints| {
+            let mut mints = mints.borrow_mut();
+            mints.entry(account.encode()).or_default().push(amount)
+        });
     }
 }
 parameter_types! {
     pub const MaxGeohashLength: u32 = 12;
+    pub const DomainTag: &'static [u8] = b"aoi-attendance";
+    pub const MaxVerifyingKeyVersions: u32 = 2;
+    pub const MaxExpiringPerBlock: u32 = 2;
+    pub const MaxOracles: u32 = 2;
+    pub const MaxProofLength: u32 = 256;
+    pub const MaxDistanceRadius: u64 = 1_000;
+    pub const MaxOracleSigs: u32 = 3;
+    pub const AttestationThreshold: u32 = 2;
+    pub const MaxHistory: u32 = 2;
+    pub const MaxLabelLength: u32 = 16;
+    pub static AllowExactMatch: bool = true;
+    pub const MaxArchivedPerBlock: u32 = 3;
+    pub static PackedChallengeKeys: bool = false;
+    pub const MaxNamespace: u32 = 8;
+    pub static SubmissionTtl: u64 = 0;
+    pub static RewardAmount: u64 = 5;
 }
 
 #[derive(Default)]
@@ -69,17 +106,50 @@ impl pallet_attendance::Config for Test {
     type RuntimeEvent = RuntimeEvent;
     type WeightInfo = ();
     type MaxGeohashLength = MaxGeohashLength;
+    type DomainTag = DomainTag;
+    type MaxVerifyingKeyVersions = MaxVerifyingKeyVersions;
+    type MaxExpiringPerBlock = MaxExpiringPerBlock;
+    type MaxOracles = MaxOracles;
+    type MaxProofLength = MaxProofLength;
+    type MaxDistanceRadius = MaxDistanceRadius;
+    type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type MaxOracleSigs = MaxOracleSigs;
+    type AttestationThreshold = AttestationThreshold;
+    type MaxHistory = MaxHistory;
+    type AllowExactMatch = AllowExactMatch;
+    type MaxLabelLength = MaxLabelLength;
+    type MaxArchivedPerBlock = MaxArchivedPerBlock;
+    type PackedChallengeKeys = PackedChallengeKeys;
+    type MaxNamespace = MaxNamespace;
+    type SubmissionTtl = SubmissionTtl;
+    type RewardAmount = RewardAmount;
     type Mint = MockMinter<Self::AccountId>;
     type PublicKeyOfOracle = Dummy;
     type PayloadHasher = MockHasher;
     type Signature = Dummy;
     type Verify = Dummy;
+    type ProofVerifier = pallet_attendance::Groth16Bn254;
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
+    MINTS.with(|mints| mints.borrow_mut().clear());
     frame_system::GenesisConfig::<Test>::default()
         .build_storage()
         .unwrap()
         .into()
 }
+
+// Build genesis storage with the attendance pallet seeded from `attendance`.
+pub fn new_test_ext_with(
+    attendance: pallet_attendance::GenesisConfig<Test>,
+) -> sp_io::TestExternalities {
+    MINTS.with(|mints| mints.borrow_mut().clear());
+    RuntimeGenesisConfig {
+        system: Default::default(),
+        attendance_module: attendance,
+    }
+    .build_storage()
+    .unwrap()
+    .into()
+}
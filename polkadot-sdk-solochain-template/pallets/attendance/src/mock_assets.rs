@@ -0,0 +1,88 @@
+//! A mock runtime rewarding attendees in an asset of `pallet_assets` through
+//! `FungiblesReward`.
+
+use crate::{self as pallet_attendance, FungiblesReward};
+use crate::mock::{
+    AllowExactMatch, AttestationThreshold, DomainTag, MaxArchivedPerBlock, MaxDistanceRadius,
+    MaxExpiringPerBlock, MaxGeohashLength, MaxHistory, MaxLabelLength, MaxNamespace, MaxOracleSigs,
+    MaxOracles, MaxProofLength, MaxVerifyingKeyVersions, MockHasher, PackedChallengeKeys,
+    SubmissionTtl,
+};
+use frame_support::{derive_impl, parameter_types, traits::AsEnsureOriginWithArg};
+use frame_system::{EnsureRoot, EnsureSigned};
+use sp_core::crypto::Dummy;
+use sp_runtime::BuildStorage;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test
+    {
+        System: frame_system,
+        Balances: pallet_balances,
+        Assets: pallet_assets,
+        AttendanceModule: pallet_attendance,
+    }
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+    type Block = Block;
+    type AccountData = pallet_balances::AccountData<u64>;
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+    type AccountStore = System;
+}
+
+#[derive_impl(pallet_assets::config_preludes::TestDefaultConfig)]
+impl pallet_assets::Config for Test {
+    type Currency = Balances;
+    type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<u64>>;
+    type ForceOrigin = EnsureRoot<u64>;
+    type Freezer = ();
+}
+
+parameter_types! {
+    pub const RewardAsset: u32 = 7;
+    pub const RewardAmount: u64 = 10;
+}
+
+impl pallet_attendance::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type WeightInfo = ();
+    type MaxGeohashLength = MaxGeohashLength;
+    type DomainTag = DomainTag;
+    type MaxVerifyingKeyVersions = MaxVerifyingKeyVersions;
+    type MaxExpiringPerBlock = MaxExpiringPerBlock;
+    type MaxOracles = MaxOracles;
+    type MaxProofLength = MaxProofLength;
+    type MaxDistanceRadius = MaxDistanceRadius;
+    type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type MaxOracleSigs = MaxOracleSigs;
+    type AttestationThreshold = AttestationThreshold;
+    type MaxHistory = MaxHistory;
+    type AllowExactMatch = AllowExactMatch;
+    type MaxLabelLength = MaxLabelLength;
+    type MaxArchivedPerBlock = MaxArchivedPerBlock;
+    type PackedChallengeKeys = PackedChallengeKeys;
+    type MaxNamespace = MaxNamespace;
+    type SubmissionTtl = SubmissionTtl;
+    type RewardAmount = RewardAmount;
+    type Mint = FungiblesReward<Assets, RewardAsset>;
+    type PublicKeyOfOracle = Dummy;
+    type PayloadHasher = MockHasher;
+    type Signature = Dummy;
+    type Verify = Dummy;
+    type ProofVerifier = pallet_attendance::Groth16Bn254;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap()
+        .into()
+}
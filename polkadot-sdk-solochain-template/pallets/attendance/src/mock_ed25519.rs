@@ -0,0 +1,67 @@
+//! A mock runtime wiring real ed25519 signatures and Blake2-256 payload hashing, so the
+//! signature verification branch of the pallet is actually exercised.
+
+use crate::{self as pallet_attendance};
+use crate::mock::{
+    AllowExactMatch, AttestationThreshold, DomainTag, MaxArchivedPerBlock, MaxDistanceRadius,
+    MaxExpiringPerBlock, MaxGeohashLength, MaxHistory, MaxLabelLength, MaxNamespace, MaxOracleSigs,
+    MaxOracles, MaxProofLength, MaxVerifyingKeyVersions, MockMinter, PackedChallengeKeys,
+    RewardAmount, SubmissionTtl,
+};
+use frame_support::derive_impl;
+use sp_core::ed25519;
+use sp_runtime::traits::BlakeTwo256;
+use sp_runtime::BuildStorage;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test
+    {
+        System: frame_system,
+        AttendanceModule: pallet_attendance,
+    }
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+    type Block = Block;
+}
+
+impl pallet_attendance::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type WeightInfo = ();
+    type MaxGeohashLength = MaxGeohashLength;
+    type DomainTag = DomainTag;
+    type MaxVerifyingKeyVersions = MaxVerifyingKeyVersions;
+    type MaxExpiringPerBlock = MaxExpiringPerBlock;
+    type MaxOracles = MaxOracles;
+    type MaxProofLength = MaxProofLength;
+    type MaxDistanceRadius = MaxDistanceRadius;
+    type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type MaxOracleSigs = MaxOracleSigs;
+    type AttestationThreshold = AttestationThreshold;
+    type MaxHistory = MaxHistory;
+    type AllowExactMatch = AllowExactMatch;
+    type MaxLabelLength = MaxLabelLength;
+    type MaxArchivedPerBlock = MaxArchivedPerBlock;
+    type PackedChallengeKeys = PackedChallengeKeys;
+    type MaxNamespace = MaxNamespace;
+    type SubmissionTtl = SubmissionTtl;
+    type RewardAmount = RewardAmount;
+    type Mint = MockMinter<Self::AccountId>;
+    type PublicKeyOfOracle = ed25519::Public;
+    type PayloadHasher = BlakeTwo256;
+    type Signature = ed25519::Signature;
+    type Verify = ed25519::Pair;
+    type ProofVerifier = pallet_attendance::Groth16Bn254;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap()
+        .into()
+}
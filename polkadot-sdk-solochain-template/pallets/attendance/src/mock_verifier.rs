@@ -0,0 +1,125 @@
+//! A mock runtime whose proof system is `MockVerifier`, so the pallet's proof submissions can be
+//! tested without generating Groth16 proofs.
+
+use core::cell::RefCell;
+
+use crate::{self as pallet_attendance, ProofVerifier, PublicInput};
+use crate::mock::{
+    AllowExactMatch, AttestationThreshold, DomainTag, MaxArchivedPerBlock, MaxDistanceRadius,
+    MaxExpiringPerBlock, MaxGeohashLength, MaxHistory, MaxLabelLength, MaxNamespace, MaxOracleSigs,
+    MaxOracles, MaxProofLength, MaxVerifyingKeyVersions, MockHasher, MockMinter,
+    PackedChallengeKeys, RewardAmount, SubmissionTtl,
+};
+use frame_support::derive_impl;
+use sp_core::crypto::Dummy;
+use sp_runtime::BuildStorage;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test
+    {
+        System: frame_system,
+        AttendanceModule: pallet_attendance,
+    }
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+    type Block = Block;
+}
+
+/// A proof `MockVerifier` accepts for any key and public inputs
+pub const VALID_PROOF: &[u8] = b"valid";
+
+/// A proof `MockVerifier` cannot check, failing with `MalformedProof`
+pub const MALFORMED_PROOF: &[u8] = b"malformed";
+
+thread_local! {
+    static VERIFIED: RefCell<Vec<(Vec<u8>, Vec<PublicInput>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The verifying keys and public inputs `MockVerifier` was called with, oldest first
+pub fn verified() -> Vec<(Vec<u8>, Vec<PublicInput>)> {
+    VERIFIED.with(|verified| verified.borrow().clone())
+}
+
+/// Why `MockVerifier` could not check a proof
+#[derive(Debug)]
+pub struct MalformedProof;
+
+/// Accepts `VALID_PROOF`, fails to check `MALFORMED_PROOF` and rejects any other proof,
+/// recording each call for `verified`. Public inputs with a nonzero last byte are invalid, and
+/// keys do not tell their public input count, so any number of inputs is verified.
+pub struct MockVerifier;
+
+impl ProofVerifier for MockVerifier {
+    type Error = MalformedProof;
+
+    fn verify(
+        verifying_key: &[u8],
+        public_inputs: &[PublicInput],
+        proof: &[u8],
+    ) -> Result<bool, MalformedProof> {
+        VERIFIED.with(|verified| {
+            verified
+                .borrow_mut()
+                .push((verifying_key.to_vec(), public_inputs.to_vec()))
+        });
+        match proof {
+            MALFORMED_PROOF => Err(MalformedProof),
+            proof => Ok(proof == VALID_PROOF),
+        }
+    }
+
+    fn valid_public_input(input: &PublicInput) -> bool {
+        input[31] == 0
+    }
+
+    fn public_input_count(_verifying_key: &[u8]) -> Option<usize> {
+        None
+    }
+
+    fn valid_verifying_key(_verifying_key: &[u8]) -> bool {
+        true
+    }
+}
+
+impl pallet_attendance::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type WeightInfo = ();
+    type MaxGeohashLength = MaxGeohashLength;
+    type DomainTag = DomainTag;
+    type MaxVerifyingKeyVersions = MaxVerifyingKeyVersions;
+    type MaxExpiringPerBlock = MaxExpiringPerBlock;
+    type MaxOracles = MaxOracles;
+    type MaxProofLength = MaxProofLength;
+    type MaxDistanceRadius = MaxDistanceRadius;
+    type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type MaxOracleSigs = MaxOracleSigs;
+    type AttestationThreshold = AttestationThreshold;
+    type MaxHistory = MaxHistory;
+    type AllowExactMatch = AllowExactMatch;
+    type MaxLabelLength = MaxLabelLength;
+    type MaxArchivedPerBlock = MaxArchivedPerBlock;
+    type PackedChallengeKeys = PackedChallengeKeys;
+    type MaxNamespace = MaxNamespace;
+    type SubmissionTtl = SubmissionTtl;
+    type RewardAmount = RewardAmount;
+    type Mint = MockMinter<Self::AccountId>;
+    type PublicKeyOfOracle = Dummy;
+    type PayloadHasher = MockHasher;
+    type Signature = Dummy;
+    type Verify = Dummy;
+    type ProofVerifier = MockVerifier;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    VERIFIED.with(|verified| verified.borrow_mut().clear());
+    frame_system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap()
+        .into()
+}
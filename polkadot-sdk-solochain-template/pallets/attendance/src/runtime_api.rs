@@ -0,0 +1,47 @@
+//! Runtime API of the attendance pallet, for front-ends reading its state through a node.
+
+use codec::Codec;
+use sp_runtime::Vec;
+
+sp_api::decl_runtime_apis! {
+    /// Queries of the attendance pallet's state
+    ///
+    /// Version 2 returns the namespaces of the challenges in the attendance history, version 3
+    /// adds `challenge_count` and version 4 `would_accept_signature`.
+    #[api_version(4)]
+    pub trait AttendanceApi<AccountId>
+    where
+        AccountId: Codec,
+    {
+        /// The challenges `who` submitted to, oldest first, at most `MaxHistory` of them
+        #[changed_in(2)]
+        fn attendance_history(who: AccountId) -> Vec<Vec<u8>>;
+
+        /// The challenges `who` submitted to as `(namespace, geohash)` pairs, oldest first, at
+        /// most `MaxHistory` of them
+        fn attendance_history(who: AccountId) -> Vec<(Vec<u8>, Vec<u8>)>;
+
+        /// The number of registered challenges, across all namespaces
+        #[api_version(3)]
+        fn challenge_count() -> u32;
+
+        /// The public key of the added oracle with `label`, if any
+        fn oracle_by_label(label: Vec<u8>) -> Option<Vec<u8>>;
+
+        /// The cells around the geohash `challenge` at the same precision, clockwise from the
+        /// north and without cells beyond a pole, to guide attendees close to the challenge
+        fn geohash_neighbors(challenge: Vec<u8>) -> Vec<Vec<u8>>;
+
+        /// Whether `submission_with_signature` from `who` would accept `signature` of
+        /// `location` for `challenge` in `namespace`, failing with the error the call would
+        /// fail with. Nothing is recorded, so clients can check before paying for a submission.
+        #[api_version(4)]
+        fn would_accept_signature(
+            who: AccountId,
+            namespace: Vec<u8>,
+            challenge: Vec<u8>,
+            location: Vec<u8>,
+            signature: Vec<u8>,
+        ) -> Result<(), sp_runtime::DispatchError>;
+    }
+}
@@ -1,10 +1,29 @@
 mod tests {
-    use crate::{mock::*, Challenges, Error};
-    use frame_support::{assert_noop, assert_ok};
+    use crate::{
+        migrations::v1::{self, v0, MigrateV0ToV1},
+        mock::*,
+        AcceptedPrecisions, AttendanceHistory, ChallengeExpiries, ChallengeInfo, ChallengeInfoOf,
+        ChallengeKey, Challenges, CommittedChallenges, CommittedSubmissions, Error, Event,
+        GenesisConfig, Groth16Bn254, MaxPrecision, MinPrecision, Namespace, Oracles,
+        PendingArchival, ProofVerifier, ProofVerifyingKey, SubmissionCount, Submissions,
+        SubmittedAt, PAYLOAD_VERSION,
+    };
+    use ark_serialize::CanonicalSerialize;
+    use codec::{Decode, Encode};
+    use frame_support::{
+        assert_noop, assert_ok, assert_storage_noop,
+        dispatch::DispatchResult,
+        traits::{ConstU32, GetStorageVersion, Hooks, OnRuntimeUpgrade, StorageVersion},
+    };
+    use geohash_prover::commitment::{self, CommittedPrefixCircuit};
+    use geohash_prover::CompareCircuit;
+    use rand::{rngs::StdRng, SeedableRng};
     use sp_core::{crypto::Dummy, Pair};
     use sp_runtime::BoundedVec;
 
     const ALICE: u64 = 1;
+    const BOB: u64 = 2;
+    const CHARLIE: u64 = 3;
 
     #[derive(Clone)]
     struct Geohash(&'static str);
@@ -14,6 +33,96 @@ mod tests {
                 .expect("Failed to convert geohash string to bounded vector")
         }
     }
+    fn challenge(geohash: &'static str) -> BoundedVec<u8, MaxGeohashLength> {
+        Geohash(geohash).into()
+    }
+
+    /// The namespace of the tests that are not about namespaces
+    fn default_namespace() -> Namespace<Test> {
+        Namespace::<Test>::default()
+    }
+
+    fn namespace(name: &'static str) -> Namespace<Test> {
+        BoundedVec::try_from(name.as_bytes().to_vec()).expect("namespace within MaxNamespace")
+    }
+
+    /// The storage key of the challenge for `geohash` in the default namespace
+    fn key(geohash: &'static str) -> ChallengeKey<Test> {
+        AttendanceModule::challenge_key(&default_namespace(), &challenge(geohash))
+    }
+
+    fn submittable(geohash: &'static str) -> Result<ChallengeInfoOf<Test>, Error<Test>> {
+        AttendanceModule::ensure_challenge_submittable(&default_namespace(), &challenge(geohash))
+    }
+
+    fn create_expiring(who: u64, geohash: &'static str, expires_at: u64) -> DispatchResult {
+        AttendanceModule::create_challenge(
+            RuntimeOrigin::signed(who),
+            default_namespace(),
+            Geohash(geohash).into(),
+            None,
+            Some(expires_at),
+        )
+    }
+
+    fn set_oracle() {
+        assert_ok!(AttendanceModule::set_oracle_public_key(
+            RuntimeOrigin::root(),
+            Dummy::default().to_raw_vec().try_into().expect(""),
+            false
+        ));
+    }
+
+    fn submit_signature(who: u64, challenge: &'static str, location: &'static str) -> DispatchResult {
+        submit_signature_in(who, default_namespace(), challenge, location)
+    }
+
+    fn submit_signature_in(
+        who: u64,
+        namespace: Namespace<Test>,
+        challenge: &'static str,
+        location: &'static str,
+    ) -> DispatchResult {
+        AttendanceModule::submission_with_signature(
+            RuntimeOrigin::signed(who),
+            namespace,
+            Geohash(challenge).into(),
+            Geohash(location).into(),
+            PAYLOAD_VERSION,
+            Dummy::default()
+                .to_raw_vec()
+                .try_into()
+                .expect("signature to vector"),
+        )
+    }
+
+    /// A verifying key of the prefix circuit for `challenge` and locations as long as
+    /// `location`, with a proof that `location` starts with `challenge`. The setup is
+    /// deterministic in `seed`.
+    fn prefix_proof(
+        seed: u64,
+        challenge: &str,
+        location: &str,
+    ) -> (BoundedVec<u8, ConstU32<2048>>, BoundedVec<u8, MaxProofLength>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let circuit = CompareCircuit::new_from_str(challenge, location);
+        let (proving_key, verifying_key) =
+            geohash_prover::setup_groth16(&mut rng, circuit.clone()).expect("setup");
+        let proof =
+            geohash_prover::create_proof(&proving_key, circuit, &mut rng).expect("proof");
+
+        let mut verifying_key_bytes = Vec::new();
+        verifying_key
+            .serialize_uncompressed(&mut verifying_key_bytes)
+            .expect("serialized verifying key");
+        (
+            verifying_key_bytes.try_into().expect("verifying key within bound"),
+            geohash_prover::proof_to_raw(&proof)
+                .try_into()
+                .expect("proof within MaxProofLength"),
+        )
+    }
+
     #[test]
     fn test_valid_geohash() {
         // Test valid geohashes
@@ -37,6 +146,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_geohash_in_geohash() {
+        new_test_ext().execute_with(|| {
+            let within = |location, geohash| {
+                AttendanceModule::geohash_in_geohash(&challenge(location), &challenge(geohash))
+            };
+            // A longer location sharing the prefix, and the challenge cell itself
+            assert!(within("bcdefg", "bcd"));
+            assert!(within("bcd", "bcd"));
+            // A shorter location, even one the challenge starts with, is a larger cell
+            assert!(!within("bc", "bcd"));
+            assert!(!within("b", "bcd"));
+            // A location outside the challenge cell, of any length
+            assert!(!within("bcefgh", "bcd"));
+            assert!(!within("bce", "bcd"));
+            assert!(!within("cbd", "bcd"));
+
+            AllowExactMatch::set(false);
+            assert!(within("bcde", "bcd"));
+            assert!(!within("bcd", "bcd"));
+        });
+    }
+
     #[test]
     fn create_challenge() {
         new_test_ext().execute_with(|| {
@@ -44,16 +176,246 @@ mod tests {
             let geohash = BoundedVec::<u8, MaxGeohashLength>::from(Geohash("bcd"));
             assert_ok!(AttendanceModule::create_challenge(
                 RuntimeOrigin::signed(ALICE),
-                geohash.clone()
+                default_namespace(),
+                geohash.clone(),
+                None,
+                None
             ));
-            assert!(Challenges::<Test>::contains_key(geohash.clone()));
+            assert!(Challenges::<Test>::contains_key(key("bcd")));
+            assert_noop!(
+                AttendanceModule::create_challenge(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    geohash,
+                    None,
+                    None
+                ),
+                Error::<Test>::ChallengeAlreadyExists
+            );
+        });
+    }
+
+    #[test]
+    fn invalid_and_existing_challenges_fail_differently() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(create_expiring(ALICE, "bcd", 100));
+
+            assert_noop!(
+                AttendanceModule::create_challenge(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge("bca"),
+                    None,
+                    None
+                ),
+                Error::<Test>::InvalidGeohash
+            );
+            assert_noop!(
+                create_expiring(BOB, "bcd", 100),
+                Error::<Test>::ChallengeAlreadyExists
+            );
+        });
+    }
+
+    #[test]
+    fn idempotent_creation_accepts_identical_retries() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let create = |who, capacity, expires_at| {
+                AttendanceModule::create_challenge_idempotent(
+                    RuntimeOrigin::signed(who),
+                    default_namespace(),
+                    challenge("bcd"),
+                    capacity,
+                    expires_at,
+                )
+            };
+
+            assert_ok!(create(ALICE, Some(2), Some(100)));
+            System::assert_last_event(
+                Event::ChallengeCreated {
+                    who: ALICE,
+                    namespace: default_namespace(),
+                    challenge: challenge("bcd"),
+                }
+                .into(),
+            );
+            let events = System::events().len();
+
+            // An identical retry changes nothing
+            assert_ok!(create(ALICE, Some(2), Some(100)));
+            assert_eq!(System::events().len(), events);
+
+            // Other parameters, or another owner, do not match the existing challenge
+            for (who, capacity, expires_at) in [
+                (ALICE, Some(3), Some(100)),
+                (ALICE, Some(2), None),
+                (BOB, Some(2), Some(100)),
+            ] {
+                assert_noop!(
+                    create(who, capacity, expires_at),
+                    Error::<Test>::ChallengeAlreadyExists
+                );
+            }
+
+            // Creation is otherwise validated as usual
             assert_noop!(
-                AttendanceModule::create_challenge(RuntimeOrigin::signed(ALICE), geohash),
+                AttendanceModule::create_challenge_idempotent(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge("bca"),
+                    None,
+                    None,
+                ),
                 Error::<Test>::InvalidGeohash
             );
         });
     }
 
+    #[test]
+    fn namespaces_hold_independent_challenges() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            let (org_a, org_b) = (namespace("org-a"), namespace("org-b"));
+            let create = |who, namespace| {
+                AttendanceModule::create_challenge(
+                    RuntimeOrigin::signed(who),
+                    namespace,
+                    challenge("bcd"),
+                    Some(1),
+                    None,
+                )
+            };
+
+            // The same geohash can be a challenge in each namespace, but once in each
+            assert_ok!(create(ALICE, org_a.clone()));
+            assert_ok!(create(BOB, org_b.clone()));
+            System::assert_last_event(
+                Event::ChallengeCreated {
+                    who: BOB,
+                    namespace: org_b.clone(),
+                    challenge: challenge("bcd"),
+                }
+                .into(),
+            );
+            assert_noop!(
+                create(BOB, org_a.clone()),
+                Error::<Test>::ChallengeAlreadyExists
+            );
+            assert_noop!(
+                submit_signature(ALICE, "bcd", "bcde"),
+                Error::<Test>::ChallengeNotFound
+            );
+
+            // Submissions and capacity are counted per namespace
+            assert_ok!(submit_signature_in(ALICE, org_a.clone(), "bcd", "bcde"));
+            assert_noop!(
+                submit_signature_in(BOB, org_a.clone(), "bcd", "bcde"),
+                Error::<Test>::ChallengeFull
+            );
+            assert_ok!(submit_signature_in(BOB, org_b.clone(), "bcd", "bcde"));
+            let org_a_key = (org_a.clone(), challenge("bcd"));
+            let org_b_key = (org_b.clone(), challenge("bcd"));
+            assert!(Submissions::<Test>::contains_key(&org_a_key, ALICE));
+            assert!(!Submissions::<Test>::contains_key(&org_b_key, ALICE));
+            assert_eq!(SubmissionCount::<Test>::get(&org_b_key), 1);
+            assert_eq!(
+                AttendanceModule::attendance_history(&BOB),
+                vec![(b"org-b".to_vec(), b"bcd".to_vec())]
+            );
+
+            // Closing a challenge leaves the one of the other namespace
+            assert_noop!(
+                AttendanceModule::close_challenge(
+                    RuntimeOrigin::signed(ALICE),
+                    org_b.clone(),
+                    challenge("bcd")
+                ),
+                Error::<Test>::NotChallengeOwner
+            );
+            assert_ok!(AttendanceModule::close_challenge(
+                RuntimeOrigin::signed(ALICE),
+                org_a.clone(),
+                challenge("bcd")
+            ));
+            assert!(!Challenges::<Test>::contains_key(&org_a_key));
+            assert!(Challenges::<Test>::contains_key(&org_b_key));
+        });
+    }
+
+    #[test]
+    fn migration_moves_challenges_into_the_default_namespace() {
+        new_test_ext().execute_with(|| {
+            StorageVersion::new(0).put::<AttendanceModule>();
+            // Version 0 only marked challenges as registered
+            v0::Challenges::<Test>::insert(challenge("bcd"), true);
+            v0::SubmissionCount::<Test>::insert(challenge("bcd"), 1);
+            v0::Submissions::<Test>::insert(challenge("bcd"), BOB, true);
+            v0::PendingArchival::<Test>::insert(challenge("bce"), ());
+            v0::ChallengeExpiries::<Test>::insert(
+                10,
+                BoundedVec::try_from(vec![challenge("bcd")]).unwrap(),
+            );
+            v0::AttendanceHistory::<Test>::insert(
+                BOB,
+                BoundedVec::try_from(vec![challenge("bcd"), challenge("bce")]).unwrap(),
+            );
+
+            MigrateV0ToV1::<Test>::on_runtime_upgrade();
+
+            let info = ChallengeInfo {
+                owner: v1::legacy_owner::<Test>(),
+                capacity: None,
+                expires_at: None,
+            };
+            assert_eq!(info.owner, 0);
+            assert_eq!(AttendanceModule::on_chain_storage_version(), 1);
+            assert_eq!(AttendanceModule::challenge_count(), 1);
+            assert_eq!(Challenges::<Test>::get(key("bcd")), Some(info.clone()));
+            assert_eq!(Challenges::<Test>::iter_keys().count(), 1);
+            assert_eq!(SubmissionCount::<Test>::get(key("bcd")), 1);
+            assert_eq!(Submissions::<Test>::get(key("bcd"), BOB), Some(true));
+            assert!(PendingArchival::<Test>::contains_key(key("bce")));
+            assert_eq!(
+                ChallengeExpiries::<Test>::get(10).to_vec(),
+                vec![key("bcd")]
+            );
+            assert_eq!(
+                AttendanceModule::attendance_history(&BOB),
+                vec![(vec![], b"bcd".to_vec()), (vec![], b"bce".to_vec())]
+            );
+
+            // Once migrated, running it again changes nothing
+            MigrateV0ToV1::<Test>::on_runtime_upgrade();
+            assert_eq!(Challenges::<Test>::get(key("bcd")), Some(info));
+            assert_eq!(Challenges::<Test>::iter_keys().count(), 1);
+        });
+    }
+
+    #[test]
+    fn migration_keeps_the_verifying_key_as_version_0() {
+        new_test_ext().execute_with(|| {
+            StorageVersion::new(0).put::<AttendanceModule>();
+            v0::ProofVerifyingKey::<Test>::put(BoundedVec::truncate_from(vec![7; 64]));
+            // Unmigrated, the key does not decode as a list of versions
+            assert!(ProofVerifyingKey::<Test>::get().is_empty());
+
+            MigrateV0ToV1::<Test>::on_runtime_upgrade();
+
+            assert_eq!(ProofVerifyingKey::<Test>::get().len(), 1);
+            assert_eq!(
+                AttendanceModule::verifying_key(0),
+                Some(BoundedVec::truncate_from(vec![7; 64]))
+            );
+            assert_eq!(
+                AttendanceModule::rotate_verifying_key(BoundedVec::truncate_from(vec![8; 64])),
+                1
+            );
+        });
+    }
+
     #[test]
     fn submit_valid_geohash_for_challenge() {
         new_test_ext().execute_with(|| {
@@ -61,20 +423,26 @@ mod tests {
 
             assert_ok!(AttendanceModule::set_oracle_public_key(
                 RuntimeOrigin::root(),
-                Dummy::default().to_raw_vec().try_into().expect("")
+                Dummy::default().to_raw_vec().try_into().expect(""),
+                false
             ));
 
             assert_ok!(AttendanceModule::create_challenge(
                 RuntimeOrigin::signed(ALICE),
-                Geohash("bcd").into()
+                default_namespace(),
+                Geohash("bcd").into(),
+                None,
+                None
             ));
 
             let signature = Dummy::default();
 
             assert_ok!(AttendanceModule::submission_with_signature(
                 RuntimeOrigin::signed(ALICE),
+                default_namespace(),
                 Geohash("bcd").into(),
                 Geohash("bcdefg").into(),
+                PAYLOAD_VERSION,
                 signature
                     .to_raw_vec()
                     .try_into()
@@ -90,31 +458,2643 @@ mod tests {
 
             assert_ok!(AttendanceModule::create_challenge(
                 RuntimeOrigin::signed(ALICE),
-                Geohash("bcd").into()
+                default_namespace(),
+                Geohash("bcd").into(),
+                None,
+                None
+            ));
+
+            // No oracle is set: the submission is accepted on the strength of the proof alone
+            let (verifying_key, proof) = prefix_proof(1, "bcd", "bcdefg");
+            let version = AttendanceModule::rotate_verifying_key(verifying_key);
+
+            assert_ok!(AttendanceModule::submission_with_proof(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                Geohash("bcd").into(),
+                version,
+                6,
+                proof,
+            ));
+            assert!(Submissions::<Test>::contains_key(key("bcd"), ALICE));
+            assert_eq!(SubmissionCount::<Test>::get(key("bcd")), 1);
+            assert_eq!(
+                AttendanceModule::attendance_history(&ALICE),
+                vec![(vec![], b"bcd".to_vec())]
+            );
+            System::assert_last_event(
+                Event::ProofAccepted {
+                    who: ALICE,
+                    namespace: default_namespace(),
+                    challenge: Geohash("bcd").into(),
+                    precision: 6,
+                }
+                .into(),
+            );
+        });
+    }
+
+    #[test]
+    fn proof_cannot_be_submitted_twice() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                Geohash("bcd").into(),
+                None,
+                None
             ));
+            let (verifying_key, proof) = prefix_proof(1, "bcd", "bcdefg");
+            let version = AttendanceModule::rotate_verifying_key(verifying_key);
+            let submit = || {
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    Geohash("bcd").into(),
+                    version,
+                    6,
+                    proof.clone(),
+                )
+            };
 
-            
-            let signature = Dummy::default();
+            assert_ok!(submit());
+            assert_noop!(submit(), Error::<Test>::AlreadySubmitted);
+            assert_eq!(SubmissionCount::<Test>::get(key("bcd")), 1);
+        });
+    }
 
-            assert_ok!(AttendanceModule::submission_with_signature(
+    #[test]
+    fn genesis_keys_accept_submissions_without_setters() {
+        let (verifying_key, proof) = prefix_proof(1, "bcd", "bcdefg");
+        new_test_ext_with(GenesisConfig {
+            oracle_public_key: Some(Dummy::default().to_raw_vec()),
+            verifying_key: Some(verifying_key.to_vec()),
+            ..Default::default()
+        })
+        .execute_with(|| {
+            System::set_block_number(1);
+            assert_eq!(AttendanceModule::verifying_key(0), Some(verifying_key));
+            assert_ok!(AttendanceModule::create_challenge(
                 RuntimeOrigin::signed(ALICE),
+                default_namespace(),
                 Geohash("bcd").into(),
-                Geohash("bcdefg").into(),
-                signature
-                    .to_raw_vec()
-                    .try_into()
-                    .expect("signature to vector"),
+                None,
+                None
+            ));
+
+            assert_ok!(submit_signature(ALICE, "bcd", "bcdefg"));
+            assert_ok!(AttendanceModule::submission_with_proof(
+                RuntimeOrigin::signed(BOB),
+                default_namespace(),
+                Geohash("bcd").into(),
+                0,
+                6,
+                proof,
             ));
+            assert_eq!(SubmissionCount::<Test>::get(key("bcd")), 2);
         });
     }
+
+    #[test]
+    #[should_panic(expected = "genesis verifying key is not a valid ProofVerifier key")]
+    fn genesis_build_rejects_a_malformed_verifying_key() {
+        new_test_ext_with(GenesisConfig {
+            verifying_key: Some(vec![1; 8]),
+            ..Default::default()
+        });
+    }
+
     #[test]
     fn set_oracle_public_key() {
         new_test_ext().execute_with(|| {
             System::set_block_number(1);
             assert_ok!(AttendanceModule::set_oracle_public_key(
                 RuntimeOrigin::root(),
-                Dummy::default().to_raw_vec().try_into().expect("")
+                Dummy::default().to_raw_vec().try_into().expect(""),
+                false
+            ));
+        });
+    }
+
+    #[test]
+    fn keys_are_only_replaced_with_force() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let public_key = |b: u8| BoundedVec::try_from(vec![b; 32]).expect("public key");
+            let verifying_key = |salt| committed_proof("bcd", salt, "bcdefg").0;
+
+            // Set when empty, kept without force, replaced with it
+            assert_ok!(AttendanceModule::set_oracle_public_key(
+                RuntimeOrigin::root(),
+                public_key(1),
+                false
+            ));
+            assert_noop!(
+                AttendanceModule::set_oracle_public_key(
+                    RuntimeOrigin::root(),
+                    public_key(2),
+                    false
+                ),
+                Error::<Test>::KeyAlreadySet
+            );
+            assert_eq!(crate::Oracle::<Test>::get(), Some(public_key(1)));
+            assert_ok!(AttendanceModule::set_oracle_public_key(
+                RuntimeOrigin::root(),
+                public_key(2),
+                true
+            ));
+            assert_eq!(crate::Oracle::<Test>::get(), Some(public_key(2)));
+
+            assert_ok!(AttendanceModule::set_distance_verifying_key(
+                RuntimeOrigin::root(),
+                distance_verifying_key(1),
+                false
+            ));
+            assert_noop!(
+                AttendanceModule::set_distance_verifying_key(
+                    RuntimeOrigin::root(),
+                    distance_verifying_key(2),
+                    false
+                ),
+                Error::<Test>::KeyAlreadySet
+            );
+            assert_ok!(AttendanceModule::set_distance_verifying_key(
+                RuntimeOrigin::root(),
+                distance_verifying_key(2),
+                true
+            ));
+            assert_eq!(
+                crate::DistanceVerifyingKey::<Test>::get(),
+                Some(distance_verifying_key(2))
+            );
+
+            assert_ok!(AttendanceModule::set_committed_verifying_key(
+                RuntimeOrigin::root(),
+                verifying_key(1),
+                false
+            ));
+            assert_noop!(
+                AttendanceModule::set_committed_verifying_key(
+                    RuntimeOrigin::root(),
+                    verifying_key(2),
+                    false
+                ),
+                Error::<Test>::KeyAlreadySet
+            );
+            assert_ok!(AttendanceModule::set_committed_verifying_key(
+                RuntimeOrigin::root(),
+                verifying_key(2),
+                true
+            ));
+            assert_eq!(
+                crate::CommittedVerifyingKey::<Test>::get(),
+                Some(verifying_key(2))
+            );
+        });
+    }
+
+    #[test]
+    fn verifying_key_is_set_by_the_admin_origin() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let key = |seed| prefix_proof(seed, "bcd", "bcdefg").0;
+
+            assert_noop!(
+                AttendanceModule::set_verifying_key(RuntimeOrigin::signed(ALICE), key(1)),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_noop!(
+                AttendanceModule::set_oracle_public_key(
+                    RuntimeOrigin::signed(ALICE),
+                    Dummy::default().to_raw_vec().try_into().expect(""),
+                    false
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert!(ProofVerifyingKey::<Test>::get().is_empty());
+
+            assert_ok!(AttendanceModule::set_verifying_key(
+                RuntimeOrigin::root(),
+                key(1)
+            ));
+            System::assert_last_event(Event::VerifyingKeyUpdated { version: 0 }.into());
+            assert_ok!(AttendanceModule::set_verifying_key(
+                RuntimeOrigin::root(),
+                key(2)
+            ));
+            System::assert_last_event(Event::VerifyingKeyUpdated { version: 1 }.into());
+            assert_eq!(AttendanceModule::verifying_key(1), Some(key(2)));
+        });
+    }
+
+    #[test]
+    fn malformed_verifying_key_is_rejected_when_set() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let (verifying_key, _) = prefix_proof(7, "bcd", "bcdefg");
+            let truncated =
+                BoundedVec::truncate_from(verifying_key[..verifying_key.len() - 1].to_vec());
+
+            for malformed in [
+                BoundedVec::default(),
+                truncated,
+                vec![1; 8].try_into().unwrap(),
+            ] {
+                assert_noop!(
+                    AttendanceModule::set_verifying_key(RuntimeOrigin::root(), malformed),
+                    Error::<Test>::InvalidVerifyingKey
+                );
+            }
+            assert!(ProofVerifyingKey::<Test>::get().is_empty());
+        });
+    }
+
+    #[test]
+    fn verifying_key_versions_rotate_and_retire() {
+        new_test_ext().execute_with(|| {
+            let key = |b: u8| BoundedVec::try_from(vec![b; 8]).expect("verifying key");
+
+            assert_eq!(AttendanceModule::rotate_verifying_key(key(1)), 0);
+            assert_eq!(AttendanceModule::rotate_verifying_key(key(2)), 1);
+            assert_eq!(AttendanceModule::verifying_key(0), Some(key(1)));
+            assert_eq!(AttendanceModule::verifying_key(1), Some(key(2)));
+
+            // MaxVerifyingKeyVersions is 2, so version 0 is retired
+            assert_eq!(AttendanceModule::rotate_verifying_key(key(3)), 2);
+            assert_eq!(AttendanceModule::verifying_key(0), None);
+            assert_eq!(AttendanceModule::verifying_key(1), Some(key(2)));
+            assert_eq!(AttendanceModule::verifying_key(2), Some(key(3)));
+            assert_eq!(ProofVerifyingKey::<Test>::get().len(), 2);
+        });
+    }
+
+    #[test]
+    fn proof_for_retired_verifying_key_version_is_rejected() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                Geohash("bcd").into(),
+                None,
+                None
+            ));
+            let key = |b: u8| BoundedVec::try_from(vec![b; 8]).expect("verifying key");
+            for b in 0..3 {
+                AttendanceModule::rotate_verifying_key(key(b));
+            }
+
+            // Retired version
+            assert_noop!(
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    Geohash("bcd").into(),
+                    0,
+                    6,
+                    BoundedVec::default(),
+                ),
+                Error::<Test>::VerifyingKeyVersionMismatch
+            );
+
+            // Version not yet registered
+            assert_noop!(
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    Geohash("bcd").into(),
+                    3,
+                    6,
+                    BoundedVec::default(),
+                ),
+                Error::<Test>::VerifyingKeyVersionMismatch
+            );
+        });
+    }
+
+    #[test]
+    fn proof_without_a_verifying_key_is_rejected() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                Geohash("bcd").into(),
+                None,
+                None
+            ));
+            assert!(ProofVerifyingKey::<Test>::get().is_empty());
+
+            assert_noop!(
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    Geohash("bcd").into(),
+                    0,
+                    6,
+                    BoundedVec::try_from(vec![0xff; 3]).unwrap(),
+                ),
+                Error::<Test>::VerifyingKeyNotSet
+            );
+        });
+    }
+
+    #[test]
+    fn public_inputs_match_the_prover() {
+        // A geohash string on the prover and the challenge's bytes on chain must
+        // yield identical field elements, or valid proofs are silently rejected
+        for geohash in ["0", "gcpv", "u4pruydqqvj", "zzzzzzzzzzzz"] {
+            let prover_inputs: Vec<_> = geohash_prover::public_inputs(geohash, 12)
+                .iter()
+                .map(|input| commitment::commitment_bytes(*input))
+                .collect();
+            assert_eq!(
+                AttendanceModule::public_inputs(&challenge(geohash), 12),
+                prover_inputs
+            );
+        }
+    }
+
+    #[test]
+    fn proof_precision_must_meet_the_challenge() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
             ));
+            let version =
+                AttendanceModule::rotate_verifying_key(BoundedVec::try_from(vec![1; 8]).unwrap());
+            let submit = |precision| {
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge("bcd"),
+                    version,
+                    precision,
+                    BoundedVec::try_from(vec![0xff; 32]).unwrap(),
+                )
+            };
+
+            // A location less precise than the challenge cannot be inside it
+            assert_noop!(submit(2), Error::<Test>::InsufficientPrecision);
+            assert_ok!(AttendanceModule::set_precision(RuntimeOrigin::root(), 0, 6));
+            assert_noop!(submit(7), Error::<Test>::PrecisionOutOfRange);
+            // Acceptable precisions reach proof verification
+            assert_noop!(submit(3), Error::<Test>::InvalidProof);
+            assert_noop!(submit(6), Error::<Test>::InvalidProof);
         });
     }
+
+    fn set_accepted(who: u64, geohash: &'static str, precisions: Vec<u8>) -> DispatchResult {
+        AttendanceModule::set_accepted_precisions(
+            RuntimeOrigin::signed(who),
+            default_namespace(),
+            challenge(geohash),
+            BoundedVec::try_from(precisions).expect("precisions within MaxGeohashLength"),
+        )
+    }
+
+    #[test]
+    fn accepted_precisions_restrict_signed_locations() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+
+            assert_noop!(
+                set_accepted(BOB, "bcd", vec![7]),
+                Error::<Test>::NotChallengeOwner
+            );
+            assert_noop!(
+                set_accepted(ALICE, "bcf", vec![7]),
+                Error::<Test>::ChallengeNotFound
+            );
+            assert_noop!(
+                set_accepted(ALICE, "bcd", vec![0]),
+                Error::<Test>::InvalidPrecisionPolicy
+            );
+            assert_noop!(
+                set_accepted(ALICE, "bcd", vec![13]),
+                Error::<Test>::InvalidPrecisionPolicy
+            );
+
+            // Stored in ascending order without repeats
+            assert_ok!(set_accepted(ALICE, "bcd", vec![8, 7, 7]));
+            let accepted = BoundedVec::try_from(vec![7, 8]).unwrap();
+            assert_eq!(
+                AcceptedPrecisions::<Test>::get(key("bcd")),
+                Some(accepted.clone())
+            );
+            System::assert_last_event(
+                Event::AcceptedPrecisionsUpdated {
+                    namespace: default_namespace(),
+                    challenge: challenge("bcd"),
+                    precisions: accepted,
+                }
+                .into(),
+            );
+
+            // Precisions between and around the accepted ones are rejected
+            for location in ["bcdefg", "bcdefghjk", "bcdefghjkm"] {
+                assert_noop!(
+                    submit_signature(ALICE, "bcd", location),
+                    Error::<Test>::PrecisionNotAccepted
+                );
+            }
+            assert_ok!(submit_signature(ALICE, "bcd", "bcdefgh"));
+            assert_ok!(submit_signature(BOB, "bcd", "bcdefghj"));
+
+            // An empty list lifts the restriction
+            assert_ok!(set_accepted(ALICE, "bcd", vec![]));
+            assert_eq!(AcceptedPrecisions::<Test>::get(key("bcd")), None);
+            assert_ok!(submit_signature(CHARLIE, "bcd", "bcdefg"));
+        });
+    }
+
+    #[test]
+    fn accepted_precisions_restrict_proofs_until_closed() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+            assert_ok!(set_accepted(ALICE, "bcd", vec![7]));
+            let version =
+                AttendanceModule::rotate_verifying_key(BoundedVec::try_from(vec![1; 8]).unwrap());
+            let submit = |precision| {
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge("bcd"),
+                    version,
+                    precision,
+                    BoundedVec::try_from(vec![0xff; 32]).unwrap(),
+                )
+            };
+            assert_noop!(submit(6), Error::<Test>::PrecisionNotAccepted);
+            assert_noop!(submit(7), Error::<Test>::InvalidProof);
+
+            // Closing the challenge drops its restriction with it
+            assert_ok!(AttendanceModule::close_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd")
+            ));
+            assert_eq!(AcceptedPrecisions::<Test>::get(key("bcd")), None);
+        });
+    }
+
+    #[test]
+    fn valid_proof_is_accepted_and_invalid_proof_rejected() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+            let (verifying_key, proof) = prefix_proof(7, "bcd", "bcdefg");
+            let version = AttendanceModule::rotate_verifying_key(verifying_key);
+            let submit = |precision: u32, proof: BoundedVec<u8, MaxProofLength>| {
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge("bcd"),
+                    version,
+                    precision,
+                    proof,
+                )
+            };
+
+            // A well-formed proof from another setup of the same circuit
+            let (_, other_proof) = prefix_proof(8, "bcd", "bcdefg");
+            assert_noop!(submit(6, other_proof), Error::<Test>::InvalidProof);
+            // The valid proof, claiming another precision
+            assert_noop!(submit(7, proof.clone()), Error::<Test>::InvalidProof);
+
+            assert_ok!(submit(6, proof));
+            assert!(Submissions::<Test>::contains_key(key("bcd"), ALICE));
+        });
+    }
+
+    #[test]
+    fn submission_mints_the_configured_reward_amount() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            RewardAmount::set(42);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+
+            assert_ok!(submit_signature(ALICE, "bcd", "bcdefg"));
+            assert_eq!(minted(&ALICE), 42);
+        });
+    }
+
+    #[test]
+    fn rewards_are_only_minted_for_accepted_submissions() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+            let (verifying_key, proof) = prefix_proof(7, "bcd", "bcdefg");
+            let version = AttendanceModule::rotate_verifying_key(verifying_key);
+            let submit_proof = |who, precision| {
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(who),
+                    default_namespace(),
+                    challenge("bcd"),
+                    version,
+                    precision,
+                    proof.clone(),
+                )
+            };
+
+            // Submissions failing a check, before or after verification, mint nothing
+            assert_noop!(
+                submit_signature(ALICE, "bcd", "gcpvj0"),
+                Error::<Test>::InvalidGeohash
+            );
+            assert_noop!(submit_proof(BOB, 7), Error::<Test>::InvalidProof);
+            assert_eq!((mints(&ALICE), mints(&BOB)), (0, 0));
+
+            assert_ok!(submit_signature(ALICE, "bcd", "bcdefg"));
+            assert_ok!(submit_proof(BOB, 6));
+            assert_eq!((mints(&ALICE), mints(&BOB)), (1, 1));
+            assert_eq!(minted(&ALICE), RewardAmount::get());
+
+            assert_noop!(
+                submit_signature(ALICE, "bcd", "bcdefg"),
+                Error::<Test>::AlreadySubmitted
+            );
+            assert_eq!(mints(&ALICE), 1);
+        });
+    }
+
+    #[test]
+    fn raw_prover_proof_verifies_in_the_pallet() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let (verifying_key, proof) = prefix_proof(7, "bcd", "bcdefg");
+            assert_eq!(proof.len(), geohash_prover::UNCOMPRESSED_PROOF_SIZE);
+            assert!(proof.len() <= MaxProofLength::get() as usize);
+
+            // The proof system deserializes the raw proof and accepts it for the inputs the
+            // pallet computes
+            let public_inputs = AttendanceModule::public_inputs(&challenge("bcd"), 6);
+            assert!(matches!(
+                Groth16Bn254::verify(&verifying_key, &public_inputs, &proof),
+                Ok(true)
+            ));
+
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+            let version = AttendanceModule::rotate_verifying_key(verifying_key);
+            assert_ok!(AttendanceModule::submission_with_proof(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                version,
+                6,
+                proof,
+            ));
+        });
+    }
+
+    #[test]
+    fn malformed_proof_is_rejected_without_panicking() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+            let version =
+                AttendanceModule::rotate_verifying_key(BoundedVec::try_from(vec![1; 8]).unwrap());
+
+            assert_noop!(
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge("bcd"),
+                    version,
+                    6,
+                    BoundedVec::try_from(vec![0xff; 32]).unwrap(),
+                ),
+                Error::<Test>::InvalidProof
+            );
+        });
+    }
+
+    #[test]
+    fn proof_for_another_challenge_length_is_rejected_before_verifying() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            // A key for 3-character challenges expects their bytes and the precision
+            let (verifying_key, proof) = prefix_proof(7, "bcd", "bcdefg");
+            assert_eq!(Groth16Bn254::public_input_count(&verifying_key), Some(4));
+            assert_eq!(Groth16Bn254::public_input_count(&[1; 8]), None);
+            let version = AttendanceModule::rotate_verifying_key(verifying_key);
+            for geohash in ["bc", "bcd", "bcde"] {
+                assert_ok!(AttendanceModule::create_challenge(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge(geohash),
+                    None,
+                    None
+                ));
+            }
+            let submit = |geohash| {
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge(geohash),
+                    version,
+                    6,
+                    proof.clone(),
+                )
+            };
+
+            // A longer and a shorter challenge give one public input too many or too few
+            assert_noop!(submit("bcde"), Error::<Test>::PublicInputCountMismatch);
+            assert_noop!(submit("bc"), Error::<Test>::PublicInputCountMismatch);
+            assert_ok!(submit("bcd"));
+        });
+    }
+
+    #[test]
+    fn submissions_are_rejected_once_capacity_is_reached() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                Geohash("bcd").into(),
+                Some(2),
+                None
+            ));
+
+            assert_ok!(submit_signature(ALICE, "bcd", "bcdefg"));
+            assert_eq!(SubmissionCount::<Test>::get(key("bcd")), 1);
+            assert!(!System::events().iter().any(|record| matches!(
+                record.event,
+                RuntimeEvent::AttendanceModule(Event::ChallengeCapacityReached { .. })
+            )));
+
+            assert_ok!(submit_signature(BOB, "bcd", "bcdefg"));
+            System::assert_has_event(
+                Event::ChallengeCapacityReached {
+                    namespace: default_namespace(),
+                    challenge: Geohash("bcd").into(),
+                }
+                .into(),
+            );
+
+            assert_noop!(
+                submit_signature(CHARLIE, "bcd", "bcdefg"),
+                Error::<Test>::ChallengeFull
+            );
+            assert_eq!(SubmissionCount::<Test>::get(key("bcd")), 2);
+        });
+    }
+
+    #[test]
+    fn submissions_are_unlimited_without_capacity() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                Geohash("bcd").into(),
+                None,
+                None
+            ));
+
+            for who in [ALICE, BOB, CHARLIE] {
+                assert_ok!(submit_signature(who, "bcd", "bcdefg"));
+            }
+            assert_eq!(SubmissionCount::<Test>::get(key("bcd")), 3);
+        });
+    }
+
+    #[test]
+    fn submission_to_unknown_challenge_is_rejected() {
+        new_test_ext().execute_with(|| {
+            set_oracle();
+            assert_noop!(
+                submit_signature(ALICE, "bcd", "bcdefg"),
+                Error::<Test>::ChallengeNotFound
+            );
+        });
+    }
+
+    #[test]
+    fn submittable_challenge_is_loaded() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                Geohash("bcd").into(),
+                Some(1),
+                Some(5)
+            ));
+            assert_eq!(
+                submittable("bcd").ok(),
+                Some(ChallengeInfo {
+                    owner: ALICE,
+                    capacity: Some(1),
+                    expires_at: Some(5),
+                })
+            );
+        });
+    }
+
+    #[test]
+    fn unknown_challenge_is_not_submittable() {
+        new_test_ext().execute_with(|| {
+            assert!(matches!(
+                submittable("bcd"),
+                Err(Error::<Test>::ChallengeNotFound)
+            ));
+        });
+    }
+
+    #[test]
+    fn expired_challenge_is_not_submittable() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(create_expiring(ALICE, "bcd", 5));
+
+            System::set_block_number(5);
+            assert!(submittable("bcd").is_ok());
+
+            // Not pruned yet, as when the expiry spilled to a later block
+            System::set_block_number(6);
+            assert!(Challenges::<Test>::contains_key(key("bcd")));
+            assert!(matches!(
+                submittable("bcd"),
+                Err(Error::<Test>::ChallengeExpired)
+            ));
+            assert_noop!(
+                submit_signature(BOB, "bcd", "bcde"),
+                Error::<Test>::ChallengeExpired
+            );
+        });
+    }
+
+    #[test]
+    fn full_challenge_is_not_submittable() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                Geohash("bcd").into(),
+                Some(1),
+                None
+            ));
+            assert_ok!(submit_signature(ALICE, "bcd", "bcde"));
+
+            assert!(matches!(
+                submittable("bcd"),
+                Err(Error::<Test>::ChallengeFull)
+            ));
+        });
+    }
+
+    #[test]
+    fn expiring_challenge_is_indexed_for_the_block_after_expiry() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(create_expiring(ALICE, "bcd", 10));
+
+            assert_eq!(ChallengeExpiries::<Test>::get(11).to_vec(), vec![key("bcd")]);
+            assert_eq!(
+                Challenges::<Test>::get(key("bcd")).map(|info| info.expires_at),
+                Some(Some(10))
+            );
+        });
+    }
+
+    #[test]
+    fn expiry_in_the_past_is_rejected() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(5);
+            assert_noop!(create_expiring(ALICE, "bcd", 4), Error::<Test>::InvalidExpiry);
+        });
+    }
+
+    #[test]
+    fn full_expiry_block_spills_to_the_next_block() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            // MaxExpiringPerBlock is 2
+            assert_ok!(create_expiring(ALICE, "bcd", 10));
+            assert_ok!(create_expiring(ALICE, "bce", 10));
+            assert_ok!(create_expiring(ALICE, "bcf", 10));
+
+            assert_eq!(
+                ChallengeExpiries::<Test>::get(11).to_vec(),
+                vec![key("bcd"), key("bce")]
+            );
+            assert_eq!(ChallengeExpiries::<Test>::get(12).to_vec(), vec![key("bcf")]);
+        });
+    }
+
+    #[test]
+    fn closing_a_challenge_removes_it_from_the_index() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(create_expiring(ALICE, "bcd", 10));
+            assert_ok!(create_expiring(ALICE, "bce", 10));
+            assert_ok!(create_expiring(ALICE, "bcf", 10));
+
+            assert_noop!(
+                AttendanceModule::close_challenge(
+                    RuntimeOrigin::signed(BOB),
+                    default_namespace(),
+                    challenge("bcf")
+                ),
+                Error::<Test>::NotChallengeOwner
+            );
+
+            // Spilled entry
+            assert_ok!(AttendanceModule::close_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcf")
+            ));
+            assert!(!ChallengeExpiries::<Test>::contains_key(12));
+            assert!(!Challenges::<Test>::contains_key(key("bcf")));
+            System::assert_has_event(
+                Event::ChallengeClosed {
+                    namespace: default_namespace(),
+                    challenge: challenge("bcf"),
+                }
+                .into(),
+            );
+
+            assert_ok!(AttendanceModule::close_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd")
+            ));
+            assert_eq!(ChallengeExpiries::<Test>::get(11).to_vec(), vec![key("bce")]);
+        });
+    }
+
+    #[test]
+    fn expired_challenges_are_pruned_on_initialize() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(create_expiring(ALICE, "bcd", 10));
+            assert_ok!(create_expiring(ALICE, "bce", 10));
+            assert_ok!(create_expiring(ALICE, "bcf", 10));
+
+            AttendanceModule::on_initialize(10);
+            assert!(Challenges::<Test>::contains_key(key("bcd")));
+
+            System::set_block_number(11);
+            AttendanceModule::on_initialize(11);
+            assert!(!Challenges::<Test>::contains_key(key("bcd")));
+            assert!(!Challenges::<Test>::contains_key(key("bce")));
+            assert!(Challenges::<Test>::contains_key(key("bcf")));
+            assert!(!ChallengeExpiries::<Test>::contains_key(11));
+            System::assert_has_event(
+                Event::ChallengePruned {
+                    namespace: default_namespace(),
+                    challenge: challenge("bcd"),
+                }
+                .into(),
+            );
+
+            System::set_block_number(12);
+            AttendanceModule::on_initialize(12);
+            assert!(!Challenges::<Test>::contains_key(key("bcf")));
+            assert!(!ChallengeExpiries::<Test>::contains_key(12));
+        });
+    }
+
+    #[test]
+    fn challenge_count_follows_creation_closing_and_pruning() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_eq!(AttendanceModule::challenge_count(), 0);
+
+            assert_ok!(create_expiring(ALICE, "bcd", 10));
+            assert_ok!(create_expiring(ALICE, "bce", 20));
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(BOB),
+                namespace("org-a"),
+                challenge("bcd"),
+                None,
+                None
+            ));
+            assert_eq!(AttendanceModule::challenge_count(), 3);
+
+            // Failed creations are not counted
+            assert_noop!(
+                create_expiring(BOB, "bcd", 10),
+                Error::<Test>::ChallengeAlreadyExists
+            );
+            assert_eq!(AttendanceModule::challenge_count(), 3);
+
+            assert_ok!(AttendanceModule::close_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bce")
+            ));
+            assert_eq!(AttendanceModule::challenge_count(), 2);
+
+            System::set_block_number(11);
+            AttendanceModule::on_initialize(11);
+            assert_eq!(AttendanceModule::challenge_count(), 1);
+            assert_eq!(
+                AttendanceModule::challenge_count(),
+                Challenges::<Test>::iter_keys().count() as u32
+            );
+        });
+    }
+
+    #[test]
+    fn expired_challenge_submissions_are_archived_across_blocks() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(create_expiring(ALICE, "bcd", 10));
+            assert_ok!(create_expiring(ALICE, "bce", 10));
+            let attendees = 10..15u64;
+            for who in attendees.clone() {
+                assert_ok!(submit_signature(who, "bcd", "bcde"));
+            }
+
+            // The challenge without submissions is archived at once, the other one clears
+            // `MaxArchivedPerBlock` entries per block
+            System::set_block_number(11);
+            AttendanceModule::on_initialize(11);
+            System::assert_has_event(
+                Event::ChallengeArchived {
+                    namespace: default_namespace(),
+                    challenge: challenge("bce"),
+                }
+                .into(),
+            );
+            assert!(!SubmissionCount::<Test>::contains_key(key("bcd")));
+            assert!(PendingArchival::<Test>::contains_key(key("bcd")));
+            assert_eq!(
+                Submissions::<Test>::iter_prefix(key("bcd")).count(),
+                5 - MaxArchivedPerBlock::get() as usize
+            );
+            assert_noop!(
+                create_expiring(BOB, "bcd", 20),
+                Error::<Test>::ChallengeBeingArchived
+            );
+
+            System::set_block_number(12);
+            AttendanceModule::on_initialize(12);
+            System::assert_has_event(
+                Event::ChallengeArchived {
+                    namespace: default_namespace(),
+                    challenge: challenge("bcd"),
+                }
+                .into(),
+            );
+            assert!(!PendingArchival::<Test>::contains_key(key("bcd")));
+            assert_eq!(Submissions::<Test>::iter_prefix(key("bcd")).count(), 0);
+            for who in attendees {
+                assert!(AttendanceHistory::<Test>::get(who).is_empty());
+            }
+
+            // Once archived the challenge can be created again and attended afresh
+            assert_ok!(create_expiring(BOB, "bcd", 20));
+            assert_ok!(submit_signature(10, "bcd", "bcde"));
+        });
+    }
+
+    #[test]
+    fn challenge_with_as_many_submissions_as_the_limit_is_archived_in_one_block() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(create_expiring(ALICE, "bcd", 10));
+            for who in 0..MaxArchivedPerBlock::get() as u64 {
+                assert_ok!(submit_signature(10 + who, "bcd", "bcde"));
+            }
+
+            System::set_block_number(11);
+            AttendanceModule::on_initialize(11);
+            System::assert_has_event(
+                Event::ChallengeArchived {
+                    namespace: default_namespace(),
+                    challenge: challenge("bcd"),
+                }
+                .into(),
+            );
+            assert!(!PendingArchival::<Test>::contains_key(key("bcd")));
+            assert_eq!(Submissions::<Test>::iter_prefix(key("bcd")).count(), 0);
+        });
+    }
+
+    #[test]
+    fn closed_challenge_submissions_are_archived_before_re_creation() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(create_expiring(ALICE, "bcd", 100));
+            let attendees = 10..15u64;
+            for who in attendees.clone() {
+                assert_ok!(submit_signature(who, "bcd", "bcde"));
+            }
+
+            assert_ok!(AttendanceModule::close_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd")
+            ));
+            assert!(PendingArchival::<Test>::contains_key(key("bcd")));
+            assert_noop!(
+                create_expiring(ALICE, "bcd", 100),
+                Error::<Test>::ChallengeBeingArchived
+            );
+
+            System::set_block_number(2);
+            AttendanceModule::on_initialize(2);
+            assert_eq!(
+                Submissions::<Test>::iter_prefix(key("bcd")).count(),
+                5 - MaxArchivedPerBlock::get() as usize
+            );
+            System::set_block_number(3);
+            AttendanceModule::on_initialize(3);
+            System::assert_has_event(
+                Event::ChallengeArchived {
+                    namespace: default_namespace(),
+                    challenge: challenge("bcd"),
+                }
+                .into(),
+            );
+            assert_eq!(Submissions::<Test>::iter_prefix(key("bcd")).count(), 0);
+            for who in attendees.clone() {
+                assert!(!SubmittedAt::<Test>::contains_key(key("bcd"), who));
+                assert!(AttendanceHistory::<Test>::get(who).is_empty());
+            }
+
+            // The re-created challenge is attended afresh by earlier attendees
+            assert_ok!(create_expiring(ALICE, "bcd", 100));
+            for who in attendees {
+                assert_ok!(submit_signature(who, "bcd", "bcde"));
+            }
+        });
+    }
+
+    #[test]
+    fn packed_challenge_keys_are_shorter_and_transparent() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            let (geohash, location) = ("gcpvj0dj4y8", "gcpvj0dj4y8w");
+            let raw_key = (default_namespace(), challenge(geohash));
+            let raw_key_length = Challenges::<Test>::hashed_key_for(&raw_key).len();
+
+            PackedChallengeKeys::set(true);
+            let key = key(geohash);
+            assert_eq!(key.1.len(), 7);
+            assert_eq!(AttendanceModule::challenge_of_key(&key), raw_key);
+            // Each map keyed by challenge saves 4 of the 11 bytes of this key
+            assert_eq!(
+                raw_key_length - Challenges::<Test>::hashed_key_for(&key).len(),
+                4
+            );
+            assert_eq!(
+                Submissions::<Test>::hashed_key_for(&raw_key, BOB).len()
+                    - Submissions::<Test>::hashed_key_for(&key, BOB).len(),
+                4
+            );
+
+            // Calls and events still take and carry the geohash
+            assert_ok!(create_expiring(ALICE, geohash, 10));
+            assert_ok!(submit_signature(BOB, geohash, location));
+            assert!(Challenges::<Test>::contains_key(&key));
+            assert!(!Challenges::<Test>::contains_key(&raw_key));
+            assert!(Submissions::<Test>::contains_key(&key, BOB));
+            assert_eq!(SubmissionCount::<Test>::get(&key), 1);
+            assert_eq!(AttendanceHistory::<Test>::get(BOB).to_vec(), vec![raw_key]);
+            assert_noop!(
+                submit_signature(BOB, geohash, location),
+                Error::<Test>::AlreadySubmitted
+            );
+
+            System::set_block_number(11);
+            AttendanceModule::on_initialize(11);
+            System::assert_has_event(
+                Event::ChallengeArchived {
+                    namespace: default_namespace(),
+                    challenge: challenge(geohash),
+                }
+                .into(),
+            );
+            assert!(!Submissions::<Test>::contains_key(&key, BOB));
+
+            // A challenge that is not a geohash has no packed key and is never found
+            assert!(
+                AttendanceModule::challenge_key(&default_namespace(), &challenge("gcpa"))
+                    .1
+                    .is_empty()
+            );
+            assert_noop!(
+                submit_signature(BOB, "gcpa", "gcpab"),
+                Error::<Test>::ChallengeNotFound
+            );
+        });
+    }
+
+    #[test]
+    fn over_length_geohash_is_rejected_not_truncated() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let max = MaxGeohashLength::get() as usize;
+
+            // A geohash of exactly `MaxGeohashLength` is accepted unchanged
+            let longest = BoundedVec::<u8, MaxGeohashLength>::try_from(vec![b'b'; max]).unwrap();
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                longest.clone(),
+                None,
+                None
+            ));
+            assert_eq!(
+                Challenges::<Test>::iter_keys().collect::<Vec<_>>(),
+                vec![(default_namespace(), longest.clone())]
+            );
+
+            // One byte longer cannot be converted into a challenge
+            let over_length = vec![b'b'; max + 1];
+            assert!(BoundedVec::<u8, MaxGeohashLength>::try_from(over_length.clone()).is_err());
+
+            // Nor does an encoded call carrying it decode, so it is rejected before dispatch
+            // instead of being truncated to the longest prefix
+            let call = RuntimeCall::AttendanceModule(crate::Call::create_challenge {
+                namespace: default_namespace(),
+                challenge: longest,
+                capacity: None,
+                expires_at: None,
+            })
+            .encode();
+            let mut encoded = call[..2].to_vec();
+            default_namespace().encode_to(&mut encoded);
+            over_length.encode_to(&mut encoded);
+            (None::<u32>, None::<u64>).encode_to(&mut encoded);
+            assert!(RuntimeCall::decode(&mut &encoded[..]).is_err());
+
+            let mut truncated = call[..2].to_vec();
+            default_namespace().encode_to(&mut truncated);
+            over_length[..max].to_vec().encode_to(&mut truncated);
+            (None::<u32>, None::<u64>).encode_to(&mut truncated);
+            assert_eq!(truncated, call);
+        });
+    }
+
+    #[test]
+    fn geohash_from_str_validates_the_geohash() {
+        new_test_ext().execute_with(|| {
+            for geohash in ["gcpvj0", "gcpvj0dj4y8w"] {
+                assert_eq!(
+                    AttendanceModule::geohash_from_str(geohash).ok(),
+                    Some(challenge(geohash))
+                );
+            }
+            for invalid in ["", "GCPVJ0", "gcpa", "gcp vj"] {
+                assert!(matches!(
+                    AttendanceModule::geohash_from_str(invalid),
+                    Err(Error::<Test>::InvalidGeohash)
+                ));
+            }
+            assert!(matches!(
+                AttendanceModule::geohash_from_str("gcpvj0dj4y8wb"),
+                Err(Error::<Test>::GeohashTooLong)
+            ));
+        });
+    }
+
+    #[test]
+    fn over_length_location_is_rejected_with_a_clear_error() {
+        new_test_ext().execute_with(|| {
+            let max = MaxGeohashLength::get() as usize;
+            assert_eq!(
+                AttendanceModule::bounded_geohash(vec![b'b'; max]).ok(),
+                Some(BoundedVec::try_from(vec![b'b'; max]).unwrap())
+            );
+            assert!(matches!(
+                AttendanceModule::bounded_geohash(vec![b'b'; max + 1]),
+                Err(Error::<Test>::GeohashTooLong)
+            ));
+
+            // A crafted submission carrying an over-length location fails to decode
+            let call = RuntimeCall::AttendanceModule(crate::Call::submission_with_signature {
+                namespace: default_namespace(),
+                challenge: challenge("bcd"),
+                location: challenge("bcdefg"),
+                payload_version: PAYLOAD_VERSION,
+                signature: BoundedVec::default(),
+            })
+            .encode();
+            let mut encoded = call[..2].to_vec();
+            default_namespace().encode_to(&mut encoded);
+            challenge("bcd").encode_to(&mut encoded);
+            vec![b'b'; max + 1].encode_to(&mut encoded);
+            PAYLOAD_VERSION.encode_to(&mut encoded);
+            BoundedVec::<u8, sp_core::ConstU32<64>>::default().encode_to(&mut encoded);
+            assert!(RuntimeCall::decode(&mut &encoded[..]).is_err());
+        });
+    }
+
+    fn submit_distance(radius: u64) -> DispatchResult {
+        submit_distance_around((141_500_000, 179_900_000), radius)
+    }
+
+    fn submit_distance_around(center: (u64, u64), radius: u64) -> DispatchResult {
+        AttendanceModule::submission_with_distance_proof(
+            RuntimeOrigin::signed(ALICE),
+            default_namespace(),
+            challenge("bcd"),
+            center.0,
+            center.1,
+            radius,
+            BoundedVec::default(),
+        )
+    }
+
+    /// A verifying key of the distance circuit, deterministic in `seed`.
+    fn distance_verifying_key(seed: u64) -> BoundedVec<u8, ConstU32<2048>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let circuit = geohash_prover::distance::DistanceCircuit::new(
+            (141_500_000, 179_900_000),
+            (141_500_000, 179_900_000),
+            100,
+        );
+        let (_, verifying_key) =
+            geohash_prover::distance::setup_distance_groth16(&mut rng, circuit).expect("setup");
+
+        let mut verifying_key_bytes = Vec::new();
+        verifying_key
+            .serialize_uncompressed(&mut verifying_key_bytes)
+            .expect("serialized verifying key");
+        verifying_key_bytes
+            .try_into()
+            .expect("verifying key within bound")
+    }
+
+    #[test]
+    fn distance_proof_is_checked_against_the_distance_verifying_key() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+            assert_noop!(submit_distance(100), Error::<Test>::DistanceVerifyingKeyNotSet);
+
+            assert_noop!(
+                AttendanceModule::set_distance_verifying_key(
+                    RuntimeOrigin::signed(ALICE),
+                    BoundedVec::default(),
+                    false
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(AttendanceModule::set_distance_verifying_key(
+                RuntimeOrigin::root(),
+                distance_verifying_key(1),
+                false
+            ));
+            System::assert_last_event(Event::DistanceVerifyingKeyUpdated.into());
+
+            // A malformed proof is rejected rather than panicking
+            assert_noop!(submit_distance(100), Error::<Test>::InvalidProof);
+        });
+    }
+
+    #[test]
+    fn malformed_distance_verifying_key_is_rejected_when_set() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let verifying_key = distance_verifying_key(7);
+            let truncated =
+                BoundedVec::truncate_from(verifying_key[..verifying_key.len() - 1].to_vec());
+
+            for malformed in [
+                BoundedVec::default(),
+                truncated,
+                vec![1; 8].try_into().unwrap(),
+            ] {
+                assert_noop!(
+                    AttendanceModule::set_distance_verifying_key(
+                        RuntimeOrigin::root(),
+                        malformed,
+                        true
+                    ),
+                    Error::<Test>::InvalidVerifyingKey
+                );
+            }
+            assert_eq!(crate::DistanceVerifyingKey::<Test>::get(), None);
+        });
+    }
+
+    #[test]
+    fn distance_proof_radius_is_bounded() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+            assert_ok!(AttendanceModule::set_distance_verifying_key(
+                RuntimeOrigin::root(),
+                distance_verifying_key(1),
+                false
+            ));
+            assert_noop!(
+                submit_distance(MaxDistanceRadius::get() + 1),
+                Error::<Test>::RadiusTooLarge
+            );
+            assert_noop!(
+                submit_distance(MaxDistanceRadius::get()),
+                Error::<Test>::InvalidProof
+            );
+        });
+    }
+
+    #[test]
+    fn distance_proof_center_must_be_encoded_coordinates() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+            assert_ok!(AttendanceModule::set_distance_verifying_key(
+                RuntimeOrigin::root(),
+                distance_verifying_key(1),
+                false
+            ));
+
+            // Sydney, encoded by the prover, reaches verification
+            let sydney = geohash_prover::distance::to_fixed_point(-33.8688, 151.2093)
+                .expect("valid coordinates");
+            assert_noop!(
+                submit_distance_around(sydney, 100),
+                Error::<Test>::InvalidProof
+            );
+
+            // Its latitude in millionths of a degree, cast naively, is rejected
+            let naive = ((-33_868_800i64) as u64, sydney.1);
+            assert_noop!(
+                submit_distance_around(naive, 100),
+                Error::<Test>::InvalidCoordinates
+            );
+            assert_noop!(
+                submit_distance_around((180_000_000, 360_000_001), 100),
+                Error::<Test>::InvalidCoordinates
+            );
+        });
+    }
+
+    /// A verifying key of the committed prefix circuit for `challenge` and locations as long
+    /// as `location`, with a proof that `location` starts with the challenge committed to
+    /// under `salt`
+    fn committed_proof(
+        challenge: &str,
+        salt: u64,
+        location: &str,
+    ) -> (BoundedVec<u8, ConstU32<2048>>, BoundedVec<u8, MaxProofLength>) {
+        let mut rng = StdRng::seed_from_u64(salt);
+        let circuit = CommittedPrefixCircuit::new_from_str(challenge, salt.into(), location);
+        let (proving_key, verifying_key) =
+            commitment::setup_committed_groth16(&mut rng, circuit.clone()).expect("setup");
+        let proof =
+            commitment::create_committed_proof(&proving_key, circuit, &mut rng).expect("proof");
+
+        let (mut verifying_key_bytes, mut proof_bytes) = (Vec::new(), Vec::new());
+        verifying_key
+            .serialize_uncompressed(&mut verifying_key_bytes)
+            .expect("serialized verifying key");
+        proof
+            .serialize_uncompressed(&mut proof_bytes)
+            .expect("serialized proof");
+        (
+            verifying_key_bytes.try_into().expect("verifying key within bound"),
+            proof_bytes.try_into().expect("proof within MaxProofLength"),
+        )
+    }
+
+    fn commit(challenge: &str, salt: u64) -> [u8; 32] {
+        commitment::commitment_bytes(commitment::commit(challenge, salt.into()))
+    }
+
+    #[test]
+    fn committed_challenges_are_closed_by_their_owner() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let commitment = commit("bcd", 42);
+            assert_noop!(
+                AttendanceModule::create_committed_challenge(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    [0xff; 32]
+                ),
+                Error::<Test>::InvalidCommitment
+            );
+            assert_ok!(AttendanceModule::create_committed_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                commitment
+            ));
+            assert!(System::events().iter().any(|record| matches!(
+                &record.event,
+                RuntimeEvent::AttendanceModule(Event::CommittedChallengeCreated { who, .. })
+                    if *who == ALICE
+            )));
+            assert_noop!(
+                AttendanceModule::create_committed_challenge(
+                    RuntimeOrigin::signed(BOB),
+                    default_namespace(),
+                    commitment
+                ),
+                Error::<Test>::ChallengeAlreadyExists
+            );
+            // The same commitment is independent in another namespace
+            assert_ok!(AttendanceModule::create_committed_challenge(
+                RuntimeOrigin::signed(BOB),
+                namespace("bob"),
+                commitment
+            ));
+
+            assert_noop!(
+                AttendanceModule::close_committed_challenge(
+                    RuntimeOrigin::signed(BOB),
+                    default_namespace(),
+                    commitment
+                ),
+                Error::<Test>::NotChallengeOwner
+            );
+            assert_ok!(AttendanceModule::close_committed_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                commitment
+            ));
+            assert!(!CommittedChallenges::<Test>::contains_key((
+                default_namespace(),
+                commitment
+            )));
+            assert!(CommittedChallenges::<Test>::contains_key((
+                namespace("bob"),
+                commitment
+            )));
+        });
+    }
+
+    #[test]
+    fn committed_proof_is_verified_against_the_commitment() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let commitment = commit("bcd", 42);
+            assert_ok!(AttendanceModule::create_committed_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                commitment
+            ));
+            let (verifying_key, proof) = committed_proof("bcd", 42, "bcdefg");
+            let submit = |commitment, precision, proof: &BoundedVec<u8, MaxProofLength>| {
+                AttendanceModule::submission_with_committed_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    commitment,
+                    precision,
+                    proof.clone(),
+                )
+            };
+            assert_noop!(
+                submit(commitment, 6, &proof),
+                Error::<Test>::CommittedVerifyingKeyNotSet
+            );
+
+            assert_noop!(
+                AttendanceModule::set_committed_verifying_key(
+                    RuntimeOrigin::signed(ALICE),
+                    verifying_key.clone(),
+                    false
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(AttendanceModule::set_committed_verifying_key(
+                RuntimeOrigin::root(),
+                verifying_key,
+                false
+            ));
+            System::assert_last_event(Event::CommittedVerifyingKeyUpdated.into());
+
+            assert_noop!(
+                submit(commit("bcd", 43), 6, &proof),
+                Error::<Test>::ChallengeNotFound
+            );
+            // The valid proof, claiming another precision, and a proof for another salt
+            assert_noop!(submit(commitment, 7, &proof), Error::<Test>::InvalidProof);
+            let (_, other_proof) = committed_proof("bcd", 43, "bcdefg");
+            assert_noop!(
+                submit(commitment, 6, &other_proof),
+                Error::<Test>::InvalidProof
+            );
+
+            assert_ok!(submit(commitment, 6, &proof));
+            assert!(CommittedSubmissions::<Test>::contains_key(
+                (default_namespace(), commitment),
+                ALICE
+            ));
+            assert!(System::events().iter().any(|record| matches!(
+                &record.event,
+                RuntimeEvent::AttendanceModule(Event::CommittedProofAccepted {
+                    precision: 6,
+                    ..
+                })
+            )));
+            assert_noop!(
+                submit(commitment, 6, &proof),
+                Error::<Test>::AlreadySubmitted
+            );
+        });
+    }
+
+    #[test]
+    fn truncated_committed_verifying_key_is_rejected_when_set() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let (verifying_key, _) = committed_proof("bcd", 42, "bcdefg");
+            let truncated =
+                BoundedVec::truncate_from(verifying_key[..verifying_key.len() - 1].to_vec());
+
+            assert_noop!(
+                AttendanceModule::set_committed_verifying_key(
+                    RuntimeOrigin::root(),
+                    truncated,
+                    false
+                ),
+                Error::<Test>::InvalidVerifyingKey
+            );
+            assert_eq!(crate::CommittedVerifyingKey::<Test>::get(), None);
+        });
+    }
+
+    #[test]
+    fn submissions_are_rejected_while_paused() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                challenge("bcd"),
+                None,
+                None
+            ));
+
+            assert_noop!(
+                AttendanceModule::pause(RuntimeOrigin::signed(ALICE)),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(AttendanceModule::pause(RuntimeOrigin::root()));
+            System::assert_last_event(Event::Paused.into());
+
+            assert_noop!(
+                submit_signature(ALICE, "bcd", "bcdefg"),
+                Error::<Test>::PalletPaused
+            );
+            assert_noop!(
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge("bcd"),
+                    0,
+                    6,
+                    BoundedVec::default(),
+                ),
+                Error::<Test>::PalletPaused
+            );
+            assert_noop!(submit_distance(100), Error::<Test>::PalletPaused);
+
+            assert_ok!(AttendanceModule::unpause(RuntimeOrigin::root()));
+            System::assert_last_event(Event::Unpaused.into());
+            assert_ok!(submit_signature(ALICE, "bcd", "bcdefg"));
+        });
+    }
+
+    #[test]
+    fn precision_policy_is_set_by_the_admin_origin() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_eq!(MinPrecision::<Test>::get(), 0);
+            assert_eq!(MaxPrecision::<Test>::get(), MaxGeohashLength::get());
+
+            assert_noop!(
+                AttendanceModule::set_precision(RuntimeOrigin::signed(ALICE), 3, 8),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_noop!(
+                AttendanceModule::set_precision(RuntimeOrigin::root(), 8, 3),
+                Error::<Test>::InvalidPrecisionPolicy
+            );
+            assert_noop!(
+                AttendanceModule::set_precision(
+                    RuntimeOrigin::root(),
+                    3,
+                    MaxGeohashLength::get() + 1
+                ),
+                Error::<Test>::InvalidPrecisionPolicy
+            );
+
+            assert_ok!(AttendanceModule::set_precision(RuntimeOrigin::root(), 3, 8));
+            System::assert_last_event(Event::PrecisionUpdated { min: 3, max: 8 }.into());
+            assert_eq!(MinPrecision::<Test>::get(), 3);
+            assert_eq!(MaxPrecision::<Test>::get(), 8);
+        });
+    }
+
+    #[test]
+    fn precision_policy_applies_to_challenges_and_locations() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            let create = |geohash| {
+                AttendanceModule::create_challenge(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge(geohash),
+                    None,
+                    None,
+                )
+            };
+            assert_ok!(create("bc"));
+            assert_ok!(AttendanceModule::set_precision(RuntimeOrigin::root(), 3, 6));
+
+            // Challenges shorter or longer than the policy can no longer be created
+            assert_noop!(create("gc"), Error::<Test>::PrecisionOutOfRange);
+            assert_noop!(create("gcpvj0d"), Error::<Test>::PrecisionOutOfRange);
+            assert_ok!(create("bcd"));
+            assert_ok!(create("gcpvj0"));
+
+            // Signed locations must be within the policy too
+            assert_noop!(
+                submit_signature(ALICE, "bcd", "bcdefgh"),
+                Error::<Test>::PrecisionOutOfRange
+            );
+            assert_ok!(submit_signature(ALICE, "bcd", "bcdefg"));
+
+            // An existing challenge coarser than the policy only admits locations within it
+            assert_noop!(
+                submit_signature(BOB, "bc", "bc"),
+                Error::<Test>::PrecisionOutOfRange
+            );
+            assert_ok!(submit_signature(BOB, "bc", "bcd"));
+
+            // Relaxing the policy takes effect immediately
+            assert_ok!(AttendanceModule::set_precision(
+                RuntimeOrigin::root(),
+                0,
+                MaxGeohashLength::get()
+            ));
+            assert_ok!(submit_signature(CHARLIE, "bcd", "bcdefgh"));
+        });
+    }
+
+    #[test]
+    fn location_equal_to_the_challenge_attends_it() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(create_expiring(ALICE, "bcd", 100));
+            assert!(AllowExactMatch::get());
+
+            // The challenge cell itself, and any cell within it
+            assert_ok!(submit_signature(ALICE, "bcd", "bcd"));
+            assert_ok!(submit_signature(BOB, "bcd", "bcdefg"));
+            // A cell of the same precision next to it does not
+            assert_noop!(
+                submit_signature(CHARLIE, "bcd", "bce"),
+                Error::<Test>::InvalidGeohash
+            );
+        });
+    }
+
+    #[test]
+    fn exact_match_can_be_disallowed() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(create_expiring(ALICE, "bcd", 100));
+            AllowExactMatch::set(false);
+
+            // Only locations strictly within the challenge cell attend it
+            assert_noop!(
+                submit_signature(ALICE, "bcd", "bcd"),
+                Error::<Test>::InvalidGeohash
+            );
+            assert_ok!(submit_signature(ALICE, "bcd", "bcde"));
+
+            // Proofs must be made for a strictly more precise location too
+            let version =
+                AttendanceModule::rotate_verifying_key(BoundedVec::try_from(vec![1; 8]).unwrap());
+            let submit_proof = |precision| {
+                AttendanceModule::submission_with_proof(
+                    RuntimeOrigin::signed(BOB),
+                    default_namespace(),
+                    challenge("bcd"),
+                    version,
+                    precision,
+                    BoundedVec::try_from(vec![0xff; 32]).unwrap(),
+                )
+            };
+            assert_noop!(submit_proof(3), Error::<Test>::InsufficientPrecision);
+            assert_noop!(submit_proof(4), Error::<Test>::InvalidProof);
+        });
+    }
+
+    #[test]
+    fn attendance_history_keeps_the_latest_submissions() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            for geohash in ["bcd", "gcp", "u4p"] {
+                assert_ok!(AttendanceModule::create_challenge(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    challenge(geohash),
+                    None,
+                    None
+                ));
+            }
+            assert!(AttendanceModule::attendance_history(&ALICE).is_empty());
+
+            assert_ok!(submit_signature(ALICE, "bcd", "bcdefg"));
+            assert_ok!(submit_signature(ALICE, "gcp", "gcpvj0"));
+            assert_eq!(
+                AttendanceModule::attendance_history(&ALICE),
+                vec![(vec![], b"bcd".to_vec()), (vec![], b"gcp".to_vec())]
+            );
+
+            let truncated = || {
+                System::events().iter().any(|record| {
+                    matches!(
+                        record.event,
+                        RuntimeEvent::AttendanceModule(Event::AttendanceHistoryTruncated { .. })
+                    )
+                })
+            };
+            assert!(!truncated());
+
+            // Once `MaxHistory` are held the oldest is dropped
+            assert_eq!(MaxHistory::get(), 2);
+            assert_ok!(submit_signature(ALICE, "u4p", "u4pruy"));
+            assert_eq!(
+                AttendanceHistory::<Test>::get(ALICE).into_inner(),
+                vec![
+                    (default_namespace(), challenge("gcp")),
+                    (default_namespace(), challenge("u4p"))
+                ]
+            );
+            System::assert_has_event(
+                Event::AttendanceHistoryTruncated {
+                    who: ALICE,
+                    namespace: default_namespace(),
+                    challenge: challenge("bcd"),
+                }
+                .into(),
+            );
+
+            // Histories are per account, and rejected submissions are not recorded
+            assert_ok!(submit_signature(BOB, "u4p", "u4pruy"));
+            assert_noop!(
+                submit_signature(BOB, "u4p", "u4pruy"),
+                Error::<Test>::AlreadySubmitted
+            );
+            assert_eq!(
+                AttendanceModule::attendance_history(&BOB),
+                vec![(vec![], b"u4p".to_vec())]
+            );
+        });
+    }
+
+    #[test]
+    fn submissions_expire_after_the_submission_ttl() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(create_expiring(ALICE, "bcd", 30));
+            // Without a TTL submissions are permanent and their block is not recorded
+            assert_ok!(submit_signature(BOB, "bcd", "bcdefg"));
+            assert_eq!(SubmittedAt::<Test>::get(key("bcd"), BOB), None);
+
+            SubmissionTtl::set(10);
+            assert_ok!(submit_signature(ALICE, "bcd", "bcdefg"));
+            assert_eq!(SubmittedAt::<Test>::get(key("bcd"), ALICE), Some(1));
+
+            // The last block within the TTL
+            System::set_block_number(10);
+            assert_noop!(
+                submit_signature(ALICE, "bcd", "bcdefg"),
+                Error::<Test>::AlreadySubmitted
+            );
+            // The first block after it, while a submission from before the TTL never expires
+            System::set_block_number(11);
+            assert_noop!(
+                submit_signature(BOB, "bcd", "bcdefg"),
+                Error::<Test>::AlreadySubmitted
+            );
+            assert_ok!(submit_signature(ALICE, "bcd", "bcdefg"));
+            assert_eq!(SubmittedAt::<Test>::get(key("bcd"), ALICE), Some(11));
+            assert_eq!(SubmissionCount::<Test>::get(key("bcd")), 3);
+            assert_noop!(
+                submit_signature(ALICE, "bcd", "bcdefg"),
+                Error::<Test>::AlreadySubmitted
+            );
+
+            // Pruning the challenge archives the submission blocks with the submissions
+            System::set_block_number(31);
+            AttendanceModule::on_initialize(31);
+            assert!(!Submissions::<Test>::contains_key(key("bcd"), ALICE));
+            assert_eq!(SubmittedAt::<Test>::get(key("bcd"), ALICE), None);
+        });
+    }
+
+    #[test]
+    fn oracle_set_is_bounded_by_max_oracles() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            // Dummy accepts any public key bytes
+            let key = |b: u8| BoundedVec::try_from(vec![b; 32]).expect("public key");
+
+            for b in 0..MaxOracles::get() as u8 {
+                assert_ok!(AttendanceModule::add_oracle(RuntimeOrigin::root(), key(b), None));
+            }
+            assert_eq!(Oracles::<Test>::count(), MaxOracles::get());
+            assert_noop!(
+                AttendanceModule::add_oracle(RuntimeOrigin::root(), key(0), None),
+                Error::<Test>::OracleAlreadyAdded
+            );
+            assert_noop!(
+                AttendanceModule::add_oracle(RuntimeOrigin::root(), key(9), None),
+                Error::<Test>::TooManyOracles
+            );
+
+            assert_ok!(AttendanceModule::remove_oracle(RuntimeOrigin::root(), key(0)));
+            System::assert_last_event(Event::OracleRemoved { public_key: key(0) }.into());
+            assert_noop!(
+                AttendanceModule::remove_oracle(RuntimeOrigin::root(), key(0)),
+                Error::<Test>::OracleNotFound
+            );
+            assert_ok!(AttendanceModule::add_oracle(RuntimeOrigin::root(), key(9), None));
+            assert_eq!(Oracles::<Test>::count(), MaxOracles::get());
+
+            assert_noop!(
+                AttendanceModule::add_oracle(RuntimeOrigin::signed(ALICE), key(10), None),
+                sp_runtime::DispatchError::BadOrigin
+            );
+        });
+    }
+
+    #[test]
+    fn geohash_neighbors_surround_the_challenge() {
+        let neighbors = AttendanceModule::geohash_neighbors(b"gcpv");
+        let expected = ["gcpy", "u10n", "u10j", "u10h", "gcpu", "gcps", "gcpt", "gcpw"];
+        assert_eq!(neighbors.len(), 8);
+        assert!(neighbors.iter().zip(expected).all(|(n, e)| n == e.as_bytes()));
+        // Every neighbour is a valid challenge of the same precision
+        assert!(neighbors
+            .into_iter()
+            .all(|n| n.len() == 4 && AttendanceModule::valid_geohash(&n.try_into().unwrap())));
+        assert!(AttendanceModule::geohash_neighbors(b"gcpa").is_empty());
+    }
+
+    #[test]
+    fn oracles_can_be_labelled() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let key = |b: u8| BoundedVec::try_from(vec![b; 32]).expect("public key");
+            let label = |l: &[u8]| Some(BoundedVec::try_from(l.to_vec()).expect("label"));
+
+            assert_ok!(AttendanceModule::add_oracle(
+                RuntimeOrigin::root(),
+                key(1),
+                label(b"london-gps")
+            ));
+            System::assert_last_event(
+                Event::OracleAdded {
+                    public_key: key(1),
+                    label: label(b"london-gps"),
+                }
+                .into(),
+            );
+            assert_eq!(Oracles::<Test>::get(key(1)), Some(label(b"london-gps")));
+            assert_eq!(AttendanceModule::oracle_by_label(b"london-gps"), Some(key(1)));
+            assert_eq!(AttendanceModule::oracle_by_label(b"paris-gps"), None);
+
+            // Labels name a single oracle, unlabelled oracles are still allowed
+            assert_noop!(
+                AttendanceModule::add_oracle(RuntimeOrigin::root(), key(2), label(b"london-gps")),
+                Error::<Test>::OracleLabelInUse
+            );
+            assert_ok!(AttendanceModule::add_oracle(RuntimeOrigin::root(), key(2), None));
+            System::assert_last_event(
+                Event::OracleAdded {
+                    public_key: key(2),
+                    label: None,
+                }
+                .into(),
+            );
+
+            // A removed oracle's label can be reused
+            assert_ok!(AttendanceModule::remove_oracle(RuntimeOrigin::root(), key(1)));
+            assert_eq!(AttendanceModule::oracle_by_label(b"london-gps"), None);
+            assert_ok!(AttendanceModule::add_oracle(
+                RuntimeOrigin::root(),
+                key(3),
+                label(b"london-gps")
+            ));
+            assert_eq!(AttendanceModule::oracle_by_label(b"london-gps"), Some(key(3)));
+        });
+    }
+
+    #[test]
+    fn would_accept_signature_dry_runs_a_submission() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(create_expiring(ALICE, "bcd", 10));
+            let signature = Dummy::default().to_raw_vec();
+            let would_accept = |who: u64, challenge: &[u8], location: &[u8]| {
+                AttendanceModule::would_accept_signature(&who, b"", challenge, location, &signature)
+            };
+
+            // Nothing is recorded or minted by a dry run
+            assert_storage_noop!(assert_ok!(would_accept(BOB, b"bcd", b"bcdefg")));
+            assert_eq!(
+                would_accept(BOB, b"bcd", b"xyzxyz"),
+                Err(Error::<Test>::InvalidGeohash.into())
+            );
+            assert_eq!(
+                would_accept(BOB, b"bcf", b"bcfefg"),
+                Err(Error::<Test>::ChallengeNotFound.into())
+            );
+            assert_eq!(
+                would_accept(BOB, b"bcd", &[b'b'; 13]),
+                Err(Error::<Test>::GeohashTooLong.into())
+            );
+
+            // The submission fails as the dry run predicts
+            assert_ok!(submit_signature(BOB, "bcd", "bcdefg"));
+            assert_eq!(
+                would_accept(BOB, b"bcd", b"bcdefg"),
+                Err(Error::<Test>::AlreadySubmitted.into())
+            );
+            assert_ok!(would_accept(CHARLIE, b"bcd", b"bcdefg"));
+        });
+    }
+
+    thread_local! {
+        /// The messages logged under the pallet's target on the current test's thread
+        static LOGGED: std::cell::RefCell<Vec<String>> = Default::default();
+    }
+
+    /// Records the pallet's log messages per thread, so tests running in parallel only see
+    /// their own
+    struct RecordingLogger;
+
+    impl log::Log for RecordingLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == "runtime::attendance"
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                LOGGED.with(|logged| logged.borrow_mut().push(record.args().to_string()));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn rejected_submissions_log_their_reason() {
+        static LOGGER: RecordingLogger = RecordingLogger;
+        // Another test may have installed the logger already
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Debug);
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            set_oracle();
+            assert_ok!(create_expiring(ALICE, "bcd", 10));
+
+            assert_noop!(
+                submit_signature(BOB, "bcd", "xyzxyz"),
+                Error::<Test>::InvalidGeohash
+            );
+            assert_ok!(submit_signature(BOB, "bcd", "bcdefg"));
+
+            // Only the rejection is logged, with its reason but not the location
+            let logged = LOGGED.with(|logged| logged.take());
+            assert_eq!(logged.len(), 1);
+            assert!(logged[0].starts_with("submission_with_signature rejected:"));
+            assert!(logged[0].contains("InvalidGeohash"));
+            assert!(!logged[0].contains("xyzxyz"));
+        });
+    }
+
+    mod ed25519_signatures {
+        use super::{default_namespace, ALICE};
+        use crate::mock::{AttestationThreshold, DomainTag, MaxGeohashLength};
+        use crate::{mock_ed25519::*, Error, Event, Submissions, PAYLOAD_VERSION};
+        use frame_support::{
+            assert_noop, assert_ok,
+            dispatch::DispatchResult,
+            traits::{ConstU32, Get},
+        };
+        use sp_core::{crypto::ByteArray, ed25519, Hasher, Pair};
+        use sp_runtime::{traits::BlakeTwo256, BoundedVec};
+
+        fn oracle() -> ed25519::Pair {
+            ed25519::Pair::from_seed(&[7u8; 32])
+        }
+
+        fn geohash(geohash: &'static str) -> BoundedVec<u8, MaxGeohashLength> {
+            BoundedVec::try_from(geohash.as_bytes().to_vec()).expect("geohash")
+        }
+
+        fn sign(
+            pair: &ed25519::Pair,
+            challenge: &'static str,
+            location: &'static str,
+        ) -> ed25519::Signature {
+            let payload =
+                AttendanceModule::signing_payload(challenge.as_bytes(), location.as_bytes());
+            pair.sign(BlakeTwo256::hash(&payload).as_ref())
+        }
+
+        fn setup() {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::set_oracle_public_key(
+                RuntimeOrigin::root(),
+                oracle().public().to_raw_vec().try_into().expect("public key"),
+                false
+            ));
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                geohash("bcd"),
+                None,
+                None
+            ));
+        }
+
+        fn submit(
+            challenge: &'static str,
+            location: &'static str,
+            signature: ed25519::Signature,
+        ) -> DispatchResult {
+            AttendanceModule::submission_with_signature(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                geohash(challenge),
+                geohash(location),
+                PAYLOAD_VERSION,
+                signature.to_raw_vec().try_into().expect("signature"),
+            )
+        }
+
+        #[test]
+        fn valid_signature_is_accepted() {
+            new_test_ext().execute_with(|| {
+                setup();
+                assert_ok!(submit("bcd", "bcdefg", sign(&oracle(), "bcd", "bcdefg")));
+                assert!(Submissions::<Test>::contains_key(
+                    (default_namespace(), geohash("bcd")),
+                    ALICE
+                ));
+            });
+        }
+
+        #[test]
+        fn oracle_public_key_of_the_wrong_length_is_rejected() {
+            new_test_ext().execute_with(|| {
+                setup();
+                for length in [0, 31] {
+                    assert_noop!(
+                        AttendanceModule::set_oracle_public_key(
+                            RuntimeOrigin::root(),
+                            vec![7; length].try_into().expect("public key"),
+                            false
+                        ),
+                        Error::<Test>::InvalidPublicKey
+                    );
+                }
+                // The valid key set before is kept
+                assert_ok!(submit("bcd", "bcdefg", sign(&oracle(), "bcd", "bcdefg")));
+            });
+        }
+
+        #[test]
+        fn signature_by_another_key_is_rejected() {
+            new_test_ext().execute_with(|| {
+                setup();
+                let impostor = ed25519::Pair::from_seed(&[8u8; 32]);
+                assert_noop!(
+                    submit("bcd", "bcdefg", sign(&impostor, "bcd", "bcdefg")),
+                    Error::<Test>::InvalidSignature
+                );
+            });
+        }
+
+        #[test]
+        fn signature_over_another_location_is_rejected() {
+            new_test_ext().execute_with(|| {
+                setup();
+                assert_noop!(
+                    submit("bcd", "bcdefg", sign(&oracle(), "bcd", "bcdefh")),
+                    Error::<Test>::InvalidSignature
+                );
+            });
+        }
+
+        #[test]
+        fn signature_for_another_challenge_is_rejected() {
+            new_test_ext().execute_with(|| {
+                setup();
+                assert_ok!(AttendanceModule::create_challenge(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    geohash("bc"),
+                    None,
+                    None
+                ));
+
+                // The location is inside both challenges, but the signature binds "bc"
+                let signature = sign(&oracle(), "bc", "bcdefg");
+                assert_noop!(
+                    submit("bcd", "bcdefg", signature.clone()),
+                    Error::<Test>::InvalidSignature
+                );
+                assert_ok!(submit("bc", "bcdefg", signature));
+            });
+        }
+
+        #[test]
+        fn signature_not_bound_to_a_challenge_is_rejected() {
+            new_test_ext().execute_with(|| {
+                setup();
+                let mut payload = vec![PAYLOAD_VERSION];
+                payload.extend_from_slice(DomainTag::get());
+                payload.extend_from_slice(b"bcdefg");
+                let signature = oracle().sign(BlakeTwo256::hash(&payload).as_ref());
+                assert_noop!(
+                    submit("bcd", "bcdefg", signature),
+                    Error::<Test>::InvalidSignature
+                );
+            });
+        }
+
+        #[test]
+        fn signature_with_different_domain_tag_fails() {
+            new_test_ext().execute_with(|| {
+                setup();
+                // The payload `signing_payload` builds, under another protocol's tag
+                let mut payload = vec![PAYLOAD_VERSION];
+                payload.extend_from_slice(b"another-protocol");
+                payload.extend_from_slice(&3u32.to_le_bytes());
+                payload.extend_from_slice(b"bcdbcdefg");
+                let signature = oracle().sign(BlakeTwo256::hash(&payload).as_ref());
+                assert_noop!(
+                    submit("bcd", "bcdefg", signature),
+                    Error::<Test>::InvalidSignature
+                );
+                assert_ok!(submit("bcd", "bcdefg", sign(&oracle(), "bcd", "bcdefg")));
+            });
+        }
+
+        #[test]
+        fn signature_by_an_added_oracle_is_accepted() {
+            new_test_ext().execute_with(|| {
+                setup();
+                let second = ed25519::Pair::from_seed(&[9u8; 32]);
+                assert_noop!(
+                    submit("bcd", "bcdefg", sign(&second, "bcd", "bcdefg")),
+                    Error::<Test>::InvalidSignature
+                );
+
+                assert_ok!(AttendanceModule::add_oracle(
+                    RuntimeOrigin::root(),
+                    second.public().to_raw_vec().try_into().expect("public key"),
+                    None
+                ));
+                assert_ok!(submit("bcd", "bcdefg", sign(&second, "bcd", "bcdefg")));
+            });
+        }
+
+        #[test]
+        fn payload_version_must_match() {
+            new_test_ext().execute_with(|| {
+                setup();
+                let signature = sign(&oracle(), "bcd", "bcdefg");
+                let submit_version = |version: u8| {
+                    AttendanceModule::submission_with_signature(
+                        RuntimeOrigin::signed(ALICE),
+                        default_namespace(),
+                        geohash("bcd"),
+                        geohash("bcdefg"),
+                        version,
+                        signature.to_raw_vec().try_into().expect("signature"),
+                    )
+                };
+                assert_noop!(
+                    submit_version(PAYLOAD_VERSION + 1),
+                    Error::<Test>::UnsupportedPayloadVersion
+                );
+                assert_ok!(submit_version(PAYLOAD_VERSION));
+
+                // The version byte is covered by the signature
+                let payload = AttendanceModule::signing_payload(b"bcd", b"bcdefg");
+                assert_eq!(payload[0], PAYLOAD_VERSION);
+            });
+        }
+
+        fn attestation(
+            pair: &ed25519::Pair,
+            location: &'static str,
+        ) -> (BoundedVec<u8, ConstU32<32>>, BoundedVec<u8, ConstU32<64>>) {
+            (
+                pair.public().to_raw_vec().try_into().expect("public key"),
+                sign(pair, "bcd", location)
+                    .to_raw_vec()
+                    .try_into()
+                    .expect("signature"),
+            )
+        }
+
+        fn submit_multisig(
+            location: &'static str,
+            signatures: Vec<(BoundedVec<u8, ConstU32<32>>, BoundedVec<u8, ConstU32<64>>)>,
+        ) -> DispatchResult {
+            AttendanceModule::submission_with_multisig(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                geohash("bcd"),
+                geohash(location),
+                PAYLOAD_VERSION,
+                signatures.try_into().expect("signatures"),
+            )
+        }
+
+        #[test]
+        fn multisig_requires_the_attestation_threshold() {
+            new_test_ext().execute_with(|| {
+                setup();
+                let second = ed25519::Pair::from_seed(&[9u8; 32]);
+                let impostor = ed25519::Pair::from_seed(&[10u8; 32]);
+                assert_ok!(AttendanceModule::add_oracle(
+                    RuntimeOrigin::root(),
+                    second.public().to_raw_vec().try_into().expect("public key"),
+                    None
+                ));
+                assert_eq!(AttestationThreshold::get(), 2);
+
+                let attest = |pair: &ed25519::Pair| attestation(pair, "bcdefg");
+
+                // One below the threshold
+                assert_noop!(
+                    submit_multisig("bcdefg", vec![attest(&oracle())]),
+                    Error::<Test>::InsufficientAttestations
+                );
+                // Untrusted and repeated signers are not counted
+                assert_noop!(
+                    submit_multisig("bcdefg", vec![attest(&oracle()), attest(&impostor)]),
+                    Error::<Test>::InsufficientAttestations
+                );
+                assert_noop!(
+                    submit_multisig("bcdefg", vec![attest(&oracle()), attest(&oracle())]),
+                    Error::<Test>::InsufficientAttestations
+                );
+
+                // Exactly at the threshold
+                assert_ok!(submit_multisig(
+                    "bcdefg",
+                    vec![attest(&oracle()), attest(&impostor), attest(&second)]
+                ));
+                assert!(Submissions::<Test>::contains_key(
+                    (default_namespace(), geohash("bcd")),
+                    ALICE
+                ));
+                System::assert_last_event(
+                    Event::MultisigSubmissionAccepted {
+                        who: ALICE,
+                        namespace: default_namespace(),
+                        challenge: geohash("bcd"),
+                        attestations: 2,
+                    }
+                    .into(),
+                );
+            });
+        }
+
+        #[test]
+        fn multisig_signatures_over_another_location_are_not_counted() {
+            new_test_ext().execute_with(|| {
+                setup();
+                let second = ed25519::Pair::from_seed(&[9u8; 32]);
+                assert_ok!(AttendanceModule::add_oracle(
+                    RuntimeOrigin::root(),
+                    second.public().to_raw_vec().try_into().expect("public key"),
+                    None
+                ));
+
+                assert_noop!(
+                    submit_multisig(
+                        "bcdefg",
+                        vec![attestation(&oracle(), "bcdefg"), attestation(&second, "bcdefh")]
+                    ),
+                    Error::<Test>::InsufficientAttestations
+                );
+            });
+        }
+
+        #[test]
+        fn attestation_signed_by_the_oracle_is_accepted() {
+            // The oracle's own payload, Blake2-256 hasher and Ed25519 signer, against a
+            // runtime whose `PayloadHasher` is Blake2-256
+            use oracle::{
+                blake2_256::Blake2_256, ed25519::Ed25519, signing_payload, Hasher as _,
+                Signer as _, DOMAIN_TAG,
+            };
+
+            new_test_ext().execute_with(|| {
+                System::set_block_number(1);
+                let (secret_key, public_key) = Ed25519::generate_key();
+                assert_ok!(AttendanceModule::set_oracle_public_key(
+                    RuntimeOrigin::root(),
+                    public_key.as_bytes().to_vec().try_into().expect("public key"),
+                    false
+                ));
+                assert_ok!(AttendanceModule::create_challenge(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    geohash("bcd"),
+                    None,
+                    None
+                ));
+
+                assert_eq!(DOMAIN_TAG, DomainTag::get());
+                let digest = Blake2_256::hash(signing_payload(
+                    DOMAIN_TAG,
+                    Some(b"bcd".as_slice()),
+                    b"bcdefg",
+                ));
+                assert_eq!(
+                    digest.as_bytes(),
+                    BlakeTwo256::hash(&AttendanceModule::signing_payload(b"bcd", b"bcdefg"))
+                        .as_bytes()
+                );
+
+                let signature = Ed25519::sign(digest, secret_key).expect("signed");
+                assert_ok!(AttendanceModule::submission_with_signature(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    geohash("bcd"),
+                    geohash("bcdefg"),
+                    oracle::PAYLOAD_VERSION,
+                    signature.try_into().expect("signature"),
+                ));
+                assert!(Submissions::<Test>::contains_key(
+                    (default_namespace(), geohash("bcd")),
+                    ALICE
+                ));
+            });
+        }
+
+        #[test]
+        fn malformed_signature_is_rejected() {
+            new_test_ext().execute_with(|| {
+                setup();
+                assert_noop!(
+                    AttendanceModule::submission_with_signature(
+                        RuntimeOrigin::signed(ALICE),
+                        default_namespace(),
+                        geohash("bcd"),
+                        geohash("bcdefg"),
+                        PAYLOAD_VERSION,
+                        vec![0u8; 10].try_into().expect("signature"),
+                    ),
+                    Error::<Test>::InvalidSignature
+                );
+            });
+        }
+    }
+
+    mod asset_rewards {
+        use super::{default_namespace, ALICE};
+        use crate::mock::MaxGeohashLength;
+        use crate::{mock_assets::*, PAYLOAD_VERSION};
+        use frame_support::{assert_noop, assert_ok, dispatch::DispatchResult};
+        use sp_core::{crypto::Dummy, Pair};
+        use sp_runtime::{BoundedVec, TokenError};
+
+        fn geohash(geohash: &'static str) -> BoundedVec<u8, MaxGeohashLength> {
+            BoundedVec::try_from(geohash.as_bytes().to_vec()).expect("geohash")
+        }
+
+        fn submit() -> DispatchResult {
+            AttendanceModule::submission_with_signature(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                geohash("bcd"),
+                geohash("bcdefg"),
+                PAYLOAD_VERSION,
+                Dummy::default().to_raw_vec().try_into().expect("signature"),
+            )
+        }
+
+        fn setup() {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::set_oracle_public_key(
+                RuntimeOrigin::root(),
+                Dummy::default().to_raw_vec().try_into().expect("public key"),
+                false
+            ));
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                geohash("bcd"),
+                None,
+                None
+            ));
+        }
+
+        #[test]
+        fn submission_mints_the_reward_asset() {
+            new_test_ext().execute_with(|| {
+                setup();
+                assert_ok!(Assets::force_create(
+                    RuntimeOrigin::root(),
+                    RewardAsset::get(),
+                    ALICE,
+                    true,
+                    1
+                ));
+                assert_ok!(Assets::force_create(RuntimeOrigin::root(), 8, ALICE, true, 1));
+
+                assert_ok!(submit());
+                assert_eq!(Assets::balance(RewardAsset::get(), ALICE), RewardAmount::get());
+                assert_eq!(Assets::balance(8, ALICE), 0);
+            });
+        }
+
+        #[test]
+        fn submission_fails_when_the_reward_cannot_be_minted() {
+            new_test_ext().execute_with(|| {
+                setup();
+                // The reward asset was never created
+                assert_noop!(submit(), TokenError::UnknownAsset);
+            });
+        }
+    }
+
+    mod mock_verifier {
+        use super::{default_namespace, ALICE, BOB};
+        use crate::mock::MaxGeohashLength;
+        use crate::{mock_verifier::*, Error, Submissions};
+        use frame_support::{assert_noop, assert_ok};
+        use sp_runtime::BoundedVec;
+
+        fn geohash(geohash: &'static str) -> BoundedVec<u8, MaxGeohashLength> {
+            BoundedVec::try_from(geohash.as_bytes().to_vec()).expect("geohash")
+        }
+
+        fn submit(who: u64, proof: &[u8]) -> frame_support::dispatch::DispatchResult {
+            AttendanceModule::submission_with_proof(
+                RuntimeOrigin::signed(who),
+                default_namespace(),
+                geohash("bcd"),
+                0,
+                6,
+                BoundedVec::try_from(proof.to_vec()).expect("proof"),
+            )
+        }
+
+        fn setup() {
+            System::set_block_number(1);
+            assert_ok!(AttendanceModule::set_verifying_key(
+                RuntimeOrigin::root(),
+                BoundedVec::try_from(b"key".to_vec()).expect("verifying key")
+            ));
+            assert_ok!(AttendanceModule::create_challenge(
+                RuntimeOrigin::signed(ALICE),
+                default_namespace(),
+                geohash("bcd"),
+                None,
+                None
+            ));
+        }
+
+        #[test]
+        fn proofs_are_checked_by_the_configured_verifier() {
+            new_test_ext().execute_with(|| {
+                setup();
+                assert_ok!(submit(ALICE, VALID_PROOF));
+                assert!(Submissions::<Test>::get(
+                    AttendanceModule::challenge_key(&default_namespace(), &geohash("bcd")),
+                    ALICE
+                ));
+
+                // The key and the challenge bytes, then the precision, reach the verifier
+                let inputs =
+                    [b'b', b'c', b'd', 6].map(|i| AttendanceModule::integer_input(i as u64));
+                assert_eq!(verified(), vec![(b"key".to_vec(), inputs.to_vec())]);
+            });
+        }
+
+        #[test]
+        fn rejected_and_malformed_proofs_fail() {
+            new_test_ext().execute_with(|| {
+                setup();
+                assert_noop!(submit(BOB, b"forged"), Error::<Test>::InvalidProof);
+                assert_noop!(submit(BOB, MALFORMED_PROOF), Error::<Test>::InvalidProof);
+            });
+        }
+
+        #[test]
+        fn commitments_must_be_public_inputs_of_the_verifier() {
+            new_test_ext().execute_with(|| {
+                let mut commitment = [1u8; 32];
+                assert_noop!(
+                    AttendanceModule::create_committed_challenge(
+                        RuntimeOrigin::signed(ALICE),
+                        default_namespace(),
+                        commitment
+                    ),
+                    Error::<Test>::InvalidCommitment
+                );
+                commitment[31] = 0;
+                assert_ok!(AttendanceModule::create_committed_challenge(
+                    RuntimeOrigin::signed(ALICE),
+                    default_namespace(),
+                    commitment
+                ));
+            });
+        }
+    }
 }
@@ -1,29 +1,10 @@
-
-//! Autogenerated weights for pallet_template
+//! Weights for pallet_attendance
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2023-04-06, STEPS: `50`, REPEAT: `20`, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! WORST CASE MAP SIZE: `1000000`
-//! HOSTNAME: `Alexs-MacBook-Pro-2.local`, CPU: `<UNKNOWN>`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
-
-// Executed Command:
-// ../../target/release/node-template
-// benchmark
-// pallet
-// --chain
-// dev
-// --pallet
-// pallet_attendance
-// --extrinsic
-// *
-// --steps=50
-// --repeat=20
-// --wasm-execution=compiled
-// --output
-// pallets/attendance/src/weights.rs
-// --template
-// ../../.maintain/frame-weight-template.hbs
+//! Hand estimates, not benchmark output: no run of the benchmarks in `benchmarking.rs` has been
+//! recorded yet. Each weight notes the basis of its constants. Ed25519 verification runs as a
+//! host function, while Groth16 verification over BN254 runs in the runtime. Replace this file
+//! with the output of `solochain-template-node benchmark pallet --pallet pallet_attendance` on
+//! reference hardware before relying on it for fees.
 
 #![cfg_attr(rustfmt, rustfmt_skip)]
 #![allow(unused_parens)]
@@ -34,31 +15,60 @@ use core::marker::PhantomData;
 
 /// Weight functions needed for pallet_attendance.
 pub trait WeightInfo {
-	fn do_something() -> Weight;
-	fn cause_error() -> Weight;
+	fn submission_with_signature() -> Weight;
+	fn submission_with_proof() -> Weight;
+	fn set_verifying_key(l: u32, ) -> Weight;
 }
 
-/// Weights for pallet_attendance using the Substrate node and recommended hardware.
+/// Estimated weights for pallet_attendance, see the module documentation.
 pub struct SubstrateWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
-	/// Storage: AttendanceModule Something (r:0 w:1)
-	/// Proof: AttendanceModule Something (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
-	fn do_something() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `0`
-		//  Estimated: `0`
-		// Minimum execution time: 8_000_000 picoseconds.
-		Weight::from_parts(9_000_000, 0)
-			.saturating_add(T::DbWeight::get().writes(1_u64))
+	/// Storage: AttendanceModule Paused (r:1 w:0)
+	/// Storage: AttendanceModule Challenges (r:1 w:0)
+	/// Storage: AttendanceModule Submissions (r:1 w:1)
+	/// Storage: AttendanceModule SubmittedAt (r:1 w:1)
+	/// Storage: AttendanceModule MinPrecision (r:1 w:0)
+	/// Storage: AttendanceModule MaxPrecision (r:1 w:0)
+	/// Storage: AttendanceModule AcceptedPrecisions (r:1 w:0)
+	/// Storage: AttendanceModule Oracle (r:1 w:0)
+	/// Storage: AttendanceModule Oracles (r:8 w:0)
+	/// Storage: AttendanceModule SubmissionCount (r:1 w:1)
+	/// Storage: AttendanceModule AttendanceHistory (r:1 w:1)
+	fn submission_with_signature() -> Weight {
+		// Hand estimate: up to 9 Ed25519 verifications, the primary oracle and `MaxOracles`
+		// added ones, at about 50us each as a host function, rounded up to 440us. The proof
+		// size is the sum of the maximum encoded lengths of the entries read.
+		Weight::from_parts(440_000_000, 5192)
+			.saturating_add(T::DbWeight::get().reads(18_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
 	}
-	/// Storage: AttendanceModule Something (r:1 w:1)
-	/// Proof: AttendanceModule Something (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
-	fn cause_error() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `32`
-		//  Estimated: `1489`
-		// Minimum execution time: 6_000_000 picoseconds.
-		Weight::from_parts(6_000_000, 1489)
+	/// Storage: AttendanceModule Paused (r:1 w:0)
+	/// Storage: AttendanceModule Challenges (r:1 w:0)
+	/// Storage: AttendanceModule MinPrecision (r:1 w:0)
+	/// Storage: AttendanceModule MaxPrecision (r:1 w:0)
+	/// Storage: AttendanceModule AcceptedPrecisions (r:1 w:0)
+	/// Storage: AttendanceModule ProofVerifyingKey (r:1 w:0)
+	/// Storage: AttendanceModule SubmissionCount (r:1 w:1)
+	/// Storage: AttendanceModule Submissions (r:0 w:1)
+	/// Storage: AttendanceModule SubmittedAt (r:0 w:1)
+	/// Storage: AttendanceModule AttendanceHistory (r:1 w:1)
+	fn submission_with_proof() -> Weight {
+		// Hand estimate: a Groth16 verification over BN254 executed in Wasm, three pairings
+		// and a multi-scalar multiplication over the public inputs, taken as 42ms. The proof
+		// size is the sum of the maximum encoded lengths of the entries read, the verifying key
+		// included.
+		Weight::from_parts(42_000_000_000, 7658)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: AttendanceModule ProofVerifyingKey (r:1 w:1)
+	/// The range of component `l` is `[520, 2048]` in the benchmark.
+	fn set_verifying_key(l: u32, ) -> Weight {
+		// Hand estimate: 17us to read and rewrite the versioned keys, plus 1.1ns per byte to
+		// copy and store the key. Decoding the key's points to validate it is not included.
+		// The proof size is the maximum encoded length of `ProofVerifyingKey`.
+		Weight::from_parts(17_000_000, 7652)
+			.saturating_add(Weight::from_parts(1_100, 0).saturating_mul(l.into()))
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
@@ -66,24 +76,52 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 
 // For backwards compatibility and tests
 impl WeightInfo for () {
-	/// Storage: AttendanceModule Something (r:0 w:1)
-	/// Proof: AttendanceModule Something (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
-	fn do_something() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `0`
-		//  Estimated: `0`
-		// Minimum execution time: 8_000_000 picoseconds.
-		Weight::from_parts(9_000_000, 0)
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	/// Storage: AttendanceModule Paused (r:1 w:0)
+	/// Storage: AttendanceModule Challenges (r:1 w:0)
+	/// Storage: AttendanceModule Submissions (r:1 w:1)
+	/// Storage: AttendanceModule SubmittedAt (r:1 w:1)
+	/// Storage: AttendanceModule MinPrecision (r:1 w:0)
+	/// Storage: AttendanceModule MaxPrecision (r:1 w:0)
+	/// Storage: AttendanceModule AcceptedPrecisions (r:1 w:0)
+	/// Storage: AttendanceModule Oracle (r:1 w:0)
+	/// Storage: AttendanceModule Oracles (r:8 w:0)
+	/// Storage: AttendanceModule SubmissionCount (r:1 w:1)
+	/// Storage: AttendanceModule AttendanceHistory (r:1 w:1)
+	fn submission_with_signature() -> Weight {
+		// Hand estimate: up to 9 Ed25519 verifications, the primary oracle and `MaxOracles`
+		// added ones, at about 50us each as a host function, rounded up to 440us. The proof
+		// size is the sum of the maximum encoded lengths of the entries read.
+		Weight::from_parts(440_000_000, 5192)
+			.saturating_add(RocksDbWeight::get().reads(18_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	/// Storage: AttendanceModule Paused (r:1 w:0)
+	/// Storage: AttendanceModule Challenges (r:1 w:0)
+	/// Storage: AttendanceModule MinPrecision (r:1 w:0)
+	/// Storage: AttendanceModule MaxPrecision (r:1 w:0)
+	/// Storage: AttendanceModule AcceptedPrecisions (r:1 w:0)
+	/// Storage: AttendanceModule ProofVerifyingKey (r:1 w:0)
+	/// Storage: AttendanceModule SubmissionCount (r:1 w:1)
+	/// Storage: AttendanceModule Submissions (r:0 w:1)
+	/// Storage: AttendanceModule SubmittedAt (r:0 w:1)
+	/// Storage: AttendanceModule AttendanceHistory (r:1 w:1)
+	fn submission_with_proof() -> Weight {
+		// Hand estimate: a Groth16 verification over BN254 executed in Wasm, three pairings
+		// and a multi-scalar multiplication over the public inputs, taken as 42ms. The proof
+		// size is the sum of the maximum encoded lengths of the entries read, the verifying key
+		// included.
+		Weight::from_parts(42_000_000_000, 7658)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
 	}
-	/// Storage: AttendanceModule Something (r:1 w:1)
-	/// Proof: AttendanceModule Something (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
-	fn cause_error() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `32`
-		//  Estimated: `1489`
-		// Minimum execution time: 6_000_000 picoseconds.
-		Weight::from_parts(6_000_000, 1489)
+	/// Storage: AttendanceModule ProofVerifyingKey (r:1 w:1)
+	/// The range of component `l` is `[520, 2048]` in the benchmark.
+	fn set_verifying_key(l: u32, ) -> Weight {
+		// Hand estimate: 17us to read and rewrite the versioned keys, plus 1.1ns per byte to
+		// copy and store the key. Decoding the key's points to validate it is not included.
+		// The proof size is the maximum encoded length of `ProofVerifyingKey`.
+		Weight::from_parts(17_000_000, 7652)
+			.saturating_add(Weight::from_parts(1_100, 0).saturating_mul(l.into()))
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
@@ -42,8 +42,8 @@ use sp_version::RuntimeVersion;
 
 // Local module imports
 use super::{
-	AccountId, Aura, Balance, Block, Executive, Grandpa, InherentDataExt, Nonce, Runtime,
-	RuntimeCall, RuntimeGenesisConfig, SessionKeys, System, TransactionPayment, VERSION,
+	AccountId, AttendanceModule, Aura, Balance, Block, Executive, Grandpa, InherentDataExt, Nonce,
+	Runtime, RuntimeCall, RuntimeGenesisConfig, SessionKeys, System, TransactionPayment, VERSION,
 };
 
 impl_runtime_apis! {
@@ -170,6 +170,40 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_attendance::runtime_api::AttendanceApi<Block, AccountId> for Runtime {
+		fn attendance_history(who: AccountId) -> Vec<(Vec<u8>, Vec<u8>)> {
+			AttendanceModule::attendance_history(&who)
+		}
+
+		fn challenge_count() -> u32 {
+			AttendanceModule::challenge_count()
+		}
+
+		fn oracle_by_label(label: Vec<u8>) -> Option<Vec<u8>> {
+			AttendanceModule::oracle_by_label(&label).map(|public_key| public_key.into_inner())
+		}
+
+		fn geohash_neighbors(challenge: Vec<u8>) -> Vec<Vec<u8>> {
+			AttendanceModule::geohash_neighbors(&challenge)
+		}
+
+		fn would_accept_signature(
+			who: AccountId,
+			namespace: Vec<u8>,
+			challenge: Vec<u8>,
+			location: Vec<u8>,
+			signature: Vec<u8>,
+		) -> Result<(), sp_runtime::DispatchError> {
+			AttendanceModule::would_accept_signature(
+				&who,
+				&namespace,
+				&challenge,
+				&location,
+				&signature,
+			)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,
@@ -160,11 +160,33 @@ impl pallet_sudo::Config for Runtime {
 
 parameter_types! {
 	pub const MaxGeohashLength: u32 = 12;
+	pub const DomainTag: &'static [u8] = b"aoi-attendance";
+	pub const MaxVerifyingKeyVersions: u32 = 3;
+	pub const MaxExpiringPerBlock: u32 = 32;
+	pub const MaxOracles: u32 = 8;
+	/// An uncompressed BN254 Groth16 proof
+	pub const MaxProofLength: u32 = 256;
+	/// About 1.1km, in millionths of a degree
+	pub const MaxDistanceRadius: u64 = 10_000;
+	/// Every oracle, the primary one and `MaxOracles` added ones
+	pub const MaxOracleSigs: u32 = 9;
+	pub const AttestationThreshold: u32 = 2;
+	pub const MaxHistory: u32 = 64;
+	pub const AllowExactMatch: bool = true;
+	pub const MaxLabelLength: u32 = 32;
+	pub const MaxArchivedPerBlock: u32 = 256;
+	/// Challenges are stored under their readable geohash
+	pub const PackedChallengeKeys: bool = false;
+	pub const MaxNamespace: u32 = 32;
+	/// Submissions never expire, each account attends a challenge once
+	pub const SubmissionTtl: BlockNumber = 0;
 }
 
 pub struct NoMint<T>( PhantomData<T>);
 impl<T> Mintable<T> for NoMint<T> {
-	fn mint(_account: &T) {
+	type Balance = ();
+
+	fn mint(_account: &T, _amount: ()) {
 		
 	}
 }
@@ -175,11 +197,30 @@ impl pallet_attendance::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = pallet_attendance::weights::SubstrateWeight<Runtime>;
 	type MaxGeohashLength = MaxGeohashLength;
+	type DomainTag = DomainTag;
+	type MaxVerifyingKeyVersions = MaxVerifyingKeyVersions;
+	type MaxExpiringPerBlock = MaxExpiringPerBlock;
+	type MaxOracles = MaxOracles;
+	type MaxProofLength = MaxProofLength;
+	type MaxDistanceRadius = MaxDistanceRadius;
+	type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type MaxOracleSigs = MaxOracleSigs;
+	type AttestationThreshold = AttestationThreshold;
+	type MaxHistory = MaxHistory;
+	type AllowExactMatch = AllowExactMatch;
+	type MaxLabelLength = MaxLabelLength;
+	type MaxArchivedPerBlock = MaxArchivedPerBlock;
+	type PackedChallengeKeys = PackedChallengeKeys;
+	type MaxNamespace = MaxNamespace;
+	type SubmissionTtl = SubmissionTtl;
+	// NoMint has no balance to reward
+	type RewardAmount = ();
 	type Mint = NoMint<Self::AccountId>;
 	type PayloadHasher = sp_runtime::traits::BlakeTwo256;
 	type PublicKeyOfOracle = ed25519::Public;
 	type Signature = ed25519::Signature;
 	type Verify = ed25519::Pair;
+	type ProofVerifier = pallet_attendance::Groth16Bn254;
 }
 
 /*impl pallet_nfts::Config for Runtime {